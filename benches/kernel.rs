@@ -0,0 +1,122 @@
+//! Compares the hash rate of the kernel, which starts every hash from a midstate of the constant
+//! preimage bytes, with the baseline kernel laying out the whole preimage in every work item.
+//!
+//! Runs on any OpenCL device, e.g. POCL on the CPU for reproducible numbers:
+//!
+//! ```console
+//! cargo bench --bench kernel -- [DEVICE] [LAUNCHES]
+//! ```
+use createxcrunch::{mk_kernel_src, ConfigBuilder, RewardVariant, DEFAULT_RESULT_BUFFER_SIZE};
+use ocl::{Buffer, Device, MemFlags, OclPrm, Platform, ProQue};
+use std::time::{Duration, Instant};
+
+/// Source of the baseline kernel, generated for the config of [`config`].
+const BASELINE: &str = include_str!("kernels/baseline.cl");
+
+const INIT_CODE_HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+/// Work items of a launch, small enough for a CPU device to finish a launch in about a second.
+const WORK_SIZE: usize = 1 << 20;
+
+/// A CreateX CREATE2 search for addresses that are never found, so that every launch hashes
+/// its work items without reporting solutions.
+fn config() -> createxcrunch::Config {
+    ConfigBuilder::new()
+        .init_code_hash(INIT_CODE_HASH)
+        .reward(RewardVariant::LeadingZeros {
+            zeros_threshold: 20,
+        })
+        .work_size(WORK_SIZE)
+        .build()
+        .unwrap()
+}
+
+/// Average time of a launch of the kernel, after a first launch warming the device up. The
+/// baseline kernel takes the launch index as a `uint`, the current one as a `ulong`.
+fn launch_time<T: OclPrm + From<u32>>(
+    src: &str,
+    platform: Platform,
+    device: Device,
+    launches: u32,
+) -> ocl::Result<Duration> {
+    let ocl_pq = ProQue::builder()
+        .platform(platform)
+        .device(device)
+        .src(src)
+        .dims(WORK_SIZE)
+        .build()?;
+    let message_buffer = Buffer::builder()
+        .queue(ocl_pq.queue().clone())
+        .flags(MemFlags::new().read_only())
+        .len(4)
+        .copy_host_slice(&[1u8, 2, 3, 4])
+        .build()?;
+    let solutions_buffer = Buffer::<u64>::builder()
+        .queue(ocl_pq.queue().clone())
+        .flags(MemFlags::new().write_only())
+        .len(4 * DEFAULT_RESULT_BUFFER_SIZE)
+        .fill_val(0)
+        .build()?;
+
+    let mut elapsed = Duration::ZERO;
+    for launch in 0..=launches {
+        let nonce_buffer = Buffer::builder()
+            .queue(ocl_pq.queue().clone())
+            .flags(MemFlags::new().read_only())
+            .len(1)
+            .copy_host_slice(&[T::from(launch)])
+            .build()?;
+        let kern = ocl_pq
+            .kernel_builder("hashMessage")
+            .arg(&message_buffer)
+            .arg(&nonce_buffer)
+            .arg(&solutions_buffer)
+            .build()?;
+        let started = Instant::now();
+        unsafe { kern.enq()? };
+        ocl_pq.finish()?;
+        if launch > 0 {
+            elapsed += started.elapsed();
+        }
+    }
+    Ok(elapsed / launches)
+}
+
+fn main() -> ocl::Result<()> {
+    // cargo passes --bench to benchmarks without a harness
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+    let device = args
+        .first()
+        .map_or(0, |arg| arg.parse().expect("device index"));
+    let launches = args
+        .get(1)
+        .map_or(10, |arg| arg.parse().expect("launch count"));
+
+    let platform = Platform::default();
+    let device = Device::list_all(platform)?[device];
+    println!(
+        "{} on {}, {} launches of {} work items",
+        platform.name()?,
+        device.name()?,
+        launches,
+        WORK_SIZE
+    );
+
+    let rate = |time: Duration| WORK_SIZE as f64 / time.as_secs_f64() / 1.0E6;
+    let baseline = rate(launch_time::<u32>(BASELINE, platform, device, launches)?);
+    println!("baseline: {baseline:.2} MH/s");
+    let midstate = rate(launch_time::<u64>(
+        &mk_kernel_src(&config()),
+        platform,
+        device,
+        launches,
+    )?);
+    println!(
+        "midstate: {midstate:.2} MH/s ({:+.1}%)",
+        (midstate / baseline - 1.0) * 100.0
+    );
+    Ok(())
+}
@@ -0,0 +1,887 @@
+// Kernel source generated by mk_kernel_src for the config of benches/kernel.rs before kernels
+// started from midstates: every work item lays out the whole CREATE2 preimage byte by byte.
+// Kept as is so that the benchmark compares against the same baseline on every run.
+#define SALT_MINED_OFFSET 0
+__constant uchar SALT_TEMPLATE[32] = {0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u};
+#define GUARD_SALT()
+#define RESULT_BUFFER_SIZE 20000
+#define hasLeading(d) (!(((uint *)(d))[0] & 0xffffffffu) && !(((uint *)(d))[1] & 0xffffffffu) && !(((uint *)(d))[2] & 0xffffffffu) && !(((uint *)(d))[3] & 0xffffffffu) && !(((uint *)(d))[4] & 0xffffffffu))
+#define SUCCESS_CONDITION() hasLeading(digest)
+#define CREATE3()
+#define S2_1 158u
+#define S2_2 63u
+#define S2_3 142u
+#define S2_4 174u
+#define S2_5 73u
+#define S2_6 228u
+#define S2_7 66u
+#define S2_8 163u
+#define S2_9 35u
+#define S2_10 239u
+#define S2_11 32u
+#define S2_12 148u
+#define S2_13 242u
+#define S2_14 119u
+#define S2_15 191u
+#define S2_16 98u
+#define S2_17 117u
+#define S2_18 46u
+#define S2_19 105u
+#define S2_20 149u
+#define S2_53 17u
+#define S2_54 17u
+#define S2_55 17u
+#define S2_56 17u
+#define S2_57 17u
+#define S2_58 17u
+#define S2_59 17u
+#define S2_60 17u
+#define S2_61 17u
+#define S2_62 17u
+#define S2_63 17u
+#define S2_64 17u
+#define S2_65 17u
+#define S2_66 17u
+#define S2_67 17u
+#define S2_68 17u
+#define S2_69 17u
+#define S2_70 17u
+#define S2_71 17u
+#define S2_72 17u
+#define S2_73 17u
+#define S2_74 17u
+#define S2_75 17u
+#define S2_76 17u
+#define S2_77 17u
+#define S2_78 17u
+#define S2_79 17u
+#define S2_80 17u
+#define S2_81 17u
+#define S2_82 17u
+#define S2_83 17u
+#define S2_84 17u
+/*
+   Copyright 2018 Lip Wee Yeo Amano
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+    https://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+/**
+* Based on the following, with small tweaks and optimizations:
+* https://github.com/lwYeo/SoliditySHA3Miner/blob/master/SoliditySHA3Miner/Miner/Kernels/OpenCL/sha3KingKernel.cl
+*
+* Originally modified for OpenCL processing by lwYeo
+*
+* Original implementer: David Leon Gil
+*
+* License: CC0, attribution kindly requested. Blame taken too, but not
+* liability.
+*/
+
+/**
+ * A generalized GPU kernel template used for mining Ethereum addresses deployed 
+ * through the CreateX contract factory:
+ * https://github.com/pcaversaccio/createx
+ *
+ * This kernel is modified from two implementations:
+ * https://github.com/0age/create2crunch/blob/master/src/kernels/keccak256.cl
+ * This implementation is from create2crunch, however, that keccak256 implementation
+ * is optimized to the point that it only calculates the proper values for the 
+ * last 20 bytes (a partial keccak256). This is not sufficient for CreateX which
+ * performs multiple keccak256 hashes. However, a partial keccak256 is used for the
+ * last ones.
+ *
+ * https://github.com/Vectorized/function-selector-miner/blob/b900660837f5fac66b5837fdaa5b3f93ff1b0ad4/cpp/main.cpp
+ * This implementation provides a full keccak256 implementation (although for a
+ * different purpose).
+ *
+ * h/t https://github.com/Vectorized
+ */
+
+/******** Keccak-f[1600] (for finding efficient Ethereum addresses) ********/
+
+#define OPENCL_PLATFORM_UNKNOWN 0
+#define OPENCL_PLATFORM_AMD   2
+
+#ifndef PLATFORM
+# define PLATFORM       OPENCL_PLATFORM_UNKNOWN
+#endif
+
+#if PLATFORM == OPENCL_PLATFORM_AMD
+# pragma OPENCL EXTENSION   cl_amd_media_ops : enable
+#endif
+
+typedef union _nonce_t
+{
+  ulong   uint64_t;
+  uint    uint32_t[2];
+  uchar   uint8_t[8];
+} nonce_t;
+
+#if PLATFORM == OPENCL_PLATFORM_AMD
+static inline ulong ROL(const ulong x, const uint s)
+{
+  uint2 output;
+  uint2 x2 = as_uint2(x);
+
+  output = (s > 32u) ? amd_bitalign((x2).yx, (x2).xy, 64u - s) : amd_bitalign((x2).xy, (x2).yx, 32u - s);
+  return as_ulong(output);
+}
+#else
+#define ROL(X, S) (((X) << S) | ((X) >> (64 - S)))
+#endif
+
+
+#define THETA_(M, N, O) t = b[M] ^ ROL(b[N], 1); \
+a[O + 0] = a[O + 0] ^ t; a[O + 5] = a[O + 5] ^ t; a[O + 10] = a[O + 10] ^ t; \
+a[O + 15] = a[O + 15] ^ t; a[O + 20] = a[O + 20] ^ t;
+
+#define THETA() \
+b[0] = a[0] ^ a[5] ^ a[10] ^ a[15] ^ a[20]; \
+b[1] = a[1] ^ a[6] ^ a[11] ^ a[16] ^ a[21]; \
+b[2] = a[2] ^ a[7] ^ a[12] ^ a[17] ^ a[22]; \
+b[3] = a[3] ^ a[8] ^ a[13] ^ a[18] ^ a[23]; \
+b[4] = a[4] ^ a[9] ^ a[14] ^ a[19] ^ a[24]; \
+THETA_(4, 1, 0); THETA_(0, 2, 1); THETA_(1, 3, 2); THETA_(2, 4, 3); THETA_(3, 0, 4);
+
+#define RHO_PI_(M, N) t = b[0]; b[0] = a[M]; a[M] = ROL(t, N);
+
+#define RHO_PI() t = a[1]; b[0] = a[10]; a[10] = ROL(t, 1); \
+RHO_PI_(7, 3); RHO_PI_(11, 6); RHO_PI_(17, 10); RHO_PI_(18, 15); RHO_PI_(3, 21); RHO_PI_(5, 28); \
+RHO_PI_(16, 36); RHO_PI_(8, 45); RHO_PI_(21, 55); RHO_PI_(24, 2); RHO_PI_(4, 14); RHO_PI_(15, 27); \
+RHO_PI_(23, 41); RHO_PI_(19, 56); RHO_PI_(13, 8); RHO_PI_(12, 25); RHO_PI_(2, 43); RHO_PI_(20, 62); \
+RHO_PI_(14, 18); RHO_PI_(22, 39); RHO_PI_(9, 61); RHO_PI_(6, 20); RHO_PI_(1, 44);
+
+#define CHI_(N) \
+b[0] = a[N + 0]; b[1] = a[N + 1]; b[2] = a[N + 2]; b[3] = a[N + 3]; b[4] = a[N + 4]; \
+a[N + 0] = b[0] ^ ((~b[1]) & b[2]); \
+a[N + 1] = b[1] ^ ((~b[2]) & b[3]); \
+a[N + 2] = b[2] ^ ((~b[3]) & b[4]); \
+a[N + 3] = b[3] ^ ((~b[4]) & b[0]); \
+a[N + 4] = b[4] ^ ((~b[0]) & b[1]);
+
+#define CHI() CHI_(0); CHI_(5); CHI_(10); CHI_(15); CHI_(20);
+
+#define IOTA(X) a[0] = a[0] ^ X;
+
+#define ITER(X) THETA(); RHO_PI(); CHI(); IOTA(X);
+
+#define ITERS() \
+ITER(0x0000000000000001); ITER(0x0000000000008082); \
+ITER(0x800000000000808a); ITER(0x8000000080008000); \
+ITER(0x000000000000808b); ITER(0x0000000080000001); \
+ITER(0x8000000080008081); ITER(0x8000000000008009); \
+ITER(0x000000000000008a); ITER(0x0000000000000088); \
+ITER(0x0000000080008009); ITER(0x000000008000000a); \
+ITER(0x000000008000808b); ITER(0x800000000000008b); \
+ITER(0x8000000000008089); ITER(0x8000000000008003); \
+ITER(0x8000000000008002); ITER(0x8000000000000080); \
+ITER(0x000000000000800a); ITER(0x800000008000000a); \
+ITER(0x8000000080008081); ITER(0x8000000000008080); \
+ITER(0x0000000080000001); ITER(0x8000000080008008);
+
+static inline void keccakf(ulong *a)
+{
+  ulong b[5];
+  ulong t;
+  ITERS();
+}
+
+static inline void partial_keccakf(ulong *a)
+{
+  ulong b[5];
+  ulong t;
+  ITER(0x0000000000000001); ITER(0x0000000000008082); 
+  ITER(0x800000000000808a); ITER(0x8000000080008000);
+  ITER(0x000000000000808b); ITER(0x0000000080000001);
+  ITER(0x8000000080008081); ITER(0x8000000000008009);
+  ITER(0x000000000000008a); ITER(0x0000000000000088);
+  ITER(0x0000000080008009); ITER(0x000000008000000a);
+  ITER(0x000000008000808b); ITER(0x800000000000008b);
+  ITER(0x8000000000008089); ITER(0x8000000000008003);
+  ITER(0x8000000000008002); ITER(0x8000000000000080);
+  ITER(0x000000000000800a); ITER(0x800000008000000a);
+  ITER(0x8000000080008081); ITER(0x8000000000008080);
+  ITER(0x0000000080000001);
+
+  // iteration 24 (partial)
+#define o ((uint *)(a))
+  // Theta (partial)
+  b[0] = a[0] ^ a[5] ^ a[10] ^ a[15] ^ a[20];
+  b[1] = a[1] ^ a[6] ^ a[11] ^ a[16] ^ a[21];
+  b[2] = a[2] ^ a[7] ^ a[12] ^ a[17] ^ a[22];
+  b[3] = a[3] ^ a[8] ^ a[13] ^ a[18] ^ a[23];
+  b[4] = a[4] ^ a[9] ^ a[14] ^ a[19] ^ a[24];
+
+  a[0] ^= b[4] ^ ROL(b[1], 1u);
+  a[6] ^= b[0] ^ ROL(b[2], 1u);
+  a[12] ^= b[1] ^ ROL(b[3], 1u);
+  a[18] ^= b[2] ^ ROL(b[4], 1u);
+  a[24] ^= b[3] ^ ROL(b[0], 1u);
+
+  // Rho Pi (partial)
+  o[3] = (o[13] >> 20) | (o[12] << 12);
+  a[2] = ROL(a[12], 43);
+  a[3] = ROL(a[18], 21);
+  a[4] = ROL(a[24], 14);
+
+  // Chi (partial)
+  o[3] ^= ((~o[5]) & o[7]);
+  o[4] ^= ((~o[6]) & o[8]);
+  o[5] ^= ((~o[7]) & o[9]);
+  o[6] ^= ((~o[8]) & o[0]);
+  o[7] ^= ((~o[9]) & o[1]);
+#undef o
+}
+
+#ifdef PATTERN()
+static inline bool isMatching(uchar const *d)
+{
+  __constant char* pattern = PATTERN();
+
+    #pragma unroll
+    for (uint i = 0; i < 20; ++i) {
+        uchar byte = d[i];
+
+        // Extract the high and low nibbles
+        char highNibble = (byte >> 4) & 0x0F;
+        char lowNibble = byte & 0x0F;
+
+        // Convert nibbles to hexadecimal characters
+        char highChar = (highNibble < 10) ? ('0' + highNibble) : ('a' + highNibble - 10);
+        char lowChar = (lowNibble < 10) ? ('0' + lowNibble) : ('a' + lowNibble - 10);
+
+        // Get the corresponding characters from the pattern
+        char patternHighChar = pattern[2 * i];     // Even index
+        char patternLowChar = pattern[2 * i + 1];  // Odd index
+
+        // Compare high nibble
+        if (patternHighChar != 'X' && patternHighChar != highChar)
+            return false;
+
+        // Compare low nibble
+        if (patternLowChar != 'X' && patternLowChar != lowChar)
+            return false;
+    }
+    return true;
+}
+#endif
+
+#define zeroBytes(d) ( \
+  (!(d[0])) + (!(d[1])) + (!(d[2])) + (!(d[3])) + \
+  (!(d[4])) + (!(d[5])) + (!(d[6])) + (!(d[7])) + \
+  (!(d[8])) + (!(d[9])) + (!(d[10])) + (!(d[11])) + \
+  (!(d[12])) + (!(d[13])) + (!(d[14])) + (!(d[15])) + \
+  (!(d[16])) + (!(d[17])) + (!(d[18])) + (!(d[19])) \
+)
+
+#define hasTotal(d) (zeroBytes(d) >= TOTAL_ZEROES)
+
+#ifdef MIN_GAS
+// Calldata gas saved by the address per reference: 12 per zero byte, and 4
+// more per leading zero byte dropped by compact encodings, see `GasScore`
+static inline uint gasSaved(uchar const *d)
+{
+  uint leading = 0;
+  while (leading < 20 && !d[leading]) ++leading;
+  return 12 * zeroBytes(d) + 4 * leading;
+}
+#endif
+
+// hasLeading and hasNibbles are generated from the criteria, see `nibble_condition`
+
+#ifdef BEST_METRIC
+static inline uint leadingZeroNibbles(uchar const *d)
+{
+  for (uint i = 0; i < 20; ++i) {
+    if (d[i]) return 2 * i + !(d[i] >> 4);
+  }
+  return 40;
+}
+
+// Raises the running best to the metric of the digest, reporting the digest
+// only if it did: the work items of a batch report improving addresses
+static inline bool improvesBest(uchar const *d, __global volatile uint *best)
+{
+  uint metric = BEST_METRIC(d);
+  return metric > *best && atomic_max(best, metric) < metric;
+}
+#endif
+
+/*
+static inline bool hasLeadingAny(uchar const *d)
+{
+    uint starting_similar_count = 0;
+    uchar first_letter = (d[0] & 0xf0) >> 4;
+    uchar second_letter = 0xff;
+    uchar third_letter = 0xff;
+
+
+#pragma unroll
+    for (uint i = 0; i < LEADING_ZEROES; ++i) {
+        uchar letter;
+        if (i % 2 == 0) {
+            letter = (d[i/2] & 0xf0) >> 4;
+        } else {
+            letter = (d[i/2] & 0xf);
+        };
+        if (letter != first_letter && second_letter == 0xff) {
+            second_letter = letter;
+        }
+        if (letter != first_letter && letter != second_letter && third_letter == 0xff) {
+            third_letter = letter;
+        }
+
+
+        if (letter != first_letter && letter != second_letter && letter != third_letter) {
+            return false;
+        }
+    }
+
+
+    return true;
+}
+*/
+
+#ifdef GROUP
+static inline bool hasLeadingAny(uchar const *d)
+{
+    uint starting_similar_count = 0;
+    uchar first_letter = (d[0] & 0xf0) >> 4;
+    uchar cc[16];
+
+    for (uint i = 0; i < 16; i++) {
+        cc[i] = 0;
+    }
+
+    ushort first_word = ((ushort) d[0]) << 8 | (ushort) d[1];
+
+    uint int_count = 0;
+
+#pragma unroll
+    for (uint i = 0; i < 20; i += 2) {
+        ushort word = ((ushort) d[i]) << 8 | (ushort) d[i + 1];
+        if (word == first_word) {
+            int_count += 1;
+        } else {
+            break;
+        }
+    }
+
+
+    uchar prev_letter = 0xFF;
+    uchar group_len = 1;
+    uchar group_score = 0;
+    uchar leading_score = 0;
+    //uchar letter_count_beg = 0;
+#pragma unroll
+    for (uint i = 0; i < 40; ++i) {
+        uchar letter;
+        if (i % 2 == 0) {
+            letter = (d[i/2] & 0xf0) >> 4;
+        } else {
+            letter = (d[i/2] & 0xf);
+        };
+        //if (letter >= 10 && i <= 12) {
+        //    letter_count_beg += 1;
+        //}
+        cc[letter] += 1;
+        if (letter == prev_letter) {
+            group_len += 1;
+        } else {
+            group_len = 1;
+        }
+        if (group_len == 3) {
+            group_score += 3;
+        } else if (group_len > 3) {
+            group_score += 2;
+        }
+        if (leading_score < 50 && letter == first_letter) {
+            leading_score += 1;
+        }
+        if (leading_score < 50 && letter != first_letter) {
+            leading_score += 50;
+        }
+        prev_letter = letter;
+    }
+    leading_score -= 50;
+
+
+    uint oneScore = 0;
+    for (uint i = 0; i < 16; i++) {
+        if (cc[i] == 0) {
+            oneScore += 1;
+        }
+    }
+
+    if (int_count >= INTS) {
+        return true;
+    }
+    if (leading_score >= LEADING) {
+        return true;
+    }
+    if (group_score >= GROUP) {
+        return true;
+    }
+    if (oneScore >= ONES) {
+        return true;
+    }
+    bool etherscan_sim = true;
+#pragma unroll
+    for (uint i = 0; i < 8; i++) {
+
+        uchar left_letter;
+        uchar right_letter;
+        if (i % 2 == 0) {
+            left_letter = (d[i/2] & 0xf0) >> 4;
+            right_letter = (d[(i+32)/2] & 0xf0) >> 4;
+        } else {
+            left_letter = (d[i/2] & 0xf);
+            right_letter = (d[(i+32)/2] & 0xf);
+        };
+        if (left_letter != right_letter) {
+            etherscan_sim = false;
+        }
+    }
+
+
+    if (etherscan_sim) {
+        return true;
+    }
+
+    //if (letter_count_beg > 13) {
+    //    return true;
+    //}
+
+
+    if (cc[10] == 0 && cc[11] == 0 && cc[12] == 0 && cc[13] == 0 && cc[14] == 0 && cc[15] == 0) {
+        return true;
+    }
+
+
+
+    return false;
+}
+#endif
+
+#ifdef FANCY_CHECKS
+// Characters of the address are nibbles, with 16 added to letters that are
+// uppercase in the checksummed address
+static inline void toNibbles(uchar const *d, uchar *n)
+{
+#pragma unroll
+  for (uint i = 0; i < 20; ++i) {
+    n[2 * i] = d[i] >> 4;
+    n[2 * i + 1] = d[i] & 0xf;
+  }
+}
+
+// The first 8 and last 9 characters, as etherscan shortens addresses
+static inline void toShort(uchar const *n, uchar *s)
+{
+  for (uint i = 0; i < 8; ++i)
+    s[i] = n[i];
+  for (uint i = 0; i < 9; ++i)
+    s[8 + i] = n[31 + i];
+}
+
+static inline uint leadingRun(uchar const *t, uint len, uchar c)
+{
+  uint run = 0;
+  while (run < len && t[run] == c)
+    ++run;
+  return run;
+}
+
+// Applies the EIP-55 checksum: letters whose nibble in the hash of the
+// lowercase address is above 7 are uppercase
+static inline void checksum(uchar const *n, uchar *c)
+{
+  ulong a[25];
+  for (int i = 0; i < 25; ++i)
+    a[i] = 0;
+  uchar *b = (uchar *) a;
+  for (int i = 0; i < 40; ++i)
+    b[i] = n[i] < 10 ? '0' + n[i] : 'a' + n[i] - 10;
+  b[40] = 0x01u;
+  b[135] = 0x80u;
+  keccakf(a);
+  for (int i = 0; i < 40; ++i) {
+    uchar h = i % 2 == 0 ? b[i / 2] >> 4 : b[i / 2] & 0xf;
+    c[i] = n[i] >= 10 && h > 7 ? n[i] + 16 : n[i];
+  }
+}
+
+// Number of letters, or 0 if the address holds two different digits
+static inline uint letters(uchar const *n)
+{
+  uint count = 0;
+  uchar digit = 0xff;
+  for (int i = 0; i < 40; ++i) {
+    if (n[i] >= 10) {
+      ++count;
+    } else if (digit == 0xff) {
+      digit = n[i];
+    } else if (n[i] != digit) {
+      return 0;
+    }
+  }
+  return count;
+}
+
+static inline uint digits(uchar const *n)
+{
+  uint count = 0;
+  for (int i = 0; i < 40; ++i)
+    count += n[i] < 10;
+  return count;
+}
+
+static inline uint repeats(uchar const *n)
+{
+  uint count = 1;
+  for (int i = 1; i < 40; ++i)
+    count += n[i] == n[i - 1];
+  return count;
+}
+
+// Whether the digits of an all-digit address are at most low or at least high
+static inline bool decimalAccept(uchar const *n, __constant uchar const *low, __constant uchar const *high)
+{
+  int below = 0;
+  int above = 0;
+  for (int i = 0; i < 40; ++i) {
+    if (below == 0 && n[i] != low[i])
+      below = n[i] < low[i] ? 1 : -1;
+    if (above == 0 && n[i] != high[i])
+      above = n[i] > high[i] ? 1 : -1;
+  }
+  return below >= 0 || above >= 0;
+}
+
+// Whether the address starts or ends with one of the words, whose nibbles are
+// stored back to back in w and end at the offsets in ends. The words are only
+// compared when the first or last byte of the address is flagged in head or
+// tail
+static inline bool anchoredWord(uchar const *n, __constant uchar const *head, __constant uchar const *tail, __constant uchar const *w, __constant uint const *ends, uint count)
+{
+  bool atHead = head[n[0] * 16 + n[1]];
+  bool atTail = tail[n[38] * 16 + n[39]];
+  if (!atHead && !atTail)
+    return false;
+  uint start = 0;
+  for (uint i = 0; i < count; ++i) {
+    uint len = ends[i] - start;
+    bool h = atHead;
+    bool t = atTail;
+    for (uint j = 0; j < len && (h || t); ++j) {
+      h = h && n[j] == w[start + j];
+      t = t && n[40 - len + j] == w[start + j];
+    }
+    if (h || t)
+      return true;
+    start = ends[i];
+  }
+  return false;
+}
+
+// Whether the address contains one of the words, stored as for anchoredWord
+static inline bool wordAnywhere(uchar const *n, __constant uchar const *w, __constant uint const *ends, uint count)
+{
+  uint start = 0;
+  for (uint i = 0; i < count; ++i) {
+    uint len = ends[i] - start;
+    for (uint p = 0; p + len <= 40; ++p) {
+      uint j = 0;
+      while (j < len && n[p + j] == w[start + j])
+        ++j;
+      if (j == len)
+        return true;
+    }
+    start = ends[i];
+  }
+  return false;
+}
+
+static inline bool isFancy(uchar const *d)
+{
+  uchar n[40];
+  uchar s[17];
+  uchar c[40];
+  uchar cs[17];
+  bool hashed = false;
+  toNibbles(d, n);
+  toShort(n, s);
+  FANCY_CHECKS()
+  return false;
+}
+#endif
+
+// Debugging helper
+#define PRINT() { \
+ printf("\ninput: "); \
+  for (int i = 0; i < 85; ++i) \
+    printf("%02x", sponge[i]); \
+  printf("\ninput full: "); \
+  for (int i = 0; i < 200; ++i) \
+    printf("%02x", sponge[i]); \
+  keccakf(spongeBuffer); \
+  printf("\noutput: "); \
+  for (int i = 0; i < 32; ++i) \
+    printf("%02x", sponge[i]); \
+  printf("\n"); \
+  for (int i = 0; i < 20; ++i) \
+    printf("%02x", digest[i]); \
+  printf("\n"); \
+}
+
+// Replaces the salt with the hash the factory derives from it, as described by GUARD_TEMPLATE
+#define RUN_GUARD_SALT() { \
+  for (int i = 0; i < GUARD_LEN; ++i) \
+    sponge[i] = GUARD_TEMPLATE[i]; \
+  for (int i = 0; i < 32; ++i) \
+    sponge[GUARD_SALT_OFFSET + i] = salt[i]; \
+  sponge[GUARD_LEN] = 0x01u; \
+  for (int i = GUARD_LEN + 1; i < 200; ++i) \
+    sponge[i] = 0; \
+  sponge[135] |= 0x80u; \
+  keccakf(spongeBuffer); \
+  for (int i = 0; i < 32; ++i) \
+    salt[i] = sponge[i]; \
+}
+
+// Same as RUN_GUARD_SALT for preimages spanning GUARD_BLOCKS keccak blocks
+#define RUN_GUARD_SALT_BLOCKS() { \
+  for (int i = 0; i < 200; ++i) \
+    sponge[i] = 0; \
+  for (int block = 0; block < GUARD_BLOCKS; ++block) { \
+    for (int i = 0; i < 136; ++i) { \
+      int j = block * 136 + i; \
+      if (j >= GUARD_SALT_OFFSET && j < GUARD_SALT_OFFSET + 32) \
+        sponge[i] ^= salt[j - GUARD_SALT_OFFSET]; \
+      else if (j < GUARD_LEN) \
+        sponge[i] ^= GUARD_TEMPLATE[j]; \
+      else if (j == GUARD_LEN) \
+        sponge[i] ^= 0x01u; \
+    } \
+    if (block == GUARD_BLOCKS - 1) \
+      sponge[135] ^= 0x80u; \
+    keccakf(spongeBuffer); \
+  } \
+  for (int i = 0; i < 32; ++i) \
+    salt[i] = sponge[i]; \
+}
+
+#define RUN_CREATE3() { \
+  keccakf(spongeBuffer); \
+  for (int i = 12; i < 32; ++i) \
+    sponge[i - 10] = sponge[i]; \
+  sponge[0] = 0xd6u; \
+  sponge[1] = 0x94u; \
+  sponge[22] = 0x01u; \
+  sponge[23] = 0x01u; \
+  for (int i = 24; i < 135; ++i) \
+    sponge[i] = 0; \
+  sponge[135] = 0x80u; \
+  for (int i = 136; i < 200; ++i) \
+    sponge[i] = 0; \
+}
+
+// Builds the CREATE preimage rlp([CREATE_DEPLOYER, nonce]) for the nonce of this work item,
+// scanning CREATE_NONCE_START..CREATE_NONCE_END in batches of CREATE_BATCH_SIZE
+#define RUN_CREATE() { \
+  nonce.uint64_t = CREATE_NONCE_START + (ulong)d_nonce[0] * CREATE_BATCH_SIZE + get_global_id(0); \
+  if (nonce.uint64_t < CREATE_NONCE_START || nonce.uint64_t >= CREATE_NONCE_END) \
+    return; \
+  sponge[1] = 0x94u; \
+  for (int i = 0; i < 20; ++i) \
+    sponge[2 + i] = CREATE_DEPLOYER[i]; \
+  int len = 22; \
+  if (nonce.uint64_t == 0) { \
+    sponge[len++] = 0x80u; \
+  } else if (nonce.uint64_t < 0x80) { \
+    sponge[len++] = nonce.uint8_t[0]; \
+  } else { \
+    int bytes = 8; \
+    while (!nonce.uint8_t[bytes - 1]) \
+      --bytes; \
+    sponge[len++] = 0x80u + bytes; \
+    for (int i = bytes - 1; i >= 0; --i) \
+      sponge[len++] = nonce.uint8_t[i]; \
+  } \
+  sponge[0] = 0xc0u + len - 1; \
+  sponge[len] = 0x01u; \
+  for (int i = len + 1; i < 200; ++i) \
+    sponge[i] = 0; \
+  sponge[135] |= 0x80u; \
+}
+
+__kernel void hashMessage(
+  __constant uchar const *d_message,
+  __constant uint const *d_nonce,
+  __global volatile ulong *restrict solutions
+#ifdef BEST_METRIC
+  , __global volatile uint *best
+#endif
+) {
+  ulong spongeBuffer[25];
+
+#define sponge ((uchar *) spongeBuffer)
+#define digest (sponge + 12)
+
+  nonce_t nonce;
+
+#ifdef CREATE_NONCE_START
+  RUN_CREATE()
+#else
+  // Salt passed to the factory: the fixed bytes of SALT_TEMPLATE with the
+  // message and nonce written at SALT_MINED_OFFSET
+  uchar salt[32];
+  for (int i = 0; i < 32; ++i)
+    salt[i] = SALT_TEMPLATE[i];
+  salt[SALT_MINED_OFFSET] = d_message[0];
+  salt[SALT_MINED_OFFSET + 1] = d_message[1];
+  salt[SALT_MINED_OFFSET + 2] = d_message[2];
+  salt[SALT_MINED_OFFSET + 3] = d_message[3];
+  nonce.uint32_t[0] = get_global_id(0);
+  nonce.uint32_t[1] = d_nonce[0];
+  salt[SALT_MINED_OFFSET + 4] = nonce.uint8_t[0];
+  salt[SALT_MINED_OFFSET + 5] = nonce.uint8_t[1];
+  salt[SALT_MINED_OFFSET + 6] = nonce.uint8_t[2];
+  salt[SALT_MINED_OFFSET + 7] = nonce.uint8_t[3];
+  salt[SALT_MINED_OFFSET + 8] = nonce.uint8_t[4];
+  salt[SALT_MINED_OFFSET + 9] = nonce.uint8_t[5];
+  salt[SALT_MINED_OFFSET + 10] = nonce.uint8_t[6];
+
+  // If the factory guards the salt, hash it the way the factory does
+  GUARD_SALT()
+
+  // Salt hash
+  for (int i = 0; i < 32; ++i)
+    sponge[21 + i] = salt[i];
+
+  // Setup Create2 Hash
+  // write the control character
+  sponge[0] = 0xffu;
+
+  sponge[1] = S2_1;
+  sponge[2] = S2_2;
+  sponge[3] = S2_3;
+  sponge[4] = S2_4;
+  sponge[5] = S2_5;
+  sponge[6] = S2_6;
+  sponge[7] = S2_7;
+  sponge[8] = S2_8;
+  sponge[9] = S2_9;
+  sponge[10] = S2_10;
+  sponge[11] = S2_11;
+  sponge[12] = S2_12;
+  sponge[13] = S2_13;
+  sponge[14] = S2_14;
+  sponge[15] = S2_15;
+  sponge[16] = S2_16;
+  sponge[17] = S2_17;
+  sponge[18] = S2_18;
+  sponge[19] = S2_19;
+  sponge[20] = S2_20;
+  sponge[53] = S2_53;
+  sponge[54] = S2_54;
+  sponge[55] = S2_55;
+  sponge[56] = S2_56;
+  sponge[57] = S2_57;
+  sponge[58] = S2_58;
+  sponge[59] = S2_59;
+  sponge[60] = S2_60;
+  sponge[61] = S2_61;
+  sponge[62] = S2_62;
+  sponge[63] = S2_63;
+  sponge[64] = S2_64;
+  sponge[65] = S2_65;
+  sponge[66] = S2_66;
+  sponge[67] = S2_67;
+  sponge[68] = S2_68;
+  sponge[69] = S2_69;
+  sponge[70] = S2_70;
+  sponge[71] = S2_71;
+  sponge[72] = S2_72;
+  sponge[73] = S2_73;
+  sponge[74] = S2_74;
+  sponge[75] = S2_75;
+  sponge[76] = S2_76;
+  sponge[77] = S2_77;
+  sponge[78] = S2_78;
+  sponge[79] = S2_79;
+  sponge[80] = S2_80;
+  sponge[81] = S2_81;
+  sponge[82] = S2_82;
+  sponge[83] = S2_83;
+  sponge[84] = S2_84;
+
+  sponge[85] = 0x01u;
+
+  // fill padding
+#pragma unroll
+  for (int i = 86; i < 135; ++i)
+    sponge[i] = 0;
+
+  // end padding
+  sponge[135] = 0x80u;
+
+  // fill remaining sponge state with zeros
+#pragma unroll
+  for (int i = 136; i < 200; ++i)
+    sponge[i] = 0;
+
+  // If this is a Create3 operation, setup and perform an additional CREATE hash
+  CREATE3()
+#endif
+
+  partial_keccakf(spongeBuffer);
+
+  // determine if the address meets the constraints
+  if (
+    SUCCESS_CONDITION()
+  ) {
+    int idx = get_global_id(0) % RESULT_BUFFER_SIZE * 4;
+    // To be honest, if we are using OpenCL,
+    // we just need to write one solution for all practical purposes,
+    // since the chance of multiple solutions appearing
+    // in a single workset is extremely low.
+    solutions[idx + 0] = nonce.uint64_t;
+
+    // Pass back output address through solutions buffer.
+    ulong newUint64 = 0;
+  #pragma unroll
+    for (ulong i = 0; i < 8; i++) {
+      ulong d = digest[i];
+      newUint64 |= (d << ((7 - i) * 8));
+    }
+    solutions[idx + 1] = newUint64;
+
+    newUint64 = 0;
+  #pragma unroll
+    for (ulong j = 0; j < 8; j++) {
+        ulong d = digest[j + 8];
+        newUint64 |= (d << ((7 - j) * 8));
+    }
+    solutions[idx + 2] = newUint64;
+
+    newUint64 = 0;
+  #pragma unroll
+    for (ulong k = 0; k < 8; k++) {
+        ulong d = digest[k + 16];
+        newUint64 |= (d << ((7 - k) * 8));
+    }
+    solutions[idx + 3] = newUint64;
+  }
+}
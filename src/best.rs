@@ -0,0 +1,204 @@
+use crate::{
+    fancy::Difficulty, nibble_run, nibbles, record_accepted, score::Thresholds, solution_address,
+    verify_solution, zero_bytes_probability, Config, CrunchError, Launcher,
+};
+use clap::ValueEnum;
+use ocl::ProQue;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    sync::{Arc, Mutex},
+};
+
+/// What addresses are ranked by in best-so-far mode, where every address better than the best
+/// found so far is recorded, instead of every address reaching a threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum BestMetric {
+    /// Leading zero nibbles.
+    LeadingZeros,
+    /// Zero bytes anywhere in the address.
+    TotalZeros,
+    /// Weighted difficulty in the scoring categories.
+    Fancy,
+}
+
+impl Display for BestMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BestMetric::LeadingZeros => write!(f, "leading_zeros"),
+            BestMetric::TotalZeros => write!(f, "total_zeros"),
+            BestMetric::Fancy => write!(f, "fancy"),
+        }
+    }
+}
+
+impl BestMetric {
+    /// Measures an address. The counted metrics match `BEST_METRIC()` in the kernel, the fancy
+    /// one is the total score of the address in the categories of the config.
+    pub fn measure(&self, config: &Config, address: &[u8; 20]) -> f64 {
+        match self {
+            BestMetric::LeadingZeros => nibble_run(nibbles(address), Some(0)) as f64,
+            BestMetric::TotalZeros => address.iter().filter(|byte| **byte == 0).count() as f64,
+            BestMetric::Fancy => config.score(address).total_score,
+        }
+    }
+
+    /// Expected number of addresses to check to find one measuring at least `value`.
+    pub fn difficulty(&self, value: f64) -> f64 {
+        match self {
+            BestMetric::LeadingZeros => 16f64.powf(value),
+            BestMetric::TotalZeros => 1.0 / zero_bytes_probability(20, value as u32),
+            BestMetric::Fancy => value,
+        }
+    }
+
+    /// The kernel function computing the metric, or `None` for the fancy metric, which the kernel
+    /// checks against a minimum difficulty instead.
+    pub(crate) fn opencl(&self) -> Option<&'static str> {
+        match self {
+            BestMetric::LeadingZeros => Some("leadingZeroNibbles(d)"),
+            BestMetric::TotalZeros => Some("zeroBytes(d)"),
+            BestMetric::Fancy => None,
+        }
+    }
+}
+
+/// Best value found so far, shared by the devices mining a job so that each records only the
+/// addresses beating those of every device.
+#[derive(Clone, Debug, Default)]
+pub struct SharedBest(Arc<Mutex<Option<f64>>>);
+
+impl SharedBest {
+    /// The best value, or `None` before the first address is measured.
+    pub fn get(&self) -> Option<f64> {
+        *self.0.lock().unwrap()
+    }
+
+    /// Raises the best to `value` if it beats it, and returns whether it did.
+    pub fn raise(&self, value: f64) -> bool {
+        let mut best = self.0.lock().unwrap();
+        if best.is_some_and(|best| best >= value) {
+            return false;
+        }
+        *best = Some(value);
+        true
+    }
+}
+
+/// Difficulty every address reaches in the categories scoring all addresses the same, like
+/// [`crate::score::FancyScoreCategory::Random`]. The fancy kernel starts above it, so that the
+/// first launch does not report every address it hashes.
+fn fancy_floor(config: &Config) -> f64 {
+    config
+        .scoring
+        .weighted()
+        .filter_map(|(scorer, weight)| match scorer.definition()?.difficulty {
+            Difficulty::Constant(difficulty) => Some(difficulty * weight),
+            _ => None,
+        })
+        .fold(1.0, f64::max)
+}
+
+/// Thresholds of a fancy kernel reporting the addresses scoring above `best`.
+fn fancy_thresholds(thresholds: &Thresholds, best: f64) -> Thresholds {
+    Thresholds {
+        // the kernel accepts the minimum itself, so it starts at the next representable value
+        min_difficulty: f64::from_bits(best.to_bits() + 1),
+        category_minimums: BTreeMap::new(),
+        ..thresholds.clone()
+    }
+}
+
+/// Searches for ever better addresses on the device, recording each one that beats the best found
+/// so far by any device, until the search is stopped or the keyspace is exhausted.
+///
+/// For the counted metrics, the kernel reports the addresses raising the running best in a device
+/// buffer, which the host resets to the shared best before each launch. The fancy metric cannot
+/// be computed on the device, so the kernel is rebuilt with the shared best as the minimum
+/// difficulty of every category instead, whenever a device raises it.
+pub(crate) fn mine(
+    config: &Config,
+    build: impl Fn(&Config) -> Result<ProQue, CrunchError>,
+) -> Result<(), CrunchError> {
+    let metric = config.best.expect("best-so-far mode needs a metric");
+    let mut config = config.clone();
+    let floor = fancy_floor(&config);
+    let thresholds = config.thresholds.clone();
+    let mut kernel_best = config.shared_best.get();
+    if metric == BestMetric::Fancy {
+        config.thresholds = fancy_thresholds(&thresholds, kernel_best.unwrap_or(floor));
+    }
+    let mut ocl_pq = build(&config)?;
+    let mut launcher = Launcher::new(&config);
+
+    loop {
+        let best = config.shared_best.get();
+        // another device may have raised the best since the fancy kernel was built
+        if metric == BestMetric::Fancy && best > kernel_best {
+            kernel_best = best;
+            config.thresholds = fancy_thresholds(&thresholds, best.unwrap_or(floor));
+            ocl_pq = build(&config)?;
+        }
+        let kernel_arg = metric.opencl().map(|_| best.unwrap_or(0.0) as u32);
+        let Some(batch) = launcher.launch(&ocl_pq, kernel_arg)? else {
+            break;
+        };
+
+        // several work items can raise the best within a launch, only the best of them is kept
+        let launch = &batch.launch;
+        let candidate = batch
+            .solutions
+            .chunks(4)
+            .filter(|solution| solution.iter().any(|&word| word != 0))
+            .map(|solution| {
+                let address = solution_address(solution);
+                (
+                    config.launch_salt(&launch.message, launch.index, solution[0] as u32),
+                    address,
+                    metric.measure(&config, &address),
+                )
+            })
+            .filter(|(salt, address, _)| verify_solution(&config, salt, address))
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+
+        // the other devices may have raised the best during the launch, so it is checked again
+        if let Some((salt, address, value)) = candidate {
+            if config.shared_best.raise(value) {
+                log::info!(
+                    "New best {}: {} for 0x{}, 1 in {:.4e} addresses",
+                    metric,
+                    value,
+                    alloy_primitives::hex::encode(address),
+                    metric.difficulty(value)
+                );
+                record_accepted(
+                    &config,
+                    salt,
+                    address,
+                    launcher.total_processed(),
+                    format!("best_{metric}={value}"),
+                );
+            }
+        }
+
+        launcher.report(
+            batch.elapsed,
+            format!(
+                "best {metric} {}, {}",
+                config.shared_best.get().unwrap_or_default(),
+                launcher.keyspace()
+            ),
+        );
+    }
+    log::info!(
+        "Keyspace exhausted after {} salts, covered {}, best {} {}",
+        launcher.keyspace().covered(),
+        launcher.keyspace(),
+        metric,
+        config.shared_best.get().unwrap_or_default()
+    );
+    Ok(())
+}
@@ -1,4 +1,11 @@
-use clap::{command, ArgAction, Args, Parser, Subcommand};
+use crate::{
+    best::BestMetric,
+    events::{EventStream, ProgressFormat},
+    factory::FactoryKind,
+    preimage::AccountFactory,
+    price::ReportFormat,
+};
+use clap::{ArgAction, Args, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(arg_required_else_help = true)]
@@ -10,45 +17,60 @@ pub struct Cli {
 
 #[derive(Args)]
 pub struct CliArgs {
+    #[arg(
+        id = "job",
+        long,
+        short = 'j',
+        long_help = "Load the job from a TOML or JSON file. Flags given on the command line override the values from the file.\n\nExample: --job job.toml.",
+        help_heading = "Crunching options"
+    )]
+    pub job: Option<String>,
+
     #[arg(
         id = "factory",
         long,
         short,
-        default_value = "0x9e3f8eae49e442a323ef2094f277bf62752e6995",
-        long_help = "Set the factory address.",
+        long_help = "Set the factory address. Defaults to the canonical address of the factory kind.",
+        help_heading = "Crunching options"
+    )]
+    pub factory: Option<String>,
+
+    #[arg(
+        id = "factory-kind",
+        long,
+        value_enum,
+        long_help = "Factory contract used for the deployment, which determines how the salt is turned into an address. Defaults to the kind recognized from --factory, or createx.",
         help_heading = "Crunching options"
     )]
-    pub factory: String,
+    pub factory_kind: Option<FactoryKind>,
 
     #[arg(
         id = "work-size",
         long,
         short,
-        default_value = "1000000000",
-        long_help = "Work size at once in GPU.",
+        long_help = "Work size at once in GPU, at most 4294967296 when mining salts. Defaults to 1000000000.",
         help_heading = "Crunching options"
     )]
-    pub work_size: usize,
+    pub work_size: Option<usize>,
 
     #[arg(
         id = "sleep-for",
         long,
         short,
         long_help = "Sleep every kernel run (override default behaviour)",
-        help_heading = "Crunching options",
-        default_value = "0.0"
+        help_heading = "Crunching options"
     )]
-    pub sleep_for: f64,
+    pub sleep_for: Option<f64>,
 
     #[arg(
         id = "gpu-device-id",
         long,
         short,
-        default_value = "0",
-        long_help = "Set the GPU device ID.",
+        num_args = 1..,
+        long_help = "Set the GPU device ID. Pass several IDs to mine on multiple devices. Defaults to 0.",
         help_heading = "Crunching options"
     )]
-    pub gpu_device_id: u8,
+    pub gpu_device_id: Vec<u8>,
 
     #[arg(
         id = "caller",
@@ -109,35 +131,196 @@ pub struct CliArgs {
     )]
     pub pattern: Option<Box<str>>,
 
-    #[arg(long = "group", default_value = "24")]
-    pub group: u64,
+    #[arg(
+        id = "leading-nibbles",
+        long = "leading-nibbles",
+        long_help = "Minimum number of leading nibbles equal to --nibble, from 1 to 40. Cannot be used in combination with other search criteria.\n\nExample: --leading-nibbles 9.",
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern"]
+    )]
+    pub leading_nibbles: Option<u8>,
+
+    #[arg(
+        id = "trailing-nibbles",
+        long = "trailing-nibbles",
+        long_help = "Minimum number of trailing nibbles equal to --nibble, from 1 to 40. Cannot be used in combination with other search criteria.\n\nExample: --trailing-nibbles 6.",
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern", "leading-nibbles"]
+    )]
+    pub trailing_nibbles: Option<u8>,
+
+    #[arg(
+        id = "nibble",
+        long = "nibble",
+        long_help = "Nibble the leading or trailing nibbles repeat, in hex, or `any` for a run of whichever nibble the address starts or ends with. Defaults to 0.\n\nExample: --leading-nibbles 8 --nibble f.",
+        help_heading = "Crunching options"
+    )]
+    pub nibble: Option<String>,
+
+    #[arg(
+        id = "gas",
+        long = "gas",
+        long_help = "Minimum calldata gas the address has to save over --gas-references references, compared to an address without zero bytes: 12 gas per zero byte, and 4 more per leading zero byte dropped by compact encodings. Cannot be used in combination with other search criteria.\n\nExample: --gas 60.",
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern", "leading-nibbles", "trailing-nibbles"]
+    )]
+    pub gas: Option<u64>,
+
+    #[arg(
+        id = "gas-references",
+        long = "gas-references",
+        long_help = "Times the address is expected to be passed in calldata, e.g. in calls to the deployed contract, the gas threshold is spread over. Defaults to 1.\n\nExample: --gas 100000 --gas-references 1000.",
+        requires = "gas",
+        help_heading = "Crunching options"
+    )]
+    pub gas_references: Option<u64>,
+
+    #[arg(
+        id = "fancy",
+        long = "fancy",
+        long_help = "Search for fancy addresses, reaching the minimum difficulty of an enabled scoring category. Cannot be used in combination with other search criteria.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern", "leading-nibbles", "trailing-nibbles", "gas", "best"]
+    )]
+    pub fancy: bool,
+
+    #[arg(
+        id = "best",
+        long = "best",
+        value_enum,
+        long_help = "Search for ever better addresses instead of addresses reaching a threshold, recording each address that beats the best found so far until the search is stopped. Addresses are ranked by leading zero nibbles, total zero bytes or their fancy score. Cannot be used in combination with other search criteria.\n\nExample: --best leading_zeros.",
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern", "leading-nibbles", "trailing-nibbles", "gas"]
+    )]
+    pub best: Option<BestMetric>,
+
+    #[arg(
+        id = "salt-layout",
+        long,
+        value_name = "SPEC",
+        long_help = "Layout of the bytes of the salt left free by the factory, as comma-separated segments: fixed:<hex> for bytes kept as given, random:<n> for bytes drawn for every kernel run and exactly one counter:<n> of 4 to 12 bytes enumerating the work items and kernel runs. Bytes after the layout are zero. Defaults to random:4,counter:7.\n\nExample: --salt-layout fixed:0xc0ffee,counter:8.",
+        help_heading = "Crunching options"
+    )]
+    pub salt_layout: Option<String>,
+
+    #[arg(long = "group", long_help = "Defaults to 24.")]
+    pub group: Option<u64>,
+
+    #[arg(long = "leading", long_help = "Defaults to 7.")]
+    pub leading: Option<u64>,
+
+    #[arg(long = "ones", long_help = "Defaults to 9.")]
+    pub ones: Option<u64>,
+
+    #[arg(long = "ints", long_help = "Defaults to 4.")]
+    pub ints: Option<u64>,
+
+    #[arg(
+        id = "dictionary",
+        long,
+        long_help = "Score hex-speak words from a word list, one word per line, in the hex_speak category. Words are spelled with 0 for o, 1 for i or l, 5 for s and 7 for t; words that cannot be spelled are skipped.\n\nExample: --dictionary /usr/share/dict/words.",
+        help_heading = "Crunching options"
+    )]
+    pub dictionary: Option<String>,
 
-    #[arg(long = "leading", default_value = "7")]
-    pub leading: u64,
+    #[arg(
+        id = "min-difficulty",
+        long,
+        long_help = "Minimum weighted difficulty an address has to reach in a scoring category to be recorded, as a number or a power. Defaults to 16^8.\n\nExample: --min-difficulty 16^10.",
+        help_heading = "Scoring options"
+    )]
+    pub min_difficulty: Option<String>,
 
-    #[arg(long = "ones", default_value = "9")]
-    pub ones: u64,
+    #[arg(
+        id = "base-difficulty",
+        long,
+        long_help = "Difficulty of the addresses priced at the base price. Defaults to 16^9.\n\nExample: --base-difficulty 1e11.",
+        help_heading = "Scoring options"
+    )]
+    pub base_difficulty: Option<String>,
 
-    #[arg(long = "ints", default_value = "4")]
-    pub ints: u64,
+    #[arg(
+        id = "category-min",
+        long,
+        num_args = 1..,
+        value_name = "CATEGORY=DIFFICULTY",
+        long_help = "Minimum difficulty of a scoring category, in place of --min-difficulty. Pass several to set the minimum of several categories.\n\nExample: --category-min leading_letters=16^7 random=1e12.",
+        help_heading = "Scoring options"
+    )]
+    pub category_min: Vec<String>,
 
     #[arg(
         id = "output",
         long,
         short,
-        default_value = "output.txt",
-        long_help = "Output file name.",
+        long_help = "Output file name. Defaults to output.txt.",
         help_heading = "Output options"
     )]
-    pub output: String,
+    pub output: Option<String>,
+
+    #[arg(
+        id = "output-dir",
+        long,
+        long_help = "Directory that accepted solutions are written to. Defaults to output.",
+        help_heading = "Output options"
+    )]
+    pub output_dir: Option<String>,
+
+    #[arg(
+        id = "keep-top",
+        long,
+        value_name = "K",
+        long_help = "Also keep the K most difficult results of each scoring category in results.csv in the output directory, one record per address across runs.",
+        help_heading = "Output options"
+    )]
+    pub keep_top: Option<usize>,
+
+    #[arg(
+        id = "tui",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Show a full-screen dashboard of the hashrate of every device, the progress and the best results by category, in place of the progress lines. Press p to pause and resume, + and - to change the time slept per kernel run and q to quit. Falls back to plain logs when standard output is not a terminal.",
+        help_heading = "Output options"
+    )]
+    pub tui: bool,
+
+    #[arg(
+        id = "progress-format",
+        long,
+        value_enum,
+        default_value_t = ProgressFormat::Text,
+        conflicts_with = "tui",
+        long_help = "Format of the mining progress. json writes newline-delimited events in place of the progress lines, each with the schema version, a millisecond timestamp and its type: started, progress, solution, device_error or stopped.",
+        help_heading = "Output options"
+    )]
+    pub progress_format: ProgressFormat,
+
+    #[arg(
+        id = "progress-stream",
+        long,
+        value_enum,
+        default_value_t = EventStream::Stdout,
+        long_help = "Stream the JSON progress events are written to. Log lines are always written to standard error.",
+        help_heading = "Output options"
+    )]
+    pub progress_stream: EventStream,
+
+    #[arg(
+        id = "db",
+        long,
+        value_name = "FILE",
+        long_help = "Also store every accepted result, with its score breakdown and the parameters of the job, in a SQLite database, queried with the db subcommand.\n\nExample: --db output/results.db.",
+        help_heading = "Output options"
+    )]
+    pub db: Option<String>,
 
     #[arg(
         long = "result-buffer-size",
-        long_help = "Set the result buffer size.",
-        help_heading = "Crunching options",
-        default_value = "20000"
+        long_help = "Set the result buffer size. Defaults to 20000.",
+        help_heading = "Crunching options"
     )]
-    pub result_buffer_size: usize,
+    pub result_buffer_size: Option<usize>,
 }
 
 #[derive(Args)]
@@ -148,12 +331,660 @@ pub struct Create2Args {
     #[arg(
         long = "code-hash",
         visible_alias = "ch",
-        long_help = "Set the init code hash in hex format.",
+        long_help = "Set the init code hash in hex format. Required unless the init code is given otherwise or provided by --job.",
+        help_heading = "Crunching options",
+        group = "init-code-source"
+    )]
+    pub init_code_hash: Option<String>,
+
+    #[arg(
+        id = "init-code",
+        long = "init-code",
+        long_help = "Set the init code in hex format. Its hash is computed.",
+        help_heading = "Crunching options",
+        group = "init-code-source"
+    )]
+    pub init_code: Option<String>,
+
+    #[arg(
+        id = "init-code-file",
+        long = "init-code-file",
+        long_help = "Read the init code in hex format from a file.",
+        help_heading = "Crunching options",
+        group = "init-code-source"
+    )]
+    pub init_code_file: Option<String>,
+
+    #[arg(
+        id = "artifact",
+        long = "artifact",
+        long_help = "Read the creation bytecode from a Foundry or Hardhat artifact.\n\nExample: --artifact out/Foo.sol/Foo.json.",
         help_heading = "Crunching options",
+        group = "init-code-source"
+    )]
+    pub artifact: Option<String>,
+
+    #[arg(
+        id = "constructor-args",
+        long = "constructor-args",
+        num_args = 1..,
+        value_names = ["SIGNATURE", "VALUES"],
+        long_help = "ABI-encode the values according to the signature and append them to the init code.\n\nExample: --constructor-args \"constructor(address,uint256)\" 0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A 42.",
+        help_heading = "Crunching options"
+    )]
+    pub constructor_args: Option<Vec<String>>,
+
+    #[arg(
+        id = "account",
+        long,
+        value_enum,
+        conflicts_with = "salt-preimage",
+        long_help = "Mine the salt of a smart account factory, which hashes it together with the owners or initialization data of the account, or uses it as is like simple-account.",
+        help_heading = "Salt preimage options"
+    )]
+    pub account: Option<AccountFactory>,
+
+    #[arg(
+        id = "owners",
+        long,
+        num_args = 1..,
+        requires = "account",
+        long_help = "Set the owners of the account. Coinbase Smart Wallet owners are addresses or 64-byte public keys.",
+        help_heading = "Salt preimage options"
+    )]
+    pub owners: Vec<String>,
+
+    #[arg(
+        id = "account-data",
+        long,
+        requires = "account",
+        long_help = "Set the initialization data of a Kernel account in hex format.",
+        help_heading = "Salt preimage options"
+    )]
+    pub account_data: Option<String>,
+
+    #[arg(
+        id = "salt-preimage",
+        long,
+        requires = "salt-offset",
+        long_help = "Hash the salt in this preimage, given in hex format with 32 placeholder bytes for the salt, before deploying with it.",
+        help_heading = "Salt preimage options"
+    )]
+    pub salt_preimage: Option<String>,
+
+    #[arg(
+        id = "salt-offset",
+        long,
+        requires = "salt-preimage",
+        long_help = "Set the offset of the salt within --salt-preimage.",
+        help_heading = "Salt preimage options"
+    )]
+    pub salt_offset: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    #[clap(flatten)]
+    pub cli_args: CliArgs,
+
+    #[arg(
+        id = "deployer",
+        long,
+        long_help = "Set the address deploying the contract with CREATE, e.g. an EOA. Defaults to the factory, for CreateX's deployCreate.",
+        help_heading = "Crunching options"
+    )]
+    pub deployer: Option<String>,
+
+    #[arg(
+        id = "nonces",
+        long,
+        long_help = "Set the nonces of the deployer to check, as START..END, START..=END or a single nonce. Required unless provided by --job.
+
+Example: --nonces 0..1000000.",
+        help_heading = "Crunching options"
+    )]
+    pub nonces: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SafeArgs {
+    #[clap(flatten)]
+    pub cli_args: CliArgs,
+
+    #[arg(
+        id = "singleton",
+        long,
+        long_help = "Set the Safe singleton the proxy delegates to. Required unless provided by --job.",
+        help_heading = "Safe options"
+    )]
+    pub singleton: Option<String>,
+
+    #[arg(
+        id = "proxy-code",
+        long,
+        long_help = "Set the creation code of the proxy in hex format, as returned by the factory's proxyCreationCode(). Required unless provided by --job.
+
+Example: --proxy-code $(cast call <FACTORY> \"proxyCreationCode()(bytes)\").",
+        help_heading = "Safe options"
+    )]
+    pub proxy_code: Option<String>,
+
+    #[arg(
+        id = "initializer",
+        long,
+        conflicts_with_all = ["owners", "threshold", "fallback-handler"],
+        long_help = "Set the calldata the proxy is initialized with in hex format, usually a call to setup.",
+        help_heading = "Safe options"
+    )]
+    pub initializer: Option<String>,
+
+    #[arg(
+        id = "owners",
+        long,
+        num_args = 1..,
+        long_help = "Build the initializer as a call to setup with these owners.
+
+Example: --owners 0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A 0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5.",
+        help_heading = "Safe options"
+    )]
+    pub owners: Vec<String>,
+
+    #[arg(
+        id = "threshold",
+        long,
+        requires = "owners",
+        long_help = "Set the number of owners required to confirm a transaction. Defaults to 1.",
+        help_heading = "Safe options"
+    )]
+    pub threshold: Option<u64>,
+
+    #[arg(
+        id = "fallback-handler",
+        long,
+        requires = "owners",
+        long_help = "Set the fallback handler passed to setup. Defaults to none.",
+        help_heading = "Safe options"
+    )]
+    pub fallback_handler: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    #[arg(
+        id = "record",
+        long_help = "Result record to export, either as a line from a results file or as the path to an addr_<address>.csv file.",
+        value_name = "RECORD"
+    )]
+    pub record: String,
+
+    #[arg(
+        id = "init-code",
+        long = "init-code",
+        long_help = "Set the init code in hex format.",
+        help_heading = "Deployment options",
+        group = "init-code-source"
+    )]
+    pub init_code: Option<String>,
+
+    #[arg(
+        id = "init-code-file",
+        long = "init-code-file",
+        long_help = "Read the init code in hex format from a file.",
+        help_heading = "Deployment options",
+        group = "init-code-source"
+    )]
+    pub init_code_file: Option<String>,
+
+    #[arg(
+        id = "artifact",
+        long = "artifact",
+        long_help = "Read the creation bytecode from a Foundry or Hardhat artifact.\n\nExample: --artifact out/Foo.sol/Foo.json.",
+        help_heading = "Deployment options",
+        group = "init-code-source"
+    )]
+    pub artifact: Option<String>,
+
+    #[arg(
+        id = "constructor-args",
+        long = "constructor-args",
+        num_args = 1..,
+        value_names = ["SIGNATURE", "VALUES"],
+        long_help = "ABI-encode the values according to the signature and append them to the init code.\n\nExample: --constructor-args \"constructor(address,uint256)\" 0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A 42.",
+        help_heading = "Deployment options"
+    )]
+    pub constructor_args: Option<Vec<String>>,
+
+    #[arg(
+        id = "clone",
+        long = "clone",
+        value_name = "IMPLEMENTATION",
+        long_help = "Deploy a minimal proxy to the implementation with deployCreate2Clone instead of deploying init code.",
+        help_heading = "Deployment options",
+        group = "init-code-source",
+        conflicts_with = "constructor-args"
+    )]
+    pub clone: Option<String>,
+
+    #[arg(
+        id = "init-data",
+        long = "init-data",
+        long_help = "Calldata in hex format of the call CreateX makes to the contract right after deploying it.",
+        help_heading = "Deployment options"
+    )]
+    pub init_data: Option<String>,
+
+    #[arg(
+        id = "value",
+        long = "value",
+        long_help = "Wei sent to the constructor, in decimal or 0x-prefixed hex. Defaults to 0.",
+        help_heading = "Deployment options"
+    )]
+    pub value: Option<String>,
+
+    #[arg(
+        id = "init-value",
+        long = "init-value",
+        long_help = "Wei sent with the initialization call, in decimal or 0x-prefixed hex. Defaults to 0.",
+        help_heading = "Deployment options"
+    )]
+    pub init_value: Option<String>,
+
+    #[arg(
+        id = "refund",
+        long = "refund",
+        long_help = "Address receiving any balance left in CreateX after an initialized deployment.",
+        help_heading = "Deployment options",
+        conflicts_with = "clone"
+    )]
+    pub refund: Option<String>,
+
+    #[arg(
+        id = "chain-id",
+        long = "chain-id",
+        long_help = "Chain id the salt is protected for, required for salts with cross-chain redeploy protection.",
+        help_heading = "Deployment options"
+    )]
+    pub chain_id: Option<u64>,
+
+    #[arg(
+        id = "script",
+        long = "script",
+        long_help = "Write the Foundry script to this file. Defaults to Deploy_<address>.s.sol in the current directory.",
+        help_heading = "Deployment options"
+    )]
+    pub script: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EoaArgs {
+    #[clap(flatten)]
+    pub cli_args: CliArgs,
+
+    #[arg(
+        id = "public-key",
+        long,
+        long_help = "Set the secp256k1 public key in hex format, compressed or uncompressed. The miner searches for offsets k such that the address of public key + k·G matches, and only ever outputs the offsets, so that the private key never leaves your machine.
+
+Example: --public-key 0x0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798.",
+        help_heading = "EOA options"
+    )]
+    pub public_key: String,
+}
+
+#[derive(Args)]
+pub struct CombineArgs {
+    #[arg(
+        id = "record",
+        long_help = "Result record of the eoa subcommand, either as a line from a results file or as the path to an addr_<address>.csv file.",
+        value_name = "RECORD"
+    )]
+    pub record: String,
+
+    #[arg(
+        id = "private-key",
+        long,
+        long_help = "Set the private key of the public key the offset was mined for, in hex format.",
+        help_heading = "EOA options"
+    )]
+    pub private_key: String,
+}
+
+#[derive(Args)]
+pub struct PriceArgs {
+    #[arg(
+        id = "addresses",
+        long_help = "Addresses to value, in hex format.",
+        value_name = "ADDRESS"
+    )]
+    pub addresses: Vec<String>,
+
+    #[arg(
+        id = "results",
+        long,
+        long_help = "Value the address of every record in the addr_<address>.csv files of a results directory.\n\nExample: --results output.",
+        value_name = "DIR"
+    )]
+    pub results: Option<String>,
+
+    #[arg(
+        id = "job",
+        long,
+        long_help = "Score the addresses with the scoring and the thresholds of a job file.",
+        help_heading = "Pricing options"
+    )]
+    pub job: Option<String>,
+
+    #[arg(
+        id = "base-difficulty",
+        long,
+        long_help = "Difficulty of the addresses priced at the base price. Defaults to the base difficulty of the job, or 16^9.",
+        help_heading = "Pricing options"
+    )]
+    pub base_difficulty: Option<String>,
+
+    #[arg(
+        id = "base-price",
+        long,
+        default_value_t = 1.0,
+        long_help = "Price of the addresses at or below the base difficulty, more difficult ones being priced proportionally higher.",
+        help_heading = "Pricing options"
+    )]
+    pub base_price: f64,
+
+    #[arg(
+        id = "hashrate",
+        long,
+        default_value = "1G",
+        long_help = "Hashrate of a GPU, to express difficulties in GPU-hours. Accepts a K, M, G or T suffix.\n\nExample: --hashrate 2.5G.",
+        help_heading = "Pricing options"
+    )]
+    pub hashrate: String,
+
+    #[arg(
+        id = "format",
+        long,
+        value_enum,
+        default_value_t = ReportFormat::Csv,
+        long_help = "Format of the valuation report.",
+        help_heading = "Output options"
+    )]
+    pub format: ReportFormat,
+
+    #[arg(
+        id = "output",
+        long,
+        short,
+        long_help = "Write the report to a file instead of standard output.",
+        help_heading = "Output options"
+    )]
+    pub output: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ScoreArgs {
+    #[arg(
+        id = "addresses",
+        long_help = "Addresses to score, in hex format.",
+        value_name = "ADDRESS"
+    )]
+    pub addresses: Vec<String>,
+
+    #[arg(
+        id = "stdin",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Also score the addresses read from standard input, one per line. Anything after the address on a line, separated by a comma or whitespace, is ignored."
+    )]
+    pub stdin: bool,
+
+    #[arg(
+        id = "top",
+        long,
+        value_name = "N",
+        long_help = "Only print the N highest scoring addresses, from the highest down."
+    )]
+    pub top: Option<usize>,
+
+    #[arg(
+        id = "job",
+        long,
+        long_help = "Score the addresses with the scoring and the thresholds of a job file."
+    )]
+    pub job: Option<String>,
+
+    #[arg(
+        id = "json",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Print every score as a line of JSON.",
+        help_heading = "Output options"
+    )]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct ResultsArgs {
+    #[arg(
+        id = "sources",
+        default_value = crate::DEFAULT_OUTPUT_DIR,
+        long_help = "Output directories, whose results.csv and addr_<address>.csv files are read, or results files. Addresses found in several are listed once, with their first record.",
+        value_name = "SOURCE"
+    )]
+    pub sources: Vec<String>,
+
+    #[arg(
+        id = "job",
+        long,
+        long_help = "Rank the results with the scoring and the thresholds of a job file."
+    )]
+    pub job: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TopResultsArgs {
+    #[clap(flatten)]
+    pub results: ResultsArgs,
+
+    #[arg(
+        id = "count",
+        long,
+        short = 'n',
+        default_value_t = 10,
+        long_help = "Number of results to list."
+    )]
+    pub count: usize,
+
+    #[arg(
+        id = "per-category",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "List the top results of every scoring category rather than overall."
+    )]
+    pub per_category: bool,
+}
+
+#[derive(Args)]
+pub struct PruneResultsArgs {
+    #[arg(
+        id = "dir",
+        default_value = crate::DEFAULT_OUTPUT_DIR,
+        long_help = "Output directory whose results.csv is compacted, taking in its addr_<address>.csv files.",
+        value_name = "DIR"
+    )]
+    pub dir: String,
+
+    #[arg(
+        id = "keep",
+        long,
+        value_name = "K",
+        long_help = "Number of results of each scoring category to keep."
+    )]
+    pub keep: usize,
+
+    #[arg(
+        id = "job",
+        long,
+        long_help = "Rank the results with the scoring and the thresholds of a job file."
+    )]
+    pub job: Option<String>,
+}
+
+#[derive(Args)]
+pub struct MergeResultsArgs {
+    #[clap(flatten)]
+    pub results: ResultsArgs,
+
+    #[arg(
+        id = "into",
+        long,
+        value_name = "DIR",
+        long_help = "Output directory whose results.csv the results are merged into."
+    )]
+    pub into: String,
+
+    #[arg(
+        id = "keep",
+        long,
+        value_name = "K",
+        long_help = "Only keep the K most difficult results of each scoring category."
+    )]
+    pub keep: Option<usize>,
+}
+
+#[derive(Subcommand)]
+pub enum ResultsCommands {
+    #[command(about = "List every result, from the most difficult down, as CSV.")]
+    List(ResultsArgs),
+    #[command(about = "List the most difficult results, overall or of every category, as CSV.")]
+    Top(TopResultsArgs),
+    #[command(about = "Compact the results.csv of an output directory to the top results.")]
+    Prune(PruneResultsArgs),
+    #[command(about = "Merge results of several output directories into a results.csv.")]
+    Merge(MergeResultsArgs),
+}
+
+#[derive(Args)]
+pub struct DbArgs {
+    #[arg(
+        id = "db",
+        long,
+        value_name = "FILE",
+        default_value = crate::db::DEFAULT_DATABASE,
+        long_help = "Results database to use."
+    )]
+    pub db: String,
+}
+
+#[derive(Args)]
+pub struct DbQueryArgs {
+    #[clap(flatten)]
+    pub db: DbArgs,
+
+    #[arg(
+        id = "address",
+        long,
+        long_help = "Only list the results of an address."
+    )]
+    pub address: Option<String>,
+
+    #[arg(
+        id = "category",
+        long,
+        long_help = "Only list the results scoring highest in a category."
+    )]
+    pub category: Option<String>,
+
+    #[arg(
+        id = "min-difficulty",
+        long,
+        long_help = "Only list the results at least as difficult. Accepts a plain number, scientific notation or a power.\n\nExample: --min-difficulty 16^10."
+    )]
+    pub min_difficulty: Option<String>,
+
+    #[arg(id = "job-id", long, long_help = "Only list the results of a job.")]
+    pub job_id: Option<String>,
+
+    #[arg(
+        id = "pending",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Only list the results not marked as uploaded."
+    )]
+    pub pending: bool,
+
+    #[arg(
+        id = "limit",
+        long,
+        short = 'n',
+        long_help = "Only list the N most difficult results.",
+        value_name = "N"
+    )]
+    pub limit: Option<usize>,
+
+    #[arg(
+        id = "json",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Print every result as a line of JSON, including its score in every category.",
+        help_heading = "Output options"
+    )]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct DbImportArgs {
+    #[clap(flatten)]
+    pub db: DbArgs,
+
+    #[arg(
+        id = "sources",
+        required = true,
+        long_help = "Output directories, whose results.csv and addr_<address>.csv files are read, or results files.",
+        value_name = "SOURCE"
+    )]
+    pub sources: Vec<String>,
+
+    #[arg(
+        id = "job",
+        long,
+        long_help = "Score the results with the scoring and the thresholds of a job file."
+    )]
+    pub job: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DbMarkArgs {
+    #[clap(flatten)]
+    pub db: DbArgs,
+
+    #[arg(
+        id = "addresses",
         required = true,
-        visible_alias = "ch"
+        long_help = "Addresses whose results were uploaded, in hex format.",
+        value_name = "ADDRESS"
     )]
-    pub init_code_hash: String,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct DbJobArgs {
+    #[clap(flatten)]
+    pub db: DbArgs,
+
+    #[arg(id = "id", long_help = "Identifier of the job.", value_name = "ID")]
+    pub id: String,
+}
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    #[command(about = "List stored results, from the most difficult down, as CSV or JSON.")]
+    Query(DbQueryArgs),
+    #[command(about = "Store the results of output directories or results files.")]
+    Import(DbImportArgs),
+    #[command(about = "Mark the results of addresses as uploaded.")]
+    MarkUploaded(DbMarkArgs),
+    #[command(about = "Print the parameters of a job that stored results, as TOML.")]
+    Job(DbJobArgs),
 }
 
 #[derive(Subcommand)]
@@ -162,4 +993,52 @@ pub enum Commands {
     Create3(CliArgs),
     #[command(about = "Mine for a CREATE2 deployment address.")]
     Create2(Create2Args),
+    #[command(about = "Search a range of deployer nonces for a CREATE deployment address.")]
+    Create(CreateArgs),
+    #[command(about = "Mine for the salt nonce of a Safe proxy deployed by the SafeProxyFactory.")]
+    Safe(SafeArgs),
+    #[command(
+        about = "Generate CreateX calldata, a cast command and a Foundry script for a result."
+    )]
+    Export(ExportArgs),
+    #[command(
+        about = "Mine on the CPU for an offset to a public key yielding a vanity EOA address, without knowing its private key."
+    )]
+    Eoa(EoaArgs),
+    #[command(
+        about = "Combine a private key with an offset found by eoa into the vanity private key."
+    )]
+    Combine(CombineArgs),
+    #[command(
+        about = "Value addresses by their score, in GPU-hours and relative to a base price, as CSV or JSON."
+    )]
+    Price(PriceArgs),
+    #[command(
+        about = "Score addresses in every category, as when mining, to audit and compare them."
+    )]
+    Score(ScoreArgs),
+    #[command(
+        about = "List, rank and compact found results, one record per address across runs and output directories.",
+        subcommand
+    )]
+    Results(ResultsCommands),
+    #[command(
+        about = "Query the results database, to audit, re-price and re-upload results.",
+        subcommand
+    )]
+    Db(DbCommands),
+}
+
+impl Commands {
+    /// The mining arguments of the subcommand, if it mines.
+    pub fn cli_args(&self) -> Option<&CliArgs> {
+        match self {
+            Commands::Create3(args) => Some(args),
+            Commands::Create2(args) => Some(&args.cli_args),
+            Commands::Create(args) => Some(&args.cli_args),
+            Commands::Safe(args) => Some(&args.cli_args),
+            Commands::Eoa(args) => Some(&args.cli_args),
+            _ => None,
+        }
+    }
 }
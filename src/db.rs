@@ -0,0 +1,269 @@
+use crate::{decode_fixed, job::Job, record::ResultRecord, Config, CrunchError};
+use alloy_primitives::hex;
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension, Row};
+use std::{
+    fmt::Write as _,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Default location of the results database, in the default output directory.
+pub const DEFAULT_DATABASE: &str = "output/results.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    id TEXT PRIMARY KEY,
+    parameters TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    id INTEGER PRIMARY KEY,
+    address TEXT NOT NULL,
+    salt TEXT NOT NULL,
+    factory TEXT NOT NULL,
+    miner TEXT NOT NULL,
+    job_id TEXT NOT NULL,
+    init_code_hash TEXT,
+    init_code_source TEXT NOT NULL,
+    acceptance TEXT NOT NULL,
+    category TEXT NOT NULL,
+    difficulty REAL NOT NULL,
+    scores TEXT NOT NULL,
+    found_at INTEGER NOT NULL,
+    uploaded_at INTEGER,
+    UNIQUE (address, salt, factory)
+);
+CREATE INDEX IF NOT EXISTS results_address ON results (address);
+CREATE INDEX IF NOT EXISTS results_category ON results (category, difficulty);
+CREATE INDEX IF NOT EXISTS results_difficulty ON results (difficulty);
+";
+
+/// An accepted result as stored in the database.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbResult {
+    pub record: ResultRecord,
+    /// Category the address scores highest in.
+    pub category: String,
+    /// Weighted difficulty of the address in its category.
+    pub difficulty: f64,
+    /// Score of the address in every category, as JSON.
+    pub scores: String,
+    /// Unix time the result was stored at.
+    pub found_at: u64,
+    /// Unix time the result was marked as uploaded at, if it was.
+    pub uploaded_at: Option<u64>,
+}
+
+/// Filters of a results query. Unset filters match every result.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    pub address: Option<[u8; 20]>,
+    pub category: Option<String>,
+    pub min_difficulty: Option<f64>,
+    pub job_id: Option<String>,
+    /// Only match results not uploaded yet.
+    pub pending: bool,
+    pub limit: Option<usize>,
+}
+
+/// Embedded SQLite database of accepted results, along with the jobs they were found by.
+///
+/// Every record is stored once, however many times its address is found with the same salt and
+/// factory, with its score breakdown at the time it was found and whether it was uploaded.
+pub struct ResultsDb {
+    conn: Connection,
+}
+
+impl ResultsDb {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CrunchError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)?;
+        // the mining threads of several devices write to the same database
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Records the parameters of a job, so that its results can be traced back to it.
+    pub fn insert_job(&self, job: &Job) -> Result<(), CrunchError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO jobs (id, parameters) VALUES (?1, ?2)",
+            params![job.id(), job.to_toml()],
+        )?;
+        Ok(())
+    }
+
+    /// Parameters of a job, as TOML.
+    pub fn job(&self, id: &str) -> Result<Option<String>, CrunchError> {
+        Ok(self
+            .conn
+            .query_row("SELECT parameters FROM jobs WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    /// Stores a record scored with the config. Returns whether it was new to the database.
+    pub fn insert(&self, config: &Config, record: &ResultRecord) -> Result<bool, CrunchError> {
+        let score = config.score(&record.address);
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO results (address, salt, factory, miner, job_id, init_code_hash, \
+             init_code_source, acceptance, category, difficulty, scores, found_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                hex::encode_prefixed(record.address),
+                hex::encode_prefixed(record.salt),
+                hex::encode_prefixed(record.factory),
+                record.miner,
+                record.job_id,
+                record.init_code_hash.map(hex::encode_prefixed),
+                record.init_code_source,
+                record.acceptance,
+                score.category,
+                score.total_score,
+                serde_json::to_string(&score.scores).expect("scores are always serializable"),
+                now(),
+            ],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    /// Results matching the query, from the most difficult down.
+    pub fn query(&self, query: &Query) -> Result<Vec<DbResult>, CrunchError> {
+        let mut sql = "SELECT salt, address, factory, miner, job_id, init_code_hash, \
+                       init_code_source, acceptance, category, difficulty, scores, found_at, \
+                       uploaded_at FROM results WHERE 1 = 1"
+            .to_string();
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(address) = query.address {
+            sql.push_str(" AND address = ?");
+            values.push(Value::Text(hex::encode_prefixed(address)));
+        }
+        if let Some(category) = &query.category {
+            sql.push_str(" AND category = ?");
+            values.push(Value::Text(category.clone()));
+        }
+        if let Some(min_difficulty) = query.min_difficulty {
+            sql.push_str(" AND difficulty >= ?");
+            values.push(Value::Real(min_difficulty));
+        }
+        if let Some(job_id) = &query.job_id {
+            sql.push_str(" AND job_id = ?");
+            values.push(Value::Text(job_id.clone()));
+        }
+        if query.pending {
+            sql.push_str(" AND uploaded_at IS NULL");
+        }
+        sql.push_str(" ORDER BY difficulty DESC, id");
+        if let Some(limit) = query.limit {
+            write!(sql, " LIMIT {limit}").unwrap();
+        }
+
+        let mut statement = self.conn.prepare(&sql)?;
+        let rows = statement.query_map(params_from_iter(values), |row| Ok(read_row(row)))?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row??);
+        }
+        Ok(results)
+    }
+
+    /// Marks every result of an address as uploaded. Returns the number of results marked.
+    pub fn mark_uploaded(&self, address: &[u8; 20]) -> Result<usize, CrunchError> {
+        Ok(self.conn.execute(
+            "UPDATE results SET uploaded_at = ?1 WHERE address = ?2 AND uploaded_at IS NULL",
+            params![now(), hex::encode_prefixed(address)],
+        )?)
+    }
+}
+
+fn read_row(row: &Row) -> Result<DbResult, CrunchError> {
+    let text = |i: usize| row.get::<_, String>(i);
+    Ok(DbResult {
+        record: ResultRecord {
+            salt: decode_fixed("record salt", &text(0)?)?,
+            address: decode_fixed("record address", &text(1)?)?,
+            factory: decode_fixed("record factory", &text(2)?)?,
+            miner: text(3)?,
+            job_id: text(4)?,
+            init_code_hash: row
+                .get::<_, Option<String>>(5)?
+                .map(|hash| decode_fixed("record init code hash", &hash))
+                .transpose()?,
+            init_code_source: text(6)?,
+            acceptance: text(7)?,
+        },
+        category: text(8)?,
+        difficulty: row.get(9)?,
+        scores: text(10)?,
+        found_at: row.get(11)?,
+        uploaded_at: row.get(12)?,
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Stores an accepted record in the results database of the config.
+pub(crate) fn record(config: &Config, path: &str, record: &ResultRecord) {
+    if let Err(e) = ResultsDb::open(path).and_then(|db| db.insert(config, record)) {
+        log::error!("Failed to store solution in {}: {}", path, e);
+    }
+}
+
+/// Writes results as CSV, with a header line. Every line is the record of the result followed by
+/// its category, difficulty and the times it was found and uploaded at.
+pub fn to_csv(results: &[DbResult]) -> String {
+    let mut csv = "salt,address,factory,miner,job_id,init_code_hash,init_code_source,acceptance,\
+                   category,difficulty,found_at,uploaded_at\n"
+        .to_string();
+    for result in results {
+        writeln!(
+            csv,
+            "{},{},{},{},{}",
+            result.record,
+            result.category,
+            result.difficulty,
+            result.found_at,
+            result
+                .uploaded_at
+                .map(|t| t.to_string())
+                .unwrap_or_default()
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// Writes results as lines of JSON, including their score breakdown.
+pub fn to_json(results: &[DbResult]) -> String {
+    let mut json = String::new();
+    for result in results {
+        let scores: serde_json::Value =
+            serde_json::from_str(&result.scores).unwrap_or(serde_json::Value::Null);
+        let line = serde_json::json!({
+            "salt": hex::encode_prefixed(result.record.salt),
+            "address": hex::encode_prefixed(result.record.address),
+            "factory": hex::encode_prefixed(result.record.factory),
+            "miner": result.record.miner,
+            "job_id": result.record.job_id,
+            "init_code_hash": result.record.init_code_hash.map(hex::encode_prefixed),
+            "init_code_source": result.record.init_code_source,
+            "acceptance": result.record.acceptance,
+            "category": result.category,
+            "difficulty": result.difficulty,
+            "scores": scores,
+            "found_at": result.found_at,
+            "uploaded_at": result.uploaded_at,
+        });
+        writeln!(json, "{line}").unwrap();
+    }
+    json
+}
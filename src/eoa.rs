@@ -0,0 +1,227 @@
+use crate::{
+    append_record, decode_fixed,
+    events::{self, Event},
+    fancy::FancyPredicate,
+    log_gas_savings,
+    record::ResultRecord,
+    Config, CrunchError, RewardVariant,
+};
+use alloy_primitives::hex;
+use rand::{thread_rng, Rng};
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+use web3::signing::keccak256;
+
+/// Offsets checked by a thread before it reports its progress.
+const BATCH_SIZE: u64 = 1 << 16;
+
+/// An offset `k` for which the address of `pub + k·G` meets the reward criteria.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EoaSolution {
+    pub offset: [u8; 32],
+    pub address: [u8; 20],
+}
+
+/// Parses a compressed or uncompressed secp256k1 public key in hex.
+pub fn parse_public_key(value: &str) -> Result<PublicKey, CrunchError> {
+    let bytes = hex::decode(value).map_err(|_| CrunchError::InvalidHex {
+        field: "public key",
+        value: value.to_string(),
+    })?;
+    PublicKey::from_slice(&bytes)
+        .map_err(|_| CrunchError::InvalidKey(format!("{value} is not a secp256k1 public key")))
+}
+
+pub fn parse_secret_key(value: &str) -> Result<SecretKey, CrunchError> {
+    SecretKey::from_slice(&decode_fixed::<32>("private key", value)?)
+        .map_err(|_| CrunchError::InvalidKey("private key is out of range".to_string()))
+}
+
+/// Address of the EOA controlled by the key.
+pub fn address(public_key: &PublicKey) -> [u8; 20] {
+    keccak256(&public_key.serialize_uncompressed()[1..])[12..]
+        .try_into()
+        .unwrap()
+}
+
+/// Computes `pub + k·G`, the public key of the private key the offset combines into.
+pub fn offset_public_key(
+    public_key: &PublicKey,
+    offset: &[u8; 32],
+) -> Result<PublicKey, CrunchError> {
+    let offset = Scalar::from_be_bytes(*offset)
+        .map_err(|_| CrunchError::InvalidKey("offset is out of range".to_string()))?;
+    public_key
+        .add_exp_tweak(&Secp256k1::verification_only(), &offset)
+        .map_err(|_| CrunchError::InvalidKey("offset cancels out the public key".to_string()))
+}
+
+/// Reconstructs the private key of a mined address from the private key of the public key the
+/// miner was given and the offset it found.
+pub fn combine(secret_key: &SecretKey, offset: &[u8; 32]) -> Result<SecretKey, CrunchError> {
+    let offset = Scalar::from_be_bytes(*offset)
+        .map_err(|_| CrunchError::InvalidKey("offset is out of range".to_string()))?;
+    secret_key
+        .add_tweak(&offset)
+        .map_err(|_| CrunchError::InvalidKey("offset cancels out the private key".to_string()))
+}
+
+/// Checks `count` consecutive offsets starting at `start`, stepping from one public key to the
+/// next by adding the generator, and returns those whose address is accepted.
+pub fn search(
+    public_key: &PublicKey,
+    accepts: impl Fn(&[u8; 20]) -> bool,
+    start: &[u8; 32],
+    count: u64,
+) -> Result<Vec<EoaSolution>, CrunchError> {
+    let generator = PublicKey::from_secret_key(
+        &Secp256k1::signing_only(),
+        &SecretKey::from_slice(&one()).unwrap(),
+    );
+    let mut point = offset_public_key(public_key, start)?;
+    let mut solutions = Vec::new();
+
+    for i in 0..count {
+        let address = address(&point);
+        if accepts(&address) {
+            solutions.push(EoaSolution {
+                offset: add(start, i),
+                address,
+            });
+        }
+        point = point.combine(&generator).map_err(|_| {
+            CrunchError::InvalidKey("offset cancels out the public key".to_string())
+        })?;
+    }
+    Ok(solutions)
+}
+
+/// Searches offsets for the public key on every CPU core, recording each accepted address.
+pub fn mine(public_key: &PublicKey, config: &Config) -> Result<(), CrunchError> {
+    if std::fs::metadata(&config.output_dir).is_err() {
+        log::info!("Creating output directory");
+        std::fs::create_dir_all(&config.output_dir)?;
+    }
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    log::info!(
+        "Searching offsets for {} on {} threads",
+        hex::encode_prefixed(public_key.serialize()),
+        threads
+    );
+
+    let predicate = FancyPredicate::new(&config.scoring, &config.thresholds);
+    let accepts = |address: &[u8; 20]| match config.reward {
+        RewardVariant::Fancy => predicate.accepts(address),
+        _ => config.reward.matches(address),
+    };
+
+    let processed = AtomicU64::new(0);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| -> Result<(), CrunchError> {
+                    // a random start keeps the threads, and separate runs, from overlapping
+                    let mut start: [u8; 32] = thread_rng().gen();
+                    start[0] &= 0x7f;
+                    loop {
+                        for solution in search(public_key, accepts, &start, BATCH_SIZE)? {
+                            let total = processed.load(Ordering::Relaxed);
+                            record_solution(config, &solution, total);
+                        }
+                        start = add(&start, BATCH_SIZE);
+                        processed.fetch_add(BATCH_SIZE, Ordering::Relaxed);
+                    }
+                })
+            })
+            .collect();
+
+        let started = Instant::now();
+        let mut reported = (0, Instant::now());
+        while !workers.iter().any(|worker| worker.is_finished()) {
+            thread::sleep(Duration::from_secs(10));
+            let total = processed.load(Ordering::Relaxed);
+            match events::enabled() {
+                true => events::emit(&Event::progress(
+                    None,
+                    total,
+                    total - reported.0,
+                    reported.1.elapsed(),
+                    format!("{threads} threads"),
+                )),
+                false => println!(
+                    "Processed: {:.1}MH, avg {:.1}kH/s",
+                    total as f64 / 1.0E6,
+                    total as f64 / started.elapsed().as_secs_f64() / 1000.0
+                ),
+            }
+            reported = (total, Instant::now());
+        }
+        workers
+            .into_iter()
+            .find(|worker| worker.is_finished())
+            .unwrap()
+            .join()
+            .expect("search thread panicked")
+    })
+}
+
+/// Appends a solution scoring high enough to `addr_<address>.csv`, with the offset in place of a
+/// salt.
+fn record_solution(config: &Config, solution: &EoaSolution, total_processed: u64) {
+    let score = config.score(&solution.address);
+    let Some(acceptance) = config.acceptance(&solution.address, &score) else {
+        return;
+    };
+    log::info!(
+        "Found accepted solution: address: {}, score: {}, category: {}",
+        score.address_mixed_case,
+        score.total_score,
+        score.category
+    );
+    log_gas_savings(config, &solution.address);
+
+    let record = ResultRecord {
+        salt: solution.offset,
+        address: solution.address,
+        factory: [0u8; 20],
+        miner: format!(
+            "{}_{}",
+            env!("CARGO_PKG_VERSION"),
+            total_processed / 1000000000
+        ),
+        job_id: config.job_id.clone().unwrap_or_default(),
+        init_code_hash: None,
+        init_code_source: "eoa".to_string(),
+        acceptance,
+    };
+    match events::enabled() {
+        true => events::emit(&Event::solution(None, &record, &score)),
+        false => println!("{}", record),
+    }
+    append_record(config, &record);
+}
+
+fn one() -> [u8; 32] {
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    one
+}
+
+/// Adds `n` to a big-endian 256-bit number.
+fn add(value: &[u8; 32], n: u64) -> [u8; 32] {
+    let mut sum = *value;
+    let mut carry = n as u128;
+    for byte in sum.iter_mut().rev() {
+        if carry == 0 {
+            break;
+        }
+        carry += *byte as u128;
+        *byte = carry as u8;
+        carry >>= 8;
+    }
+    sum
+}
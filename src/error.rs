@@ -0,0 +1,163 @@
+use std::fmt::Display;
+
+/// Errors that can occur while configuring or running the miner.
+#[derive(Debug)]
+pub enum CrunchError {
+    InvalidHex {
+        field: &'static str,
+        value: String,
+    },
+    InvalidLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidChecksum {
+        field: &'static str,
+    },
+    InvalidThreshold {
+        value: u8,
+        max: u8,
+    },
+    InvalidPattern(&'static str),
+    InvalidJob(String),
+    InvalidConstructorArgs(String),
+    InvalidArtifact(String),
+    UnsupportedFactory(String),
+    InvalidRecord(String),
+    InvalidExport(String),
+    InvalidNonceRange(String),
+    InvalidSafeSetup(String),
+    InvalidSaltPreimage(String),
+    InvalidKey(String),
+    InvalidScoring(String),
+    InvalidPricing(String),
+    InvalidGas(String),
+    Database(rusqlite::Error),
+    InvalidSaltLayout(String),
+    InvalidWorkSize(String),
+    KernelBuild {
+        log: String,
+    },
+    DeviceNotFound {
+        index: usize,
+        available: usize,
+    },
+    Io(std::io::Error),
+    OpenCl(ocl::Error),
+}
+
+impl CrunchError {
+    /// Process exit code used by the CLI, so that scripts can tell failures apart.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CrunchError::InvalidHex { .. } => 3,
+            CrunchError::InvalidLength { .. } => 4,
+            CrunchError::InvalidChecksum { .. } => 5,
+            CrunchError::InvalidThreshold { .. } => 6,
+            CrunchError::InvalidPattern(_) => 7,
+            CrunchError::InvalidJob(_) => 8,
+            CrunchError::InvalidConstructorArgs(_) => 9,
+            CrunchError::InvalidArtifact(_) => 14,
+            CrunchError::UnsupportedFactory(_) => 15,
+            CrunchError::InvalidRecord(_) => 16,
+            CrunchError::InvalidExport(_) => 17,
+            CrunchError::InvalidNonceRange(_) => 18,
+            CrunchError::InvalidSafeSetup(_) => 19,
+            CrunchError::InvalidSaltPreimage(_) => 20,
+            CrunchError::InvalidKey(_) => 21,
+            CrunchError::InvalidScoring(_) => 22,
+            CrunchError::InvalidPricing(_) => 23,
+            CrunchError::InvalidGas(_) => 24,
+            CrunchError::Database(_) => 25,
+            CrunchError::InvalidSaltLayout(_) => 26,
+            CrunchError::InvalidWorkSize(_) => 27,
+            CrunchError::KernelBuild { .. } => 10,
+            CrunchError::DeviceNotFound { .. } => 11,
+            CrunchError::OpenCl(_) => 12,
+            CrunchError::Io(_) => 13,
+        }
+    }
+}
+
+impl Display for CrunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrunchError::InvalidHex { field, value } => {
+                write!(f, "{field} is not valid hex: {value}")
+            }
+            CrunchError::InvalidLength {
+                field,
+                expected,
+                actual,
+            } => write!(f, "{field} must be {expected} bytes long, got {actual}"),
+            CrunchError::InvalidChecksum { field } => write!(f, "{field} uses invalid checksum"),
+            CrunchError::InvalidThreshold { value, max } => {
+                write!(f, "threshold must be between 1 and {max}, got {value}")
+            }
+            CrunchError::InvalidPattern(reason) => write!(f, "{reason}"),
+            CrunchError::InvalidJob(reason) => write!(f, "invalid job: {reason}"),
+            CrunchError::InvalidConstructorArgs(reason) => {
+                write!(f, "invalid constructor arguments: {reason}")
+            }
+            CrunchError::InvalidArtifact(reason) => write!(f, "invalid artifact: {reason}"),
+            CrunchError::UnsupportedFactory(reason) => write!(f, "unsupported factory: {reason}"),
+            CrunchError::InvalidRecord(reason) => write!(f, "invalid result record: {reason}"),
+            CrunchError::InvalidExport(reason) => write!(f, "cannot export deployment: {reason}"),
+            CrunchError::InvalidNonceRange(reason) => write!(f, "invalid nonce range: {reason}"),
+            CrunchError::InvalidSafeSetup(reason) => write!(f, "invalid Safe setup: {reason}"),
+            CrunchError::InvalidSaltPreimage(reason) => {
+                write!(f, "invalid salt preimage: {reason}")
+            }
+            CrunchError::InvalidKey(reason) => write!(f, "invalid key: {reason}"),
+            CrunchError::InvalidScoring(reason) => write!(f, "invalid scoring: {reason}"),
+            CrunchError::InvalidPricing(reason) => write!(f, "invalid pricing: {reason}"),
+            CrunchError::InvalidGas(reason) => write!(f, "invalid gas criteria: {reason}"),
+            CrunchError::Database(e) => write!(f, "database error: {e}"),
+            CrunchError::InvalidSaltLayout(reason) => write!(f, "invalid salt layout: {reason}"),
+            CrunchError::InvalidWorkSize(reason) => write!(f, "invalid work size: {reason}"),
+            CrunchError::KernelBuild { log } => write!(f, "failed to build OpenCL kernel:\n{log}"),
+            CrunchError::DeviceNotFound { index, available } => write!(
+                f,
+                "OpenCL device {index} not found ({available} device(s) available)"
+            ),
+            CrunchError::Io(e) => write!(f, "I/O error: {e}"),
+            CrunchError::OpenCl(e) => write!(f, "OpenCL error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CrunchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CrunchError::Io(e) => Some(e),
+            CrunchError::OpenCl(e) => Some(e),
+            CrunchError::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for CrunchError {
+    fn from(e: std::io::Error) -> Self {
+        CrunchError::Io(e)
+    }
+}
+
+impl From<ocl::Error> for CrunchError {
+    fn from(e: ocl::Error) -> Self {
+        CrunchError::OpenCl(e)
+    }
+}
+
+impl From<ocl::OclCoreError> for CrunchError {
+    fn from(e: ocl::OclCoreError) -> Self {
+        CrunchError::OpenCl(e.into())
+    }
+}
+
+impl From<rusqlite::Error> for CrunchError {
+    fn from(e: rusqlite::Error) -> Self {
+        CrunchError::Database(e)
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{record::ResultRecord, score::FancyScore, CrunchError};
+use alloy_primitives::hex;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{
+    io::{self, Write},
+    sync::OnceLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Version of the event schemas, raised whenever a field is removed or changes meaning. Fields
+/// may be added to an event without raising it.
+pub const EVENTS_VERSION: u32 = 1;
+
+/// How mining progress is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ProgressFormat {
+    /// Progress lines for people to read.
+    #[default]
+    Text,
+    /// Newline-delimited JSON events for programs to read.
+    Json,
+}
+
+/// Stream the JSON events are written to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum EventStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// The stream installed by `--progress-format json`.
+static STREAM: OnceLock<EventStream> = OnceLock::new();
+
+/// A change in the state of a mining run. Devices are GPU indices, and are missing for the CPU
+/// search of the eoa subcommand.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The job is validated and mining starts on its devices.
+    Started {
+        job_id: String,
+        devices: Vec<u8>,
+        output_dir: String,
+        /// Addresses checked per accepted address on average, if it can be estimated.
+        expected_difficulty: Option<f64>,
+    },
+    /// A batch of addresses was checked.
+    Progress {
+        device: Option<u8>,
+        /// Addresses checked by the device since it started.
+        processed: u64,
+        /// Addresses checked in the batch.
+        hashes: u64,
+        elapsed_ms: u64,
+        /// Addresses checked per second in the batch.
+        hashrate: f64,
+        /// Where the device is in its search, e.g. `message 0a1b2c3d launch 7 of 16777216`.
+        position: String,
+    },
+    /// An address was accepted and recorded.
+    Solution {
+        device: Option<u8>,
+        address: String,
+        salt: String,
+        factory: String,
+        job_id: String,
+        acceptance: String,
+        category: String,
+        difficulty: f64,
+    },
+    /// A device stopped mining with an error.
+    DeviceError {
+        device: u8,
+        error: String,
+        exit_code: i32,
+    },
+    /// The run ended, with the exit code of the process.
+    Stopped {
+        reason: String,
+        error: Option<String>,
+        exit_code: i32,
+    },
+}
+
+impl Event {
+    pub fn progress(
+        device: Option<u8>,
+        processed: u64,
+        hashes: u64,
+        elapsed: Duration,
+        position: impl Into<String>,
+    ) -> Self {
+        Event::Progress {
+            device,
+            processed,
+            hashes,
+            elapsed_ms: elapsed.as_millis() as u64,
+            hashrate: match elapsed.is_zero() {
+                true => 0.0,
+                false => hashes as f64 / elapsed.as_secs_f64(),
+            },
+            position: position.into(),
+        }
+    }
+
+    pub fn solution(device: Option<u8>, record: &ResultRecord, score: &FancyScore) -> Self {
+        Event::Solution {
+            device,
+            address: score.address_mixed_case.clone(),
+            salt: hex::encode_prefixed(record.salt),
+            factory: hex::encode_prefixed(record.factory),
+            job_id: record.job_id.clone(),
+            acceptance: record.acceptance.clone(),
+            category: score.category.clone(),
+            difficulty: score.total_score,
+        }
+    }
+
+    pub fn device_error(device: u8, error: &CrunchError) -> Self {
+        Event::DeviceError {
+            device,
+            error: error.to_string(),
+            exit_code: error.exit_code(),
+        }
+    }
+
+    pub fn stopped(result: &Result<(), CrunchError>) -> Self {
+        match result {
+            Ok(()) => Event::Stopped {
+                reason: "completed".to_string(),
+                error: None,
+                exit_code: 0,
+            },
+            Err(e) => Event::Stopped {
+                reason: "error".to_string(),
+                error: Some(e.to_string()),
+                exit_code: e.exit_code(),
+            },
+        }
+    }
+
+    /// The event as a line of JSON, along with the schema version and the Unix time in
+    /// milliseconds it happened at.
+    pub fn to_json(&self, timestamp_ms: u64) -> String {
+        #[derive(Serialize)]
+        struct Envelope<'a> {
+            version: u32,
+            timestamp_ms: u64,
+            #[serde(flatten)]
+            event: &'a Event,
+        }
+        serde_json::to_string(&Envelope {
+            version: EVENTS_VERSION,
+            timestamp_ms,
+            event: self,
+        })
+        .expect("events are always serializable")
+    }
+}
+
+/// Writes the JSON events to the stream from now on, in place of the progress lines.
+pub fn install(stream: EventStream) {
+    let _ = STREAM.set(stream);
+}
+
+/// Whether JSON events are written.
+pub fn enabled() -> bool {
+    STREAM.get().is_some()
+}
+
+/// Writes the event as a line to the installed stream, if any.
+pub fn emit(event: &Event) {
+    let Some(stream) = STREAM.get() else {
+        return;
+    };
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let line = event.to_json(timestamp_ms);
+    // a locked write keeps the lines of concurrent devices whole
+    let _ = match stream {
+        EventStream::Stdout => writeln!(io::stdout().lock(), "{line}"),
+        EventStream::Stderr => writeln!(io::stderr().lock(), "{line}"),
+    };
+}
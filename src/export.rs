@@ -0,0 +1,311 @@
+use crate::{
+    factory::{create2_address, create3_address, FactoryKind, PROXY_CHILD_CODEHASH},
+    CrunchError, SaltVariant,
+};
+use alloy_primitives::{hex, Address};
+use std::fmt::Write as _;
+use web3::{
+    ethabi::{self, Token},
+    signing::keccak256,
+    types::U256,
+};
+
+/// What a CreateX call deploys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Create2 {
+        init_code: Vec<u8>,
+    },
+    Create3 {
+        init_code: Vec<u8>,
+    },
+    /// An EIP-1167 minimal proxy to the implementation, deployed with CREATE2.
+    Create2Clone {
+        implementation: [u8; 20],
+    },
+}
+
+/// A call to one of the `deployCreate2`/`deployCreate3` overloads of CreateX.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreateXCall {
+    /// The salt as mined, including its caller and cross-chain protection bytes. CreateX guards
+    /// it itself.
+    pub salt: [u8; 32],
+    pub target: Target,
+    /// Calldata of the initialization call made right after the deployment.
+    pub init_data: Option<Vec<u8>>,
+    /// Wei sent to the constructor.
+    pub constructor_value: U256,
+    /// Wei sent with the initialization call.
+    pub init_value: U256,
+    /// Receiver of any excess balance left after an initialized deployment.
+    pub refund: Option<[u8; 20]>,
+}
+
+impl CreateXCall {
+    pub fn new(salt: [u8; 32], target: Target) -> Self {
+        Self {
+            salt,
+            target,
+            init_data: None,
+            constructor_value: U256::zero(),
+            init_value: U256::zero(),
+            refund: None,
+        }
+    }
+
+    /// Whether the call goes to one of the `…AndInit` overloads.
+    fn is_init(&self) -> bool {
+        self.init_data.is_some() || !self.init_value.is_zero() || self.refund.is_some()
+    }
+
+    /// Solidity signature of the selected overload.
+    pub fn signature(&self) -> String {
+        match (&self.target, self.is_init(), self.refund.is_some()) {
+            (Target::Create2Clone { .. }, _, _) => "deployCreate2Clone(bytes32,address,bytes)",
+            (Target::Create2 { .. }, false, _) => "deployCreate2(bytes32,bytes)",
+            (Target::Create3 { .. }, false, _) => "deployCreate3(bytes32,bytes)",
+            (Target::Create2 { .. }, true, false) => {
+                "deployCreate2AndInit(bytes32,bytes,bytes,(uint256,uint256))"
+            }
+            (Target::Create3 { .. }, true, false) => {
+                "deployCreate3AndInit(bytes32,bytes,bytes,(uint256,uint256))"
+            }
+            (Target::Create2 { .. }, true, true) => {
+                "deployCreate2AndInit(bytes32,bytes,bytes,(uint256,uint256),address)"
+            }
+            (Target::Create3 { .. }, true, true) => {
+                "deployCreate3AndInit(bytes32,bytes,bytes,(uint256,uint256),address)"
+            }
+        }
+        .to_string()
+    }
+
+    /// ABI-encoded calldata of the call, selector included.
+    pub fn calldata(&self) -> Vec<u8> {
+        let salt = Token::FixedBytes(self.salt.to_vec());
+        let init_data = Token::Bytes(self.init_data.clone().unwrap_or_default());
+
+        let tokens = match &self.target {
+            Target::Create2Clone { implementation } => {
+                vec![salt, Token::Address((*implementation).into()), init_data]
+            }
+            Target::Create2 { init_code } | Target::Create3 { init_code } => {
+                let mut tokens = vec![salt, Token::Bytes(init_code.clone())];
+                if self.is_init() {
+                    tokens.push(init_data);
+                    tokens.push(Token::Tuple(vec![
+                        Token::Uint(self.constructor_value),
+                        Token::Uint(self.init_value),
+                    ]));
+                    if let Some(refund) = self.refund {
+                        tokens.push(Token::Address(refund.into()));
+                    }
+                }
+                tokens
+            }
+        };
+
+        let mut calldata = keccak256(self.signature().as_bytes())[..4].to_vec();
+        calldata.extend(ethabi::encode(&tokens));
+        calldata
+    }
+
+    /// Wei to send with the call. Clones forward all of it to the initialization call.
+    pub fn value(&self) -> U256 {
+        self.constructor_value + self.init_value
+    }
+
+    /// Init code hash the deployment's CREATE2 address depends on. For CREATE3, the init code
+    /// does not affect the address.
+    pub fn init_code_hash(&self) -> Option<[u8; 32]> {
+        match &self.target {
+            Target::Create2 { init_code } => Some(keccak256(init_code)),
+            Target::Create3 { .. } => None,
+            Target::Create2Clone { implementation } => {
+                Some(keccak256(&clone_init_code(implementation)))
+            }
+        }
+    }
+
+    /// Finds how CreateX guards the salt by matching the address it deploys to against the
+    /// expected one. The chain id is needed for salts with cross-chain redeploy protection.
+    pub fn resolve_salt_variant(
+        &self,
+        factory: &[u8; 20],
+        address: &[u8; 20],
+        chain_id: Option<u64>,
+    ) -> Result<SaltVariant, CrunchError> {
+        let mut calling_address = [0u8; 20];
+        calling_address.copy_from_slice(&self.salt[..20]);
+        let chain_id = chain_id.map(|chain_id| {
+            let mut arr = [0u8; 32];
+            arr[24..].copy_from_slice(&chain_id.to_be_bytes());
+            arr
+        });
+
+        let mut candidates = vec![SaltVariant::Random];
+        if calling_address != [0u8; 20] {
+            candidates.push(SaltVariant::Sender { calling_address });
+        }
+        if let Some(chain_id) = chain_id {
+            candidates.push(SaltVariant::Crosschain { chain_id });
+            candidates.push(SaltVariant::CrosschainSender {
+                chain_id,
+                calling_address,
+            });
+        }
+
+        candidates
+            .into_iter()
+            .find(|salt_variant| self.derive_address(factory, salt_variant) == *address)
+            .ok_or_else(|| {
+                let hint = match (self.salt[20], chain_id) {
+                    (1, None) => {
+                        ", the salt has cross-chain redeploy protection and needs the chain id"
+                    }
+                    _ => "",
+                };
+                CrunchError::InvalidExport(format!(
+                    "salt {} does not deploy to {} through {}{hint}",
+                    hex::encode_prefixed(self.salt),
+                    hex::encode_prefixed(address),
+                    hex::encode_prefixed(factory),
+                ))
+            })
+    }
+
+    /// Computes the address CreateX deploys to when the salt is guarded as `salt_variant`.
+    pub fn derive_address(&self, factory: &[u8; 20], salt_variant: &SaltVariant) -> [u8; 20] {
+        let salt = FactoryKind::CreateX.guard_salt(salt_variant, &self.salt);
+        match self.init_code_hash() {
+            Some(init_code_hash) => create2_address(factory, &salt, &init_code_hash),
+            None => create3_address(factory, &salt, &PROXY_CHILD_CODEHASH),
+        }
+    }
+
+    /// `cast send` command line performing the call.
+    pub fn cast_command(&self, factory: &[u8; 20]) -> String {
+        let mut command = format!(
+            "cast send {} {}",
+            checksum(factory),
+            hex::encode_prefixed(self.calldata())
+        );
+        if !self.value().is_zero() {
+            write!(command, " --value {}", self.value()).unwrap();
+        }
+        command
+    }
+
+    /// Foundry script broadcasting the call and checking the deployed address.
+    pub fn foundry_script(
+        &self,
+        factory: &[u8; 20],
+        address: &[u8; 20],
+        salt_variant: &SaltVariant,
+    ) -> String {
+        let (sender, chain_id) = match salt_variant {
+            SaltVariant::CrosschainSender {
+                chain_id,
+                calling_address,
+            } => (Some(calling_address), Some(chain_id)),
+            SaltVariant::Crosschain { chain_id } => (None, Some(chain_id)),
+            SaltVariant::Sender { calling_address } => (Some(calling_address), None),
+            SaltVariant::Random => (None, None),
+        };
+
+        let mut script = String::new();
+        writeln!(script, "// SPDX-License-Identifier: UNLICENSED").unwrap();
+        writeln!(script, "pragma solidity ^0.8.0;\n").unwrap();
+        writeln!(
+            script,
+            "import {{Script, console}} from \"forge-std/Script.sol\";\n"
+        )
+        .unwrap();
+        writeln!(
+            script,
+            "/// Deploys {} with CreateX's {}.",
+            checksum(address),
+            self.signature()
+        )
+        .unwrap();
+        writeln!(script, "contract Deploy is Script {{").unwrap();
+        writeln!(
+            script,
+            "    address constant CREATEX = {};",
+            checksum(factory)
+        )
+        .unwrap();
+        writeln!(
+            script,
+            "    address constant EXPECTED = {};\n",
+            checksum(address)
+        )
+        .unwrap();
+        writeln!(script, "    function run() external {{").unwrap();
+        if let Some(chain_id) = chain_id {
+            writeln!(
+                script,
+                "        require(block.chainid == {}, \"salt is protected against deployments on other chains\");",
+                U256::from_big_endian(chain_id)
+            )
+            .unwrap();
+        }
+        match sender {
+            // CreateX only accepts salts starting with an address from that address
+            Some(sender) => {
+                writeln!(script, "        vm.startBroadcast({});", checksum(sender)).unwrap()
+            }
+            None => writeln!(script, "        vm.startBroadcast();").unwrap(),
+        }
+        let value = match self.value().is_zero() {
+            true => String::new(),
+            false => format!("{{value: {}}}", self.value()),
+        };
+        writeln!(
+            script,
+            "        (bool success, bytes memory result) = CREATEX.call{value}(\n            hex\"{}\"\n        );",
+            hex::encode(self.calldata())
+        )
+        .unwrap();
+        writeln!(script, "        require(success, \"deployment failed\");").unwrap();
+        writeln!(script, "        vm.stopBroadcast();\n").unwrap();
+        writeln!(
+            script,
+            "        address deployed = abi.decode(result, (address));"
+        )
+        .unwrap();
+        writeln!(
+            script,
+            "        require(deployed == EXPECTED, \"deployed to an unexpected address\");"
+        )
+        .unwrap();
+        writeln!(script, "        console.log(\"Deployed at\", deployed);").unwrap();
+        writeln!(script, "    }}").unwrap();
+        writeln!(script, "}}").unwrap();
+        script
+    }
+}
+
+/// Creation code of the EIP-1167 minimal proxy CreateX deploys for `deployCreate2Clone`.
+pub fn clone_init_code(implementation: &[u8; 20]) -> Vec<u8> {
+    let mut init_code = hex!("3d602d80600a3d3981f3363d3d373d3d3d363d73").to_vec();
+    init_code.extend(implementation);
+    init_code.extend(hex!("5af43d82803e903d91602b57fd5bf3"));
+    init_code
+}
+
+/// Parses an amount of wei, given in decimal or as `0x`-prefixed hex.
+pub fn parse_amount(field: &'static str, value: &str) -> Result<U256, CrunchError> {
+    let amount = match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    };
+    amount.ok_or_else(|| {
+        CrunchError::InvalidExport(format!("{field} is not a valid amount: {value}"))
+    })
+}
+
+fn checksum(address: &[u8; 20]) -> String {
+    Address::from(*address).to_checksum(None)
+}
@@ -0,0 +1,295 @@
+use crate::{CreateXVariant, CrunchError, SaltVariant, DEFAULT_FACTORY};
+use alloy_primitives::hex;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use web3::signing::keccak256;
+
+/// keccak256 of the CREATE3 proxy bytecode `0x67363d3d37363d34f03d5260086018f3`, shared by
+/// CreateX, Solady and ZeframLou's CREATE3Factory.
+pub const PROXY_CHILD_CODEHASH: [u8; 32] = [
+    33, 195, 93, 190, 27, 52, 74, 36, 136, 207, 51, 33, 214, 206, 84, 47, 142, 159, 48, 85, 68,
+    255, 9, 228, 153, 58, 98, 49, 154, 73, 124, 31,
+];
+
+/// The factory contract that performs the deployment, which determines how the mined salt turns
+/// into an address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum FactoryKind {
+    /// Deploys with CREATE2 (or CREATE3 through the standard proxy) using the salt as is.
+    Plain,
+    /// CreateX, which guards the salt according to its sender and cross-chain protection bytes.
+    #[default]
+    #[value(name = "createx")]
+    CreateX,
+    /// Arachnid's deterministic deployment proxy.
+    Arachnid,
+    /// Safe singleton factory.
+    Safe,
+    /// Safe's SafeProxyFactory, which hashes the proxy's initializer into the salt nonce.
+    #[value(name = "safe-proxy")]
+    SafeProxy,
+    /// 0age's ImmutableCreate2Factory, which requires the salt to start with the caller.
+    Immutable,
+    /// A contract using Solady's CREATE3 library with the salt as is.
+    Solady,
+    /// ZeframLou's CREATE3Factory, which hashes the caller into the salt.
+    #[value(name = "zeframlou")]
+    ZeframLou,
+}
+
+impl Display for FactoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FactoryKind::Plain => write!(f, "plain"),
+            FactoryKind::CreateX => write!(f, "createx"),
+            FactoryKind::Arachnid => write!(f, "arachnid"),
+            FactoryKind::Safe => write!(f, "safe"),
+            FactoryKind::SafeProxy => write!(f, "safe-proxy"),
+            FactoryKind::Immutable => write!(f, "immutable"),
+            FactoryKind::Solady => write!(f, "solady"),
+            FactoryKind::ZeframLou => write!(f, "zeframlou"),
+        }
+    }
+}
+
+impl FactoryKind {
+    /// Address the factory is canonically deployed at, if there is one.
+    pub fn default_address(&self) -> Option<&'static str> {
+        match self {
+            FactoryKind::Plain => Some(DEFAULT_FACTORY),
+            FactoryKind::CreateX => Some("0xba5ed099633d3b313e4d5f7bdc1305d3c28ba5ed"),
+            FactoryKind::Arachnid => Some("0x4e59b44847b379578588920ca78fbf26c0b4956c"),
+            FactoryKind::Safe => Some("0x914d7fec6aac8cd542e72bca78b30650d45643d7"),
+            // v1.4.1
+            FactoryKind::SafeProxy => Some("0x4e1dcf7ad4e460cfd30791ccc4f9c8a4f820ec67"),
+            FactoryKind::Immutable => Some("0x0000000000ffe8b47b3e2130213b802212439497"),
+            FactoryKind::Solady => None,
+            FactoryKind::ZeframLou => Some("0x9fbb3df7c40da2e5a0de984ffe2ccb7c47cd0abf"),
+        }
+    }
+
+    /// Recognizes a factory by its canonical address. A plain factory has no address of its own,
+    /// it only deploys at the default one when no other is given.
+    pub fn from_address(address: &[u8; 20]) -> Option<Self> {
+        Self::value_variants()
+            .iter()
+            .copied()
+            .filter(|kind| *kind != FactoryKind::Plain)
+            .find(|kind| {
+                kind.default_address()
+                    .is_some_and(|default| hex::decode(default).is_ok_and(|d| d == address))
+            })
+    }
+
+    /// Whether the factory deploys with CREATE from its own nonce, like CreateX's `deployCreate`.
+    /// Plain CREATE deployments from an EOA use [`FactoryKind::Plain`].
+    pub fn supports_create(&self) -> bool {
+        matches!(self, FactoryKind::Plain | FactoryKind::CreateX)
+    }
+
+    pub fn supports_create2(&self) -> bool {
+        !matches!(self, FactoryKind::Solady | FactoryKind::ZeframLou)
+    }
+
+    pub fn supports_create3(&self) -> bool {
+        matches!(
+            self,
+            FactoryKind::Plain
+                | FactoryKind::CreateX
+                | FactoryKind::Solady
+                | FactoryKind::ZeframLou
+        )
+    }
+
+    /// Code hash of the proxy deployed with CREATE2 in the first step of a CREATE3 deployment.
+    pub fn proxy_codehash(&self) -> [u8; 32] {
+        PROXY_CHILD_CODEHASH
+    }
+
+    /// Checks that the factory can be used with the given deployment and salt variants.
+    pub fn validate(
+        &self,
+        create_variant: &CreateXVariant,
+        salt_variant: &SaltVariant,
+    ) -> Result<(), CrunchError> {
+        let unsupported =
+            |reason: &str| Err(CrunchError::UnsupportedFactory(format!("{self} {reason}")));
+
+        match create_variant {
+            CreateXVariant::Create { .. } if !self.supports_create() => {
+                return unsupported("does not support CREATE deployments");
+            }
+            CreateXVariant::Create { .. } if *salt_variant != SaltVariant::Random => {
+                return unsupported(
+                    "does not take a caller address or chain id for CREATE deployments",
+                );
+            }
+            CreateXVariant::Create2 { .. } if !self.supports_create2() => {
+                return unsupported("does not support CREATE2 deployments");
+            }
+            CreateXVariant::Create3 if !self.supports_create3() => {
+                return unsupported("does not support CREATE3 deployments");
+            }
+            _ => {}
+        }
+
+        match (self, salt_variant) {
+            (FactoryKind::CreateX, _) => Ok(()),
+            (_, SaltVariant::Crosschain { .. } | SaltVariant::CrosschainSender { .. }) => {
+                unsupported("does not support crosschain redeploy protection")
+            }
+            (FactoryKind::ZeframLou, SaltVariant::Random) => {
+                unsupported("requires the caller address")
+            }
+            (
+                FactoryKind::Plain
+                | FactoryKind::Arachnid
+                | FactoryKind::Safe
+                | FactoryKind::SafeProxy
+                | FactoryKind::Solady,
+                SaltVariant::Sender { .. },
+            ) => unsupported("does not take a caller address"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the fixed bytes of the salt passed to the factory, and the offset at which the 11
+    /// mined bytes are placed.
+    pub fn salt_template(&self, salt_variant: &SaltVariant) -> ([u8; 32], usize) {
+        let mut salt = [0u8; 32];
+        match (self, salt_variant) {
+            (
+                FactoryKind::CreateX,
+                SaltVariant::CrosschainSender {
+                    calling_address, ..
+                },
+            ) => {
+                salt[..20].copy_from_slice(calling_address);
+                salt[20] = 1;
+                (salt, 21)
+            }
+            (FactoryKind::CreateX, SaltVariant::Crosschain { .. }) => {
+                salt[20] = 1;
+                (salt, 21)
+            }
+            (
+                FactoryKind::CreateX | FactoryKind::Immutable,
+                SaltVariant::Sender { calling_address },
+            ) => {
+                salt[..20].copy_from_slice(calling_address);
+                (salt, 21)
+            }
+            // the first 20 bytes must be either the caller or zero
+            (FactoryKind::Immutable, _) => (salt, 21),
+            _ => (salt, 0),
+        }
+    }
+
+    /// Returns the preimage that the factory hashes to guard the salt, and the offset of the salt
+    /// within it, or `None` if the salt is used as is. Preimages depending on more than the
+    /// caller and chain id, like a Safe's initializer, are given by a
+    /// [`crate::preimage::SaltPreimage`].
+    pub fn guard_preimage(&self, salt_variant: &SaltVariant) -> Option<(Vec<u8>, usize)> {
+        match (self, salt_variant) {
+            // keccak256(abi.encode(msg.sender, block.chainid, salt))
+            (
+                FactoryKind::CreateX,
+                SaltVariant::CrosschainSender {
+                    chain_id,
+                    calling_address,
+                },
+            ) => {
+                let mut preimage = vec![0u8; 96];
+                preimage[12..32].copy_from_slice(calling_address);
+                preimage[32..64].copy_from_slice(chain_id);
+                Some((preimage, 64))
+            }
+            // keccak256(abi.encode(block.chainid, salt))
+            (FactoryKind::CreateX, SaltVariant::Crosschain { chain_id }) => {
+                let mut preimage = vec![0u8; 64];
+                preimage[..32].copy_from_slice(chain_id);
+                Some((preimage, 32))
+            }
+            // keccak256(abi.encode(msg.sender, salt))
+            (FactoryKind::CreateX, SaltVariant::Sender { calling_address }) => {
+                let mut preimage = vec![0u8; 64];
+                preimage[12..32].copy_from_slice(calling_address);
+                Some((preimage, 32))
+            }
+            // keccak256(abi.encode(salt))
+            (FactoryKind::CreateX, SaltVariant::Random) => Some((vec![0u8; 32], 0)),
+            // keccak256(abi.encodePacked(msg.sender, salt))
+            (FactoryKind::ZeframLou, SaltVariant::Sender { calling_address }) => {
+                let mut preimage = vec![0u8; 52];
+                preimage[..20].copy_from_slice(calling_address);
+                Some((preimage, 20))
+            }
+            _ => None,
+        }
+    }
+
+    /// Computes the salt the factory ultimately deploys with.
+    pub fn guard_salt(&self, salt_variant: &SaltVariant, salt: &[u8; 32]) -> [u8; 32] {
+        match self.guard_preimage(salt_variant) {
+            Some((mut preimage, offset)) => {
+                preimage[offset..offset + 32].copy_from_slice(salt);
+                keccak256(&preimage)
+            }
+            None => *salt,
+        }
+    }
+}
+
+/// Computes the address of a contract deployed with CREATE2.
+pub fn create2_address(
+    deployer: &[u8; 20],
+    salt: &[u8; 32],
+    init_code_hash: &[u8; 32],
+) -> [u8; 20] {
+    let mut preimage = [0u8; 85];
+    preimage[0] = 0xff;
+    preimage[1..21].copy_from_slice(deployer);
+    preimage[21..53].copy_from_slice(salt);
+    preimage[53..].copy_from_slice(init_code_hash);
+    keccak256(&preimage)[12..].try_into().unwrap()
+}
+
+/// Computes the address of a contract deployed with CREATE, i.e. `keccak256(rlp([deployer,
+/// nonce]))`.
+pub fn create_address(deployer: &[u8; 20], nonce: u64) -> [u8; 20] {
+    // RLP encodes 0 as the empty string, and nonces up to 0x7f as a single byte
+    let nonce_bytes = nonce.to_be_bytes();
+    let nonce_bytes = &nonce_bytes[nonce.leading_zeros() as usize / 8..];
+    let mut preimage = Vec::with_capacity(31);
+    preimage.push(0);
+    preimage.push(0x94);
+    preimage.extend(deployer);
+    match nonce {
+        0 => preimage.push(0x80),
+        1..=0x7f => preimage.push(nonce as u8),
+        _ => {
+            preimage.push(0x80 + nonce_bytes.len() as u8);
+            preimage.extend(nonce_bytes);
+        }
+    }
+    preimage[0] = 0xc0 + (preimage.len() - 1) as u8;
+    keccak256(&preimage)[12..].try_into().unwrap()
+}
+
+/// Computes the address of a contract deployed with CREATE3 through a proxy with the given code
+/// hash, which deploys the contract with CREATE at nonce 1.
+pub fn create3_address(
+    deployer: &[u8; 20],
+    salt: &[u8; 32],
+    proxy_codehash: &[u8; 32],
+) -> [u8; 20] {
+    let proxy = create2_address(deployer, salt, proxy_codehash);
+    let mut preimage = [0u8; 23];
+    preimage[0] = 0xd6;
+    preimage[1] = 0x94;
+    preimage[2..22].copy_from_slice(&proxy);
+    preimage[22] = 0x01;
+    keccak256(&preimage)[12..].try_into().unwrap()
+}
@@ -0,0 +1,478 @@
+use crate::hexspeak::Dictionary;
+use crate::score::{
+    exactly_letters_combinations_difficulty, total_combinations, AddressForms, ScorerRegistry,
+    Thresholds,
+};
+use std::fmt::Write as _;
+use web3::types::Address;
+
+/// Text of the address a run is counted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Text {
+    Lowercase,
+    Checksummed,
+    /// The first 8 and last 9 characters of the checksummed address.
+    ShortChecksummed,
+}
+
+/// Character a run is made of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Run {
+    Zero,
+    First,
+    /// The first character, if it is a letter. Addresses starting with a digit measure 0.
+    FirstLetter,
+}
+
+/// What a fancy category measures on an address, a number between 0 and 40.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// Length of the run of a character at the start of the text.
+    LeadingRun {
+        text: Text,
+        of: Run,
+    },
+    /// Number of letters, or 0 if the address holds two different digits.
+    Letters,
+    Digits,
+    /// 1 plus the number of characters repeating the one before.
+    Repeats,
+    /// 1 for every address.
+    Constant,
+}
+
+/// How the difficulty of a category grows with its measure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Constant(f64),
+    /// `base^(measure - offset)`.
+    Exponential {
+        base: f64,
+        offset: f64,
+    },
+    /// Odds of an address made of the measured number of letters and a single repeated digit.
+    Letters,
+    /// Odds of an all-digit address being as close to the smallest or the largest 40-digit
+    /// number. Addresses holding a letter have a difficulty of 1.
+    Decimal,
+}
+
+/// Declarative definition of a fancy category, from which both its scorer and the OpenCL
+/// predicate reporting the addresses it accepts are generated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Definition {
+    pub metric: Metric,
+    /// Subtracted from the measure to obtain the reported score.
+    pub score_offset: f64,
+    pub difficulty: Difficulty,
+}
+
+const MAX_DECIMAL: f64 = 9999999999999999999999999999999999999999f64;
+
+impl Metric {
+    pub fn measure(&self, address: &AddressForms) -> usize {
+        let lowercase = address.lower_case.as_str();
+        match self {
+            Metric::LeadingRun { text, of } => {
+                let text = match text {
+                    Text::Lowercase => lowercase,
+                    Text::Checksummed => &address.mixed_case,
+                    Text::ShortChecksummed => &address.short_etherscan,
+                };
+                let first = text.chars().next().unwrap();
+                let c = match of {
+                    Run::Zero => '0',
+                    Run::First => first,
+                    Run::FirstLetter if first.is_alphabetic() => first,
+                    Run::FirstLetter => return 0,
+                };
+                text.chars().take_while(|x| *x == c).count()
+            }
+            Metric::Letters => {
+                let mut digits = lowercase.chars().filter(|c| c.is_numeric());
+                match digits.next() {
+                    Some(digit) if digits.any(|c| c != digit) => 0,
+                    _ => lowercase.chars().filter(|c| c.is_alphabetic()).count(),
+                }
+            }
+            Metric::Digits => lowercase.chars().filter(|c| c.is_numeric()).count(),
+            Metric::Repeats => {
+                let chars = lowercase.as_bytes();
+                1 + chars.windows(2).filter(|pair| pair[0] == pair[1]).count()
+            }
+            Metric::Constant => 1,
+        }
+    }
+
+    /// Whether the measure depends on the case of the checksummed address.
+    fn is_cased(&self) -> bool {
+        matches!(
+            self,
+            Metric::LeadingRun {
+                text: Text::Checksummed | Text::ShortChecksummed,
+                of: Run::First | Run::FirstLetter,
+            }
+        )
+    }
+
+    /// OpenCL expression of the measure, reading the lowercase nibbles `n` and their short form
+    /// `s`, or the cased characters `c` and `cs` when `cased`.
+    fn opencl(&self, cased: bool) -> String {
+        let (full, short) = match cased {
+            true => ("c", "cs"),
+            false => ("n", "s"),
+        };
+        match self {
+            Metric::LeadingRun { text, of } => {
+                let (t, len) = match text {
+                    Text::Lowercase | Text::Checksummed => (full, 40),
+                    Text::ShortChecksummed => (short, 17),
+                };
+                match of {
+                    Run::Zero => format!("leadingRun({t}, {len}, 0)"),
+                    Run::First => format!("leadingRun({t}, {len}, {t}[0])"),
+                    Run::FirstLetter => {
+                        format!("(({t}[0] & 15) >= 10 ? leadingRun({t}, {len}, {t}[0]) : 0)")
+                    }
+                }
+            }
+            Metric::Letters => "letters(n)".to_string(),
+            Metric::Digits => "digits(n)".to_string(),
+            Metric::Repeats => "repeats(n)".to_string(),
+            Metric::Constant => "1".to_string(),
+        }
+    }
+}
+
+impl Difficulty {
+    pub fn of(&self, measure: usize, address: &AddressForms) -> f64 {
+        match self {
+            Difficulty::Decimal if measure == 40 => {
+                let number = address.lower_case.parse::<f64>().unwrap();
+                let odds = total_combinations(40.0) / 10.0f64.powf(measure as f64);
+                let difficulty1 = odds / (number / MAX_DECIMAL);
+                let difficulty2 = odds / ((MAX_DECIMAL - number) / MAX_DECIMAL);
+                difficulty1.max(difficulty2)
+            }
+            _ => self.of_measure(measure),
+        }
+    }
+
+    /// Difficulty of a measure, for any address but all-digit ones scored by [`Difficulty::Decimal`].
+    fn of_measure(&self, measure: usize) -> f64 {
+        match self {
+            Difficulty::Constant(difficulty) => *difficulty,
+            Difficulty::Exponential { base, offset } => base.powf(measure as f64 - offset),
+            Difficulty::Letters => exactly_letters_combinations_difficulty(measure as f64, 40.0),
+            Difficulty::Decimal => 1.0f64,
+        }
+    }
+}
+
+impl Definition {
+    /// Returns the score and the difficulty of the address.
+    pub fn score(&self, address: &AddressForms) -> (f64, f64) {
+        let measure = self.metric.measure(address);
+        (
+            measure as f64 - self.score_offset,
+            self.difficulty.of(measure, address),
+        )
+    }
+}
+
+/// Which measures of a category reach the minimum difficulty.
+#[derive(Clone, Debug)]
+struct Acceptance {
+    name: String,
+    metric: Metric,
+    measures: [bool; 41],
+    /// All-digit addresses at or below the first bound, or at or above the second, as digits.
+    decimal: Option<([u8; 40], [u8; 40])>,
+}
+
+/// Words of a dictionary category whose weighted difficulty reaches the minimum.
+#[derive(Clone, Debug)]
+struct WordAcceptance {
+    name: String,
+    /// Whether every address is accepted, not containing any word being enough.
+    all: bool,
+    /// Words accepted at the start or the end of the address, in hex.
+    anchored: Vec<String>,
+    /// Words accepted anywhere in the address, a subset of the anchored ones.
+    anywhere: Vec<String>,
+}
+
+/// Predicate reporting the addresses that score at least the minimum difficulty of a category,
+/// generated from the definitions of the categories in a registry.
+///
+/// The kernel evaluates [`FancyPredicate::opencl`], so that it reports exactly the addresses the
+/// scorer accepts. Dictionary categories are prefiltered on the first and last byte of the
+/// address before their words are compared. Categories with neither a definition nor a dictionary
+/// are only scored on the addresses reported for the others.
+#[derive(Clone, Debug)]
+pub struct FancyPredicate {
+    categories: Vec<Acceptance>,
+    dictionaries: Vec<WordAcceptance>,
+    cpu_only: Vec<String>,
+}
+
+impl FancyPredicate {
+    pub fn new(registry: &ScorerRegistry, thresholds: &Thresholds) -> Self {
+        let mut categories = Vec::new();
+        let mut dictionaries = Vec::new();
+        let mut cpu_only = Vec::new();
+        for (scorer, weight) in registry.weighted() {
+            let min_difficulty = thresholds.minimum(&scorer.name());
+            if let Some(dictionary) = scorer.dictionary() {
+                let accepted = |anchored: bool| -> Vec<String> {
+                    let words = dictionary.words().iter();
+                    words
+                        .filter(|word| {
+                            Dictionary::difficulty(word.len(), anchored) * weight >= min_difficulty
+                        })
+                        .cloned()
+                        .collect()
+                };
+                dictionaries.push(WordAcceptance {
+                    name: scorer.name(),
+                    all: weight >= min_difficulty,
+                    anchored: accepted(true),
+                    anywhere: accepted(false),
+                });
+                continue;
+            }
+            let Some(definition) = scorer.definition() else {
+                cpu_only.push(scorer.name());
+                continue;
+            };
+            let accepts = |difficulty: f64| difficulty * weight >= min_difficulty;
+            let measures = std::array::from_fn(|measure| {
+                accepts(definition.difficulty.of_measure(measure))
+                    && !(definition.difficulty == Difficulty::Decimal && measure == 40)
+            });
+            let decimal = (definition.difficulty == Difficulty::Decimal)
+                .then(|| decimal_bounds(weight, min_difficulty));
+            categories.push(Acceptance {
+                name: scorer.name(),
+                metric: definition.metric,
+                measures,
+                decimal,
+            });
+        }
+        Self {
+            categories,
+            dictionaries,
+            cpu_only,
+        }
+    }
+
+    /// Categories that have neither a definition nor a dictionary, and are not checked by the
+    /// kernel.
+    pub fn cpu_only(&self) -> &[String] {
+        &self.cpu_only
+    }
+
+    /// Categories whose check needs the checksummed address, which the kernel only hashes once
+    /// the lowercase address can reach their minimum. Categories accepting no address are left
+    /// out, as they are not checked at all.
+    pub fn checksummed(&self) -> Vec<&str> {
+        self.categories
+            .iter()
+            .filter(|category| category.metric.is_cased() && category.measures.contains(&true))
+            .map(|category| category.name.as_str())
+            .collect()
+    }
+
+    /// Evaluates the predicate the way the kernel does.
+    pub fn accepts(&self, address: &[u8; 20]) -> bool {
+        let forms = AddressForms::new(&Address::from_slice(address));
+        self.categories.iter().any(|category| {
+            let measure = category.metric.measure(&forms);
+            category.measures[measure]
+                || category.decimal.as_ref().is_some_and(|(low, high)| {
+                    let digits: Vec<u8> = forms.lower_case.bytes().map(|c| c - b'0').collect();
+                    measure == 40 && (digits[..] <= low[..] || digits[..] >= high[..])
+                })
+        }) || self.dictionaries.iter().any(|dictionary| {
+            let address = forms.lower_case.as_str();
+            dictionary.all
+                || dictionary.anchored.iter().any(|word| {
+                    address.starts_with(word.as_str()) || address.ends_with(word.as_str())
+                })
+                || dictionary
+                    .anywhere
+                    .iter()
+                    .any(|word| address.contains(word.as_str()))
+        })
+    }
+
+    /// Defines the acceptance tables and `FANCY_CHECKS()`, the body of `isFancy` in the kernel.
+    pub fn opencl(&self) -> String {
+        let mut src = String::new();
+        let mut checks = Vec::new();
+        for (i, category) in self.categories.iter().enumerate() {
+            if !category.measures.contains(&true) && category.decimal.is_none() {
+                continue;
+            }
+            let table = category.measures.map(|accept| format!("{}", accept as u8));
+            writeln!(src, "// {}", category.name).unwrap();
+            writeln!(
+                src,
+                "__constant uchar FANCY_{i}[41] = {{{}}};",
+                table.join(", ")
+            )
+            .unwrap();
+
+            let metric = category.metric;
+            let mut condition = format!("FANCY_{i}[{}]", metric.opencl(metric.is_cased()));
+            if let Some((low, high)) = &category.decimal {
+                for (bound, digits) in [("LOW", low), ("HIGH", high)] {
+                    let digits = digits.map(|digit| format!("{digit}"));
+                    writeln!(
+                        src,
+                        "__constant uchar FANCY_{i}_{bound}[40] = {{{}}};",
+                        digits.join(", ")
+                    )
+                    .unwrap();
+                }
+                condition = format!(
+                    "{condition} || (digits(n) == 40 && decimalAccept(n, FANCY_{i}_LOW, FANCY_{i}_HIGH))"
+                );
+            }
+            match metric.is_cased() {
+                // the run in the lowercase address bounds the run in the checksummed one, which
+                // is only hashed when the bound can be accepted
+                true => {
+                    let min = category.measures.iter().position(|accept| *accept).unwrap();
+                    checks.push(format!("if ({} >= {min}) {{", metric.opencl(false)));
+                    checks.push(
+                        "  if (!hashed) { checksum(n, c); toShort(c, cs); hashed = true; }"
+                            .to_string(),
+                    );
+                    checks.push(format!("  if ({condition}) return true;"));
+                    checks.push("}".to_string());
+                }
+                false => checks.push(format!("if ({condition}) return true;")),
+            }
+        }
+        for (i, dictionary) in self.dictionaries.iter().enumerate() {
+            if dictionary.all {
+                checks.push("return true;".to_string());
+                continue;
+            }
+            if dictionary.anchored.is_empty() {
+                continue;
+            }
+            writeln!(src, "// {}", dictionary.name).unwrap();
+            // the first and last bytes the words can start and end with
+            let mut head = [0u8; 256];
+            let mut tail = [0u8; 256];
+            for word in &dictionary.anchored {
+                let nibbles = nibbles(word);
+                let (first, last) = (nibbles[0] as usize, nibbles[nibbles.len() - 1] as usize);
+                for other in 0..16 {
+                    match nibbles.len() {
+                        1 => {
+                            head[first * 16 + other] = 1;
+                            tail[other * 16 + last] = 1;
+                        }
+                        n => {
+                            head[first * 16 + nibbles[1] as usize] = 1;
+                            tail[nibbles[n - 2] as usize * 16 + last] = 1;
+                        }
+                    }
+                }
+            }
+            for (table, bytes) in [("HEAD", head), ("TAIL", tail)] {
+                let bytes = bytes.map(|byte| format!("{byte}"));
+                writeln!(
+                    src,
+                    "__constant uchar WORDS_{i}_{table}[256] = {{{}}};",
+                    bytes.join(", ")
+                )
+                .unwrap();
+            }
+            for (list, words) in [
+                ("ANCHORED", &dictionary.anchored),
+                ("ANYWHERE", &dictionary.anywhere),
+            ] {
+                if words.is_empty() {
+                    continue;
+                }
+                write_words(&mut src, &format!("WORDS_{i}_{list}"), words);
+            }
+            checks.push(format!(
+                "if (anchoredWord(n, WORDS_{i}_HEAD, WORDS_{i}_TAIL, WORDS_{i}_ANCHORED, WORDS_{i}_ANCHORED_ENDS, {})) return true;",
+                dictionary.anchored.len()
+            ));
+            if !dictionary.anywhere.is_empty() {
+                checks.push(format!(
+                    "if (wordAnywhere(n, WORDS_{i}_ANYWHERE, WORDS_{i}_ANYWHERE_ENDS, {})) return true;",
+                    dictionary.anywhere.len()
+                ));
+            }
+        }
+        writeln!(src, "#define FANCY_CHECKS() \\").unwrap();
+        for check in checks {
+            writeln!(src, "  {check} \\").unwrap();
+        }
+        writeln!(src).unwrap();
+        src
+    }
+}
+
+fn nibbles(word: &str) -> Vec<u8> {
+    word.chars()
+        .map(|c| c.to_digit(16).unwrap() as u8)
+        .collect()
+}
+
+/// Defines the nibbles of the words back to back, and the offset each of them ends at.
+fn write_words(src: &mut String, name: &str, words: &[String]) {
+    let nibbles: Vec<String> = words
+        .iter()
+        .flat_map(|word| nibbles(word))
+        .map(|nibble| format!("{nibble}"))
+        .collect();
+    let ends: Vec<String> = words
+        .iter()
+        .scan(0, |end, word| {
+            *end += word.len();
+            Some(format!("{end}"))
+        })
+        .collect();
+    writeln!(
+        src,
+        "__constant uchar {name}[{}] = {{{}}};",
+        nibbles.len(),
+        nibbles.join(", ")
+    )
+    .unwrap();
+    writeln!(
+        src,
+        "__constant uint {name}_ENDS[{}] = {{{}}};",
+        ends.len(),
+        ends.join(", ")
+    )
+    .unwrap();
+}
+
+/// Bounds of the all-digit addresses with a weighted [`Difficulty::Decimal`] of at least the
+/// minimum: those within `odds · weight / min` of either end of the 40-digit numbers.
+fn decimal_bounds(weight: f64, min_difficulty: f64) -> ([u8; 40], [u8; 40]) {
+    let odds = total_combinations(40.0) / 10.0f64.powf(40.0);
+    let distance = (MAX_DECIMAL * odds * weight / min_difficulty).min(MAX_DECIMAL);
+    let digits = |number: f64| -> [u8; 40] {
+        // the largest 40-digit number rounds up to 10^40
+        if number >= MAX_DECIMAL {
+            return [9; 40];
+        }
+        let number = format!("{:040.0}", number.max(0.0));
+        let digits: Vec<u8> = number.bytes().map(|c| c - b'0').collect();
+        digits.try_into().unwrap()
+    };
+    (
+        digits(distance.floor()),
+        digits((MAX_DECIMAL - distance).ceil()),
+    )
+}
@@ -0,0 +1,100 @@
+use crate::{zero_bytes_probability, CrunchError};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Calldata gas of a zero byte, see EIP-2028.
+pub const ZERO_BYTE_GAS: u64 = 4;
+/// Calldata gas of a non-zero byte, see EIP-2028.
+pub const NONZERO_BYTE_GAS: u64 = 16;
+
+/// Most gas an address can save per reference: twenty leading zero bytes.
+pub const MAX_GAS: u64 = 20 * NONZERO_BYTE_GAS;
+
+/// Calldata gas an address saves each time it is passed around, compared to an address without
+/// zero bytes.
+///
+/// Every zero byte costs 12 gas less than a non-zero one. Compact encodings, like packed calldata
+/// or a `PUSHn` of the address, also drop the leading zero bytes altogether, saving their
+/// remaining 4 gas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct GasScore {
+    pub zero_bytes: u8,
+    pub leading_zero_bytes: u8,
+    /// Gas saved per reference to the address.
+    pub gas: u64,
+}
+
+impl GasScore {
+    pub fn new(address: &[u8; 20]) -> Self {
+        let zero_bytes = address.iter().filter(|byte| **byte == 0).count() as u8;
+        let leading_zero_bytes = address.iter().take_while(|byte| **byte == 0).count() as u8;
+        Self {
+            zero_bytes,
+            leading_zero_bytes,
+            gas: gas(zero_bytes, leading_zero_bytes),
+        }
+    }
+
+    /// Gas saved by an address referenced `references` times, e.g. in every call to a contract.
+    pub fn saved(&self, references: u64) -> u64 {
+        self.gas.saturating_mul(references)
+    }
+}
+
+impl Display for GasScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} gas per reference ({} zero bytes, {} leading)",
+            self.gas, self.zero_bytes, self.leading_zero_bytes
+        )
+    }
+}
+
+fn gas(zero_bytes: u8, leading_zero_bytes: u8) -> u64 {
+    (NONZERO_BYTE_GAS - ZERO_BYTE_GAS) * zero_bytes as u64
+        + ZERO_BYTE_GAS * leading_zero_bytes as u64
+}
+
+/// Reference count of gas criteria that leave it out.
+pub(crate) fn one_reference() -> u64 {
+    1
+}
+
+/// Gas an address has to save per reference to save `min_gas` over `references` references.
+pub fn min_gas_per_reference(min_gas: u64, references: u64) -> Result<u64, CrunchError> {
+    if min_gas == 0 || references == 0 {
+        return Err(CrunchError::InvalidGas(
+            "the gas threshold and the reference count must be positive".to_string(),
+        ));
+    }
+    let per_reference = min_gas.div_ceil(references);
+    if per_reference > MAX_GAS {
+        return Err(CrunchError::InvalidGas(format!(
+            "saving {min_gas} gas over {references} references takes {per_reference} gas per \
+             reference, at most {MAX_GAS} can be saved"
+        )));
+    }
+    Ok(per_reference)
+}
+
+/// Expected number of addresses to check to find one saving at least `min_gas` per reference.
+pub fn difficulty(min_gas: u64) -> f64 {
+    let p = 1.0f64 / 256.0;
+    // the address starts with `leading` zero bytes followed by a non-zero one, while the zero
+    // bytes among the rest are binomially distributed
+    let mut probability = match gas(20, 20) >= min_gas {
+        true => p.powi(20),
+        false => 0.0,
+    };
+    for leading in 0..20u8 {
+        let rest = 19 - leading as u32;
+        let Some(zeros) = (0..=rest as u8).find(|zeros| gas(leading + zeros, leading) >= min_gas)
+        else {
+            continue;
+        };
+        probability +=
+            p.powi(leading as i32) * (1.0 - p) * zero_bytes_probability(rest, zeros as u32);
+    }
+    1.0 / probability
+}
@@ -0,0 +1,158 @@
+use crate::{
+    score::{AddressForms, Scorer},
+    CrunchError,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Words of the built-in dictionary, spelled out; see [`spell`].
+pub const DEFAULT_WORDS: [&str; 32] = [
+    "abode", "accede", "acid", "added", "babe", "based", "beef", "best", "bold", "boss", "cafe",
+    "coffee", "code", "cool", "dead", "deadbeef", "decade", "decaf", "defi", "face", "facade",
+    "faded", "feed", "food", "idea", "oddball", "safe", "seed", "sold", "solid", "toast", "total",
+];
+
+/// Spells a word with the characters of an address, reading `0` as `o`, `1` as `i` or `l`, `5`
+/// as `s` and `7` as `t`. Returns `None` for words with any other letter.
+pub fn spell(word: &str) -> Option<String> {
+    word.chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c @ ('0'..='9' | 'a'..='f') => Some(c),
+            'o' => Some('0'),
+            'i' | 'l' => Some('1'),
+            's' => Some('5'),
+            't' => Some('7'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A list of hex-speak words, scoring addresses by the rarest of them they contain.
+///
+/// A word of `n` characters at the start or the end of the address has a difficulty of `16^n`,
+/// while one found anywhere else can sit at any of `41 - n` positions. The kernel checks the words
+/// whose difficulty can reach the minimum, see [`crate::fancy::FancyPredicate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dictionary {
+    words: Vec<String>,
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::new(DEFAULT_WORDS).unwrap()
+    }
+}
+
+impl Dictionary {
+    /// Spells the words, failing on those that cannot be spelled or do not fit in an address.
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Result<Self, CrunchError> {
+        let mut spelled = Vec::new();
+        for word in words {
+            let word = word.as_ref();
+            match spell(word) {
+                Some(hex) if !hex.is_empty() && hex.len() <= 40 => spelled.push(hex),
+                _ => {
+                    return Err(CrunchError::InvalidScoring(format!(
+                        "{word} cannot be spelled in an address"
+                    )))
+                }
+            }
+        }
+        if spelled.is_empty() {
+            return Err(CrunchError::InvalidScoring(
+                "the dictionary holds no word".to_string(),
+            ));
+        }
+        spelled.sort();
+        spelled.dedup();
+        Ok(Self { words: spelled })
+    }
+
+    /// Reads a word list, one word per line, skipping the words that cannot be spelled.
+    pub fn from_word_list(list: &str) -> Result<Self, CrunchError> {
+        Self::new(spellable(list))
+    }
+
+    /// The words, in hex.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Difficulty of a word of `length` characters at the start or the end of the address, if
+    /// `anchored`, or anywhere in it, where it can start at any of `41 - length` positions. Custom
+    /// word categories score their words anywhere in the address with it too.
+    pub fn difficulty(length: usize, anchored: bool) -> f64 {
+        let difficulty = 16.0f64.powi(length as i32);
+        match anchored {
+            true => difficulty,
+            false => (difficulty / (41 - length) as f64).max(1.0),
+        }
+    }
+
+    /// The built-in dictionary the `hex_speak` category scores with.
+    pub fn builtin() -> &'static Dictionary {
+        static BUILTIN: OnceLock<Dictionary> = OnceLock::new();
+        BUILTIN.get_or_init(Dictionary::default)
+    }
+}
+
+impl Scorer for Dictionary {
+    fn name(&self) -> String {
+        "hex_speak".to_string()
+    }
+
+    fn score(&self, address: &AddressForms) -> (f64, f64) {
+        let address = address.lower_case.as_str();
+        let mut best = (0.0, 1.0);
+        for word in &self.words {
+            let anchored = address.starts_with(word.as_str()) || address.ends_with(word.as_str());
+            if !anchored && !address.contains(word.as_str()) {
+                continue;
+            }
+            let difficulty = Dictionary::difficulty(word.len(), anchored);
+            if difficulty > best.1 {
+                best = (word.len() as f64, difficulty);
+            }
+        }
+        best
+    }
+
+    fn dictionary(&self) -> Option<&Dictionary> {
+        Some(self)
+    }
+}
+
+/// The words of the `hex_speak` category in a job file. A spec without words or a word list
+/// selects the built-in dictionary.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DictionarySpec {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<String>,
+    /// Word list with a word per line. Words that cannot be spelled in an address are skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl DictionarySpec {
+    pub fn is_builtin(&self) -> bool {
+        self.words.is_empty() && self.file.is_none()
+    }
+
+    pub fn dictionary(&self) -> Result<Dictionary, CrunchError> {
+        let mut words: Vec<&str> = self.words.iter().map(String::as_str).collect();
+        let list = match &self.file {
+            Some(file) => std::fs::read_to_string(file)?,
+            None => String::new(),
+        };
+        words.extend(spellable(&list));
+        Dictionary::new(words)
+    }
+}
+
+/// The words of a word list that can be spelled in an address.
+fn spellable(list: &str) -> impl Iterator<Item = &str> {
+    list.lines()
+        .map(str::trim)
+        .filter(|word| spell(word).is_some_and(|hex| !hex.is_empty() && hex.len() <= 40))
+}
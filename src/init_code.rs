@@ -0,0 +1,174 @@
+use crate::CrunchError;
+use alloy_primitives::hex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use web3::ethabi::{
+    self,
+    param_type::{ParamType, Reader},
+    token::{LenientTokenizer, Tokenizer},
+};
+use web3::signing::keccak256;
+
+/// Where the init code of a CREATE2 deployment came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InitCodeSource {
+    Bytecode,
+    File(PathBuf),
+    Artifact(PathBuf),
+}
+
+impl Display for InitCodeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitCodeSource::Bytecode => write!(f, "bytecode"),
+            InitCodeSource::File(path) => write!(f, "file:{}", path.display()),
+            InitCodeSource::Artifact(path) => write!(f, "artifact:{}", path.display()),
+        }
+    }
+}
+
+/// Constructor arguments, given as a signature such as `constructor(address,uint256)` and one
+/// value per parameter.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConstructorArgs {
+    pub signature: String,
+    pub values: Vec<String>,
+}
+
+impl ConstructorArgs {
+    /// ABI-encodes the values according to the signature.
+    pub fn encode(&self) -> Result<Vec<u8>, CrunchError> {
+        let params = parse_signature(&self.signature)?;
+        if params.len() != self.values.len() {
+            return Err(CrunchError::InvalidConstructorArgs(format!(
+                "signature takes {} argument(s), got {}",
+                params.len(),
+                self.values.len()
+            )));
+        }
+        let tokens = params
+            .iter()
+            .zip(&self.values)
+            .map(|(param, value)| {
+                LenientTokenizer::tokenize(param, value).map_err(|e| {
+                    CrunchError::InvalidConstructorArgs(format!(
+                        "could not encode {value} as {param}: {e}"
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ethabi::encode(&tokens))
+    }
+}
+
+/// Parses `constructor(address,uint256)`, `(address,uint256)` or `address,uint256` into the
+/// list of parameter types.
+fn parse_signature(signature: &str) -> Result<Vec<ParamType>, CrunchError> {
+    let signature = signature.trim();
+    let params = match signature.find('(') {
+        Some(start) => signature[start..].to_string(),
+        None => format!("({signature})"),
+    };
+    if params == "()" {
+        return Ok(Vec::new());
+    }
+
+    match Reader::read(&params) {
+        Ok(ParamType::Tuple(params)) => Ok(params),
+        _ => Err(CrunchError::InvalidConstructorArgs(format!(
+            "invalid signature: {signature}"
+        ))),
+    }
+}
+
+/// Init code of a CREATE2 deployment, together with where it came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitCode {
+    pub bytecode: Vec<u8>,
+    pub source: InitCodeSource,
+    pub has_constructor_args: bool,
+}
+
+impl InitCode {
+    pub fn from_hex(bytecode: &str) -> Result<Self, CrunchError> {
+        Ok(Self {
+            bytecode: decode_bytecode("init code", bytecode)?,
+            source: InitCodeSource::Bytecode,
+            has_constructor_args: false,
+        })
+    }
+
+    /// Reads init code stored as hex in a file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, CrunchError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Self {
+            bytecode: decode_bytecode("init code file", contents.trim())?,
+            source: InitCodeSource::File(path.to_path_buf()),
+            has_constructor_args: false,
+        })
+    }
+
+    /// Reads the creation bytecode from a Foundry (`bytecode.object`) or Hardhat (`bytecode`)
+    /// artifact.
+    pub fn from_artifact(path: impl AsRef<Path>) -> Result<Self, CrunchError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let artifact: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| CrunchError::InvalidArtifact(format!("{}: {}", path.display(), e)))?;
+
+        let bytecode = match &artifact["bytecode"] {
+            serde_json::Value::String(bytecode) => bytecode,
+            bytecode => bytecode["object"].as_str().ok_or_else(|| {
+                CrunchError::InvalidArtifact(format!("{}: no bytecode found", path.display()))
+            })?,
+        };
+        if bytecode.trim_start_matches("0x").is_empty() {
+            return Err(CrunchError::InvalidArtifact(format!(
+                "{}: bytecode is empty",
+                path.display()
+            )));
+        }
+        if bytecode.contains("__") {
+            return Err(CrunchError::InvalidArtifact(format!(
+                "{}: bytecode has unlinked libraries",
+                path.display()
+            )));
+        }
+
+        Ok(Self {
+            bytecode: decode_bytecode("artifact bytecode", bytecode)?,
+            source: InitCodeSource::Artifact(path.to_path_buf()),
+            has_constructor_args: false,
+        })
+    }
+
+    /// Appends the ABI-encoded constructor arguments to the init code.
+    pub fn with_constructor_args(mut self, args: &ConstructorArgs) -> Result<Self, CrunchError> {
+        self.bytecode.extend(args.encode()?);
+        self.has_constructor_args = true;
+        Ok(self)
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        keccak256(&self.bytecode)
+    }
+
+    /// Human-readable description of where the init code came from.
+    pub fn describe(&self) -> String {
+        if self.has_constructor_args {
+            format!("{}+constructor_args", self.source)
+        } else {
+            self.source.to_string()
+        }
+    }
+}
+
+fn decode_bytecode(field: &'static str, value: &str) -> Result<Vec<u8>, CrunchError> {
+    hex::decode(value).map_err(|_| CrunchError::InvalidHex {
+        field,
+        value: value.to_string(),
+    })
+}
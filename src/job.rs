@@ -0,0 +1,385 @@
+use crate::{
+    best::BestMetric,
+    factory::FactoryKind,
+    init_code::{ConstructorArgs, InitCode},
+    parse_nonce_range,
+    preimage::{PreimageSpec, SaltPreimage},
+    safe::SafeSetup,
+    salt_layout::SaltLayout,
+    score::{ScoringSpec, ThresholdSpec},
+    Config, ConfigBuilder, CrunchError, RewardVariant,
+};
+use alloy_primitives::hex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use web3::signing::keccak256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreateKind {
+    Create2,
+    Create3,
+    Create,
+}
+
+/// A mining job as stored in a TOML or JSON job file.
+///
+/// Every field is optional so that a job file only needs to spell out what differs from the
+/// defaults, and so that CLI flags can be layered on top of it with [`Job::merge`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factory_kind: Option<FactoryKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caller: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create: Option<CreateKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_code_hash: Option<String>,
+    /// Init code in hex, hashed to obtain the init code hash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_code: Option<String>,
+    /// File containing the init code in hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_code_file: Option<String>,
+    /// Foundry or Hardhat artifact containing the creation bytecode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<String>,
+    /// Arguments ABI-encoded and appended to the init code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constructor_args: Option<ConstructorArgs>,
+    /// Safe proxy deployed through the SafeProxyFactory, in place of an init code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safe: Option<SafeSetup>,
+    /// Preimage the factory hashes the salt in, for smart account factories.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub salt_preimage: Option<PreimageSpec>,
+    /// Layout of the free bytes of the salt, e.g. `fixed:0xc0ffee,random:4,counter:8`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub salt_layout: Option<String>,
+    /// Deployer of a CREATE deployment, defaulting to the factory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployer: Option<String>,
+    /// Nonces of the deployer to check, e.g. `0..1000000`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonces: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reward: Option<RewardVariant>,
+    /// Categories results are scored in: built-in ones to disable, weights and custom ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<ScoringSpec>,
+    /// Difficulties results are recorded and priced at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<ThresholdSpec>,
+    /// Metric of the best-so-far mode, recording every address better than the last one found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best: Option<BestMetric>,
+    /// Number of results of each category kept in the store file of the output directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_top: Option<usize>,
+    /// Results database every accepted result is also stored in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_buffer_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sleep_for: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+}
+
+impl Job {
+    /// Loads a job file. Files ending in `.json` are parsed as JSON, anything else as TOML.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CrunchError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        }
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, CrunchError> {
+        toml::from_str(s).map_err(|e| CrunchError::InvalidJob(e.to_string()))
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, CrunchError> {
+        serde_json::from_str(s).map_err(|e| CrunchError::InvalidJob(e.to_string()))
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("job is always serializable")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("job is always serializable")
+    }
+
+    /// Overrides the fields of this job with every field that is set in `other`.
+    pub fn merge(mut self, other: Job) -> Self {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field;
+                })*
+            };
+        }
+        merge!(
+            factory,
+            factory_kind,
+            caller,
+            chain_id,
+            create,
+            salt_preimage,
+            salt_layout,
+            deployer,
+            nonces,
+            reward,
+            scoring,
+            best,
+            keep_top,
+            database,
+            work_size,
+            result_buffer_size,
+            sleep_for,
+            output,
+            output_dir
+        );
+        if other.init_code_hash.is_some()
+            || other.init_code.is_some()
+            || other.init_code_file.is_some()
+            || other.artifact.is_some()
+        {
+            self.init_code_hash = other.init_code_hash;
+            self.init_code = other.init_code;
+            self.init_code_file = other.init_code_file;
+            self.artifact = other.artifact;
+        }
+        if other.constructor_args.is_some() {
+            self.constructor_args = other.constructor_args;
+        }
+        if let Some(safe) = other.safe {
+            self.safe = Some(self.safe.take().unwrap_or_default().merge(safe));
+        }
+        if let Some(thresholds) = other.thresholds {
+            self.thresholds = Some(self.thresholds.take().unwrap_or_default().merge(thresholds));
+        }
+        if !other.devices.is_empty() {
+            self.devices = other.devices;
+        }
+        self
+    }
+
+    /// Short identifier derived from the job contents, recorded with every result.
+    pub fn id(&self) -> String {
+        hex::encode(&keccak256(self.to_json().as_bytes())[..8])
+    }
+
+    /// Writes the job to `<dir>/job_<id>.toml` so that results can be traced back to it.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<PathBuf, CrunchError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("job_{}.toml", self.id()));
+        std::fs::write(&path, self.to_toml())?;
+        Ok(path)
+    }
+
+    /// Creates a [`ConfigBuilder`] from the job, applying the defaults for unset fields.
+    pub fn builder(&self) -> Result<ConfigBuilder, CrunchError> {
+        let mut builder = ConfigBuilder::new().job_id(self.id());
+
+        if let Some(factory) = &self.factory {
+            builder = builder.factory_address(factory);
+        }
+        match (self.factory_kind, &self.safe) {
+            (Some(factory_kind), _) => builder = builder.factory_kind(factory_kind),
+            (None, Some(_)) => builder = builder.factory_kind(FactoryKind::SafeProxy),
+            (None, None) => {}
+        }
+        match (&self.safe, &self.salt_preimage) {
+            (Some(_), Some(_)) => {
+                return Err(CrunchError::InvalidJob(
+                    "safe jobs hash the salt with the initializer, not a salt_preimage".to_string(),
+                ))
+            }
+            (Some(safe), None) => {
+                builder = builder.salt_preimage(SaltPreimage::SafeProxy {
+                    initializer: safe.initializer()?,
+                })
+            }
+            (None, Some(salt_preimage)) => {
+                builder = builder.salt_preimage(salt_preimage.resolve()?)
+            }
+            (None, None) => {}
+        }
+        if let Some(caller) = &self.caller {
+            builder = builder.calling_address(caller);
+        }
+        if let Some(chain_id) = self.chain_id {
+            builder = builder.chain_id(chain_id);
+        }
+        if let Some(init_code_hash) = &self.init_code_hash {
+            builder = builder.init_code_hash(init_code_hash);
+        }
+        if let Some(init_code) = self.init_code()? {
+            builder = builder.init_code(&init_code);
+        }
+        match (self.create, &self.nonces) {
+            (Some(CreateKind::Create), None) => {
+                return Err(CrunchError::InvalidJob(
+                    "create jobs require nonces".to_string(),
+                ))
+            }
+            (Some(CreateKind::Create2 | CreateKind::Create3), Some(_)) => {
+                return Err(CrunchError::InvalidJob(
+                    "nonces are only used by create jobs".to_string(),
+                ))
+            }
+            (_, Some(nonces)) => builder = builder.nonce_range(parse_nonce_range(nonces)?),
+            (_, None) => {}
+        }
+        if let Some(deployer) = &self.deployer {
+            builder = builder.deployer(deployer);
+        }
+        if let Some(salt_layout) = &self.salt_layout {
+            builder = builder.salt_layout(salt_layout.parse::<SaltLayout>()?);
+        }
+        if let Some(reward) = &self.reward {
+            builder = builder.reward(reward.clone());
+        }
+        if let Some(scoring) = &self.scoring {
+            builder = builder.scoring(scoring.registry()?);
+        }
+        if let Some(thresholds) = &self.thresholds {
+            builder = builder.thresholds(thresholds.thresholds());
+        }
+        if let Some(best) = self.best {
+            builder = builder.best(best);
+        }
+        if let Some(keep_top) = self.keep_top {
+            builder = builder.keep_top(keep_top);
+        }
+        if let Some(database) = &self.database {
+            builder = builder.database(database);
+        }
+        if let Some(work_size) = self.work_size {
+            builder = builder.work_size(work_size);
+        }
+        if let Some(result_buffer_size) = self.result_buffer_size {
+            builder = builder.result_buffer_size(result_buffer_size);
+        }
+        if let Some(sleep_for) = self.sleep_for {
+            builder = builder.sleep_for(sleep_for);
+        }
+        if let Some(output) = &self.output {
+            builder = builder.output(output);
+        }
+        if let Some(output_dir) = &self.output_dir {
+            builder = builder.output_dir(output_dir);
+        }
+
+        Ok(builder)
+    }
+
+    /// Builds one validated [`Config`] per device of the job.
+    pub fn configs(&self) -> Result<Vec<Config>, CrunchError> {
+        let builder = self.builder()?;
+        if self.devices.is_empty() {
+            return Ok(vec![builder.build()?]);
+        }
+        let count = self.devices.len() as u64;
+        self.devices
+            .iter()
+            .zip(0..)
+            .map(|(device, index)| {
+                builder
+                    .clone()
+                    .gpu_device(*device)
+                    .device_share(index, count)
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Resolves the init code of the job, if it is given as bytecode rather than as a hash.
+    pub fn init_code(&self) -> Result<Option<InitCode>, CrunchError> {
+        let sources = [
+            self.init_code_hash.is_some(),
+            self.init_code.is_some(),
+            self.init_code_file.is_some(),
+            self.artifact.is_some(),
+        ];
+        let sources = sources.into_iter().filter(|source| *source).count();
+        if sources > 1 {
+            return Err(CrunchError::InvalidJob(
+                "only one of init_code_hash, init_code, init_code_file and artifact may be set"
+                    .to_string(),
+            ));
+        }
+
+        if let Some(safe) = &self.safe {
+            return match (self.create, sources) {
+                (Some(CreateKind::Create2) | None, 0) if self.constructor_args.is_none() => {
+                    safe.init_code().map(Some)
+                }
+                (Some(CreateKind::Create2) | None, _) => Err(CrunchError::InvalidJob(
+                    "safe jobs take the proxy creation code instead of an init code".to_string(),
+                )),
+                _ => Err(CrunchError::InvalidJob(
+                    "safe proxies are deployed with create2".to_string(),
+                )),
+            };
+        }
+
+        match (self.create, sources) {
+            (Some(CreateKind::Create3), 1) => {
+                return Err(CrunchError::InvalidJob(
+                    "create3 jobs do not take an init code".to_string(),
+                ))
+            }
+            (Some(CreateKind::Create), 1) => {
+                return Err(CrunchError::InvalidJob(
+                    "create jobs do not take an init code".to_string(),
+                ))
+            }
+            (Some(CreateKind::Create2), 0) => {
+                return Err(CrunchError::InvalidJob(
+                    "create2 jobs require an init code hash or init code".to_string(),
+                ))
+            }
+            _ => {}
+        }
+
+        let init_code = if let Some(init_code) = &self.init_code {
+            InitCode::from_hex(init_code)?
+        } else if let Some(path) = &self.init_code_file {
+            InitCode::from_file(path)?
+        } else if let Some(path) = &self.artifact {
+            InitCode::from_artifact(path)?
+        } else {
+            if self.constructor_args.is_some() {
+                return Err(CrunchError::InvalidJob(
+                    "constructor_args require init_code, init_code_file or artifact".to_string(),
+                ));
+            }
+            return Ok(None);
+        };
+
+        match &self.constructor_args {
+            Some(args) => init_code.with_constructor_args(args).map(Some),
+            None => Ok(Some(init_code)),
+        }
+    }
+}
@@ -215,38 +215,44 @@ static inline bool isMatching(uchar const *d)
 }
 #endif
 
-#define hasTotal(d) ( \
+#define zeroBytes(d) ( \
   (!(d[0])) + (!(d[1])) + (!(d[2])) + (!(d[3])) + \
   (!(d[4])) + (!(d[5])) + (!(d[6])) + (!(d[7])) + \
   (!(d[8])) + (!(d[9])) + (!(d[10])) + (!(d[11])) + \
   (!(d[12])) + (!(d[13])) + (!(d[14])) + (!(d[15])) + \
   (!(d[16])) + (!(d[17])) + (!(d[18])) + (!(d[19])) \
->= TOTAL_ZEROES)
-
-#if LEADING_ZEROES == 8
-#define hasLeading(d) (!(((uint*)d)[0]) && !(((uint*)d)[1]))
-#elif LEADING_ZEROES == 7
-#define hasLeading(d) (!(((uint*)d)[0]) && !(((uint*)d)[1] & 0x00ffffffu))
-#elif LEADING_ZEROES == 6
-#define hasLeading(d) (!(((uint*)d)[0]) && !(((uint*)d)[1] & 0x0000ffffu))
-#elif LEADING_ZEROES == 5
-#define hasLeading(d) (!(((uint*)d)[0]) && !(((uint*)d)[1] & 0x000000ffu))
-#elif LEADING_ZEROES == 4
-#define hasLeading(d) (!(((uint*)d)[0]))
-#elif LEADING_ZEROES == 3
-#define hasLeading(d) (!(((uint*)d)[0] & 0x00ffffffu))
-#elif LEADING_ZEROES == 2
-#define hasLeading(d) (!(((uint*)d)[0] & 0x0000ffffu))
-#elif LEADING_ZEROES == 1
-#define hasLeading(d) (!(((uint*)d)[0] & 0x000000ffu))
-#else
-static inline bool hasLeading(uchar const *d)
+)
+
+#define hasTotal(d) (zeroBytes(d) >= TOTAL_ZEROES)
+
+#ifdef MIN_GAS
+// Calldata gas saved by the address per reference: 12 per zero byte, and 4
+// more per leading zero byte dropped by compact encodings, see `GasScore`
+static inline uint gasSaved(uchar const *d)
 {
-#pragma unroll
-  for (uint i = 0; i < LEADING_ZEROES; ++i) {
-    if (d[i] != 0) return false;
+  uint leading = 0;
+  while (leading < 20 && !d[leading]) ++leading;
+  return 12 * zeroBytes(d) + 4 * leading;
+}
+#endif
+
+// hasLeading and hasNibbles are generated from the criteria, see `nibble_condition`
+
+#ifdef BEST_METRIC
+static inline uint leadingZeroNibbles(uchar const *d)
+{
+  for (uint i = 0; i < 20; ++i) {
+    if (d[i]) return 2 * i + !(d[i] >> 4);
   }
-  return true;
+  return 40;
+}
+
+// Raises the running best to the metric of the digest, reporting the digest
+// only if it did: the work items of a batch report improving addresses
+static inline bool improvesBest(uchar const *d, __global volatile uint *best)
+{
+  uint metric = BEST_METRIC(d);
+  return metric > *best && atomic_max(best, metric) < metric;
 }
 #endif
 
@@ -285,6 +291,7 @@ static inline bool hasLeadingAny(uchar const *d)
 }
 */
 
+#ifdef GROUP
 static inline bool hasLeadingAny(uchar const *d)
 {
     uint starting_similar_count = 0;
@@ -403,6 +410,160 @@ static inline bool hasLeadingAny(uchar const *d)
 
     return false;
 }
+#endif
+
+#ifdef FANCY_CHECKS
+// Characters of the address are nibbles, with 16 added to letters that are
+// uppercase in the checksummed address
+static inline void toNibbles(uchar const *d, uchar *n)
+{
+#pragma unroll
+  for (uint i = 0; i < 20; ++i) {
+    n[2 * i] = d[i] >> 4;
+    n[2 * i + 1] = d[i] & 0xf;
+  }
+}
+
+// The first 8 and last 9 characters, as etherscan shortens addresses
+static inline void toShort(uchar const *n, uchar *s)
+{
+  for (uint i = 0; i < 8; ++i)
+    s[i] = n[i];
+  for (uint i = 0; i < 9; ++i)
+    s[8 + i] = n[31 + i];
+}
+
+static inline uint leadingRun(uchar const *t, uint len, uchar c)
+{
+  uint run = 0;
+  while (run < len && t[run] == c)
+    ++run;
+  return run;
+}
+
+// Applies the EIP-55 checksum: letters whose nibble in the hash of the
+// lowercase address is above 7 are uppercase
+static inline void checksum(uchar const *n, uchar *c)
+{
+  ulong a[25];
+  for (int i = 0; i < 25; ++i)
+    a[i] = 0;
+  uchar *b = (uchar *) a;
+  for (int i = 0; i < 40; ++i)
+    b[i] = n[i] < 10 ? '0' + n[i] : 'a' + n[i] - 10;
+  b[40] = 0x01u;
+  b[135] = 0x80u;
+  keccakf(a);
+  for (int i = 0; i < 40; ++i) {
+    uchar h = i % 2 == 0 ? b[i / 2] >> 4 : b[i / 2] & 0xf;
+    c[i] = n[i] >= 10 && h > 7 ? n[i] + 16 : n[i];
+  }
+}
+
+// Number of letters, or 0 if the address holds two different digits
+static inline uint letters(uchar const *n)
+{
+  uint count = 0;
+  uchar digit = 0xff;
+  for (int i = 0; i < 40; ++i) {
+    if (n[i] >= 10) {
+      ++count;
+    } else if (digit == 0xff) {
+      digit = n[i];
+    } else if (n[i] != digit) {
+      return 0;
+    }
+  }
+  return count;
+}
+
+static inline uint digits(uchar const *n)
+{
+  uint count = 0;
+  for (int i = 0; i < 40; ++i)
+    count += n[i] < 10;
+  return count;
+}
+
+static inline uint repeats(uchar const *n)
+{
+  uint count = 1;
+  for (int i = 1; i < 40; ++i)
+    count += n[i] == n[i - 1];
+  return count;
+}
+
+// Whether the digits of an all-digit address are at most low or at least high
+static inline bool decimalAccept(uchar const *n, __constant uchar const *low, __constant uchar const *high)
+{
+  int below = 0;
+  int above = 0;
+  for (int i = 0; i < 40; ++i) {
+    if (below == 0 && n[i] != low[i])
+      below = n[i] < low[i] ? 1 : -1;
+    if (above == 0 && n[i] != high[i])
+      above = n[i] > high[i] ? 1 : -1;
+  }
+  return below >= 0 || above >= 0;
+}
+
+// Whether the address starts or ends with one of the words, whose nibbles are
+// stored back to back in w and end at the offsets in ends. The words are only
+// compared when the first or last byte of the address is flagged in head or
+// tail
+static inline bool anchoredWord(uchar const *n, __constant uchar const *head, __constant uchar const *tail, __constant uchar const *w, __constant uint const *ends, uint count)
+{
+  bool atHead = head[n[0] * 16 + n[1]];
+  bool atTail = tail[n[38] * 16 + n[39]];
+  if (!atHead && !atTail)
+    return false;
+  uint start = 0;
+  for (uint i = 0; i < count; ++i) {
+    uint len = ends[i] - start;
+    bool h = atHead;
+    bool t = atTail;
+    for (uint j = 0; j < len && (h || t); ++j) {
+      h = h && n[j] == w[start + j];
+      t = t && n[40 - len + j] == w[start + j];
+    }
+    if (h || t)
+      return true;
+    start = ends[i];
+  }
+  return false;
+}
+
+// Whether the address contains one of the words, stored as for anchoredWord
+static inline bool wordAnywhere(uchar const *n, __constant uchar const *w, __constant uint const *ends, uint count)
+{
+  uint start = 0;
+  for (uint i = 0; i < count; ++i) {
+    uint len = ends[i] - start;
+    for (uint p = 0; p + len <= 40; ++p) {
+      uint j = 0;
+      while (j < len && n[p + j] == w[start + j])
+        ++j;
+      if (j == len)
+        return true;
+    }
+    start = ends[i];
+  }
+  return false;
+}
+
+static inline bool isFancy(uchar const *d)
+{
+  uchar n[40];
+  uchar s[17];
+  uchar c[40];
+  uchar cs[17];
+  bool hashed = false;
+  toNibbles(d, n);
+  toShort(n, s);
+  FANCY_CHECKS()
+  return false;
+}
+#endif
 
 // Debugging helper
 #define PRINT() { \
@@ -422,271 +583,89 @@ static inline bool hasLeadingAny(uchar const *d)
   printf("\n"); \
 }
 
-#define SENDER() { \
-  for (int i = 0; i < 12; ++i) \
-    sponge[i] = 0; \
-  sponge[12] = S1_12; \
-  sponge[13] = S1_13; \
-  sponge[14] = S1_14; \
-  sponge[15] = S1_15; \
-  sponge[16] = S1_16; \
-  sponge[17] = S1_17; \
-  sponge[18] = S1_18; \
-  sponge[19] = S1_19; \
-  sponge[20] = S1_20; \
-  sponge[21] = S1_21; \
-  sponge[22] = S1_22; \
-  sponge[23] = S1_23; \
-  sponge[24] = S1_24; \
-  sponge[25] = S1_25; \
-  sponge[26] = S1_26; \
-  sponge[27] = S1_27; \
-  sponge[28] = S1_28; \
-  sponge[29] = S1_29; \
-  sponge[30] = S1_30; \
-  sponge[31] = S1_31; \
-  sponge[32] = S1_12; \
-  sponge[33] = S1_13; \
-  sponge[34] = S1_14; \
-  sponge[35] = S1_15; \
-  sponge[36] = S1_16; \
-  sponge[37] = S1_17; \
-  sponge[38] = S1_18; \
-  sponge[39] = S1_19; \
-  sponge[40] = S1_20; \
-  sponge[41] = S1_21; \
-  sponge[42] = S1_22; \
-  sponge[43] = S1_23; \
-  sponge[44] = S1_24; \
-  sponge[45] = S1_25; \
-  sponge[46] = S1_26; \
-  sponge[47] = S1_27; \
-  sponge[48] = S1_28; \
-  sponge[49] = S1_29; \
-  sponge[50] = S1_30; \
-  sponge[51] = S1_31;  \
-  sponge[52] = 0u; \
-  sponge[53] = d_message[0]; \
-  sponge[54] = d_message[1]; \
-  sponge[55] = d_message[2]; \
-  sponge[56] = d_message[3]; \
-  nonce.uint32_t[0] = get_global_id(0); \
-  nonce.uint32_t[1] = d_nonce[0]; \
-  sponge[57] = nonce.uint8_t[0]; \
-  sponge[58] = nonce.uint8_t[1]; \
-  sponge[59] = nonce.uint8_t[2]; \
-  sponge[60] = nonce.uint8_t[3]; \
-  sponge[61] = nonce.uint8_t[4]; \
-  sponge[62] = nonce.uint8_t[5]; \
-  sponge[63] = nonce.uint8_t[6]; \
-  sponge[64] = 0x01u; \
-  for (int i = 65; i < 135; ++i) \
-    sponge[i] = 0; \
-  sponge[135] = 0x80u; \
-  for (int i = 136; i < 200; ++i) \
-    sponge[i] = 0; \
-  keccakf(spongeBuffer); \
-}
-
-#define SENDER_XCHAIN() { \
-  for (int i = 0; i < 12; ++i) \
-    sponge[i] = 0; \
-  sponge[12] = S1_12; \
-  sponge[13] = S1_13; \
-  sponge[14] = S1_14; \
-  sponge[15] = S1_15; \
-  sponge[16] = S1_16; \
-  sponge[17] = S1_17; \
-  sponge[18] = S1_18; \
-  sponge[19] = S1_19; \
-  sponge[20] = S1_20; \
-  sponge[21] = S1_21; \
-  sponge[22] = S1_22; \
-  sponge[23] = S1_23; \
-  sponge[24] = S1_24; \
-  sponge[25] = S1_25; \
-  sponge[26] = S1_26; \
-  sponge[27] = S1_27; \
-  sponge[28] = S1_28; \
-  sponge[29] = S1_29; \
-  sponge[30] = S1_30; \
-  sponge[31] = S1_31; \
-  sponge[32] = S1_32; \
-  sponge[33] = S1_33; \
-  sponge[34] = S1_34; \
-  sponge[35] = S1_35; \
-  sponge[36] = S1_36; \
-  sponge[37] = S1_37; \
-  sponge[38] = S1_38; \
-  sponge[39] = S1_39; \
-  sponge[40] = S1_40; \
-  sponge[41] = S1_41; \
-  sponge[42] = S1_42; \
-  sponge[43] = S1_43; \
-  sponge[44] = S1_44; \
-  sponge[45] = S1_45; \
-  sponge[46] = S1_46; \
-  sponge[47] = S1_47; \
-  sponge[48] = S1_48; \
-  sponge[49] = S1_49; \
-  sponge[50] = S1_50; \
-  sponge[51] = S1_51; \
-  sponge[52] = S1_52; \
-  sponge[53] = S1_53; \
-  sponge[54] = S1_54; \
-  sponge[55] = S1_55; \
-  sponge[56] = S1_56; \
-  sponge[57] = S1_57; \
-  sponge[58] = S1_58; \
-  sponge[59] = S1_59; \
-  sponge[60] = S1_60; \
-  sponge[61] = S1_61; \
-  sponge[62] = S1_62; \
-  sponge[63] = S1_63; \
-  sponge[64] = S1_12; \
-  sponge[65] = S1_13; \
-  sponge[66] = S1_14; \
-  sponge[67] = S1_15; \
-  sponge[68] = S1_16; \
-  sponge[69] = S1_17; \
-  sponge[70] = S1_18; \
-  sponge[71] = S1_19; \
-  sponge[72] = S1_20; \
-  sponge[73] = S1_21; \
-  sponge[74] = S1_22; \
-  sponge[75] = S1_23; \
-  sponge[76] = S1_24; \
-  sponge[77] = S1_25; \
-  sponge[78] = S1_26; \
-  sponge[79] = S1_27; \
-  sponge[80] = S1_28; \
-  sponge[81] = S1_29; \
-  sponge[82] = S1_30; \
-  sponge[83] = S1_31;  \
-  sponge[84] = 1u; \
-  sponge[85] = d_message[0]; \
-  sponge[86] = d_message[1]; \
-  sponge[87] = d_message[2]; \
-  sponge[88] = d_message[3]; \
-  nonce.uint32_t[0] = get_global_id(0); \
-  nonce.uint32_t[1] = d_nonce[0]; \
-  sponge[89] = nonce.uint8_t[0]; \
-  sponge[90] = nonce.uint8_t[1]; \
-  sponge[91] = nonce.uint8_t[2]; \
-  sponge[92] = nonce.uint8_t[3]; \
-  sponge[93] = nonce.uint8_t[4]; \
-  sponge[94] = nonce.uint8_t[5]; \
-  sponge[95] = nonce.uint8_t[6]; \
-  sponge[96] = 0x01u; \
-  for (int i = 97; i < 135; ++i) \
-    sponge[i] = 0; \
-  sponge[135] = 0x80u; \
-  for (int i = 136; i < 200; ++i) \
-    sponge[i] = 0; \
-  keccakf(spongeBuffer); \
-}
-
-#define XCHAIN() { \
-  sponge[0] = S1_32; \
-  sponge[1] = S1_33; \
-  sponge[2] = S1_34; \
-  sponge[3] = S1_35; \
-  sponge[4] = S1_36; \
-  sponge[5] = S1_37; \
-  sponge[6] = S1_38; \
-  sponge[7] = S1_39; \
-  sponge[8] = S1_40; \
-  sponge[9] = S1_41; \
-  sponge[10] = S1_42; \
-  sponge[11] = S1_43; \
-  sponge[12] = S1_44; \
-  sponge[13] = S1_45; \
-  sponge[14] = S1_46; \
-  sponge[15] = S1_47; \
-  sponge[16] = S1_48; \
-  sponge[17] = S1_49; \
-  sponge[18] = S1_50; \
-  sponge[19] = S1_51; \
-  sponge[20] = S1_52; \
-  sponge[21] = S1_53; \
-  sponge[22] = S1_54; \
-  sponge[23] = S1_55; \
-  sponge[24] = S1_56; \
-  sponge[25] = S1_57; \
-  sponge[26] = S1_58; \
-  sponge[27] = S1_59; \
-  sponge[28] = S1_60; \
-  sponge[29] = S1_61; \
-  sponge[30] = S1_62; \
-  sponge[31] = S1_63; \
-  for (int i = 32; i < 52; i++) \
-    sponge[i] = 0; \
-  sponge[52] = 1; \
-  sponge[53] = d_message[0]; \
-  sponge[54] = d_message[1]; \
-  sponge[55] = d_message[2]; \
-  sponge[56] = d_message[3]; \
-  nonce.uint32_t[0] = get_global_id(0); \
-  nonce.uint32_t[1] = d_nonce[0]; \
-  sponge[57] = nonce.uint8_t[0]; \
-  sponge[58] = nonce.uint8_t[1]; \
-  sponge[59] = nonce.uint8_t[2]; \
-  sponge[60] = nonce.uint8_t[3]; \
-  sponge[61] = nonce.uint8_t[4]; \
-  sponge[62] = nonce.uint8_t[5]; \
-  sponge[63] = nonce.uint8_t[6]; \
-  sponge[64] = 0x01u; \
-  for (int i = 65; i < 135; ++i) \
-    sponge[i] = 0; \
-  sponge[135] = 0x80u; \
-  for (int i = 136; i < 200; ++i) \
+// Replaces the salt with the hash the factory derives from it, as described by GUARD_TEMPLATE
+#define RUN_GUARD_SALT() { \
+  for (int i = 0; i < GUARD_LEN; ++i) \
+    sponge[i] = GUARD_TEMPLATE[i]; \
+  for (int i = 0; i < 32; ++i) \
+    sponge[GUARD_SALT_OFFSET + i] = salt[i]; \
+  sponge[GUARD_LEN] = 0x01u; \
+  for (int i = GUARD_LEN + 1; i < 200; ++i) \
     sponge[i] = 0; \
+  sponge[135] |= 0x80u; \
   keccakf(spongeBuffer); \
+  for (int i = 0; i < 32; ++i) \
+    salt[i] = sponge[i]; \
 }
 
-#define RANDOM() { \
-  sponge[0] = d_message[0]; \
-  sponge[1] = d_message[1]; \
-  sponge[2] = d_message[2]; \
-  sponge[3] = d_message[3]; \
-  nonce.uint32_t[0] = get_global_id(0); \
-  nonce.uint32_t[1] = d_nonce[0]; \
-  sponge[4] = nonce.uint8_t[0]; \
-  sponge[5] = nonce.uint8_t[1]; \
-  sponge[6] = nonce.uint8_t[2]; \
-  sponge[7] = nonce.uint8_t[3]; \
-  sponge[8] = nonce.uint8_t[4]; \
-  sponge[9] = nonce.uint8_t[5]; \
-  sponge[10] = nonce.uint8_t[6]; \
-  for (int i = 11; i < 32; ++i) \
-    sponge[i] = 0; \
-  sponge[32] = 0x01u; \
-  for (int i = 33; i < 135; ++i) \
-    sponge[i] = 0; \
-  sponge[135] = 0x80u; \
-  for (int i = 136; i < 200; ++i) \
+// Same as RUN_GUARD_SALT for preimages spanning GUARD_BLOCKS keccak blocks
+#define RUN_GUARD_SALT_BLOCKS() { \
+  for (int i = 0; i < 200; ++i) \
     sponge[i] = 0; \
+  for (int block = 0; block < GUARD_BLOCKS; ++block) { \
+    for (int i = 0; i < 136; ++i) { \
+      int j = block * 136 + i; \
+      if (j >= GUARD_SALT_OFFSET && j < GUARD_SALT_OFFSET + 32) \
+        sponge[i] ^= salt[j - GUARD_SALT_OFFSET]; \
+      else if (j < GUARD_LEN) \
+        sponge[i] ^= GUARD_TEMPLATE[j]; \
+      else if (j == GUARD_LEN) \
+        sponge[i] ^= 0x01u; \
+    } \
+    if (block == GUARD_BLOCKS - 1) \
+      sponge[135] ^= 0x80u; \
+    keccakf(spongeBuffer); \
+  } \
+  for (int i = 0; i < 32; ++i) \
+    salt[i] = sponge[i]; \
 }
 
+// Hashes rlp([proxy, 1]) from CREATE3_MIDSTATE, writing only the proxy address
 #define RUN_CREATE3() { \
   keccakf(spongeBuffer); \
+  ulong proxy[4] = {spongeBuffer[0], spongeBuffer[1], spongeBuffer[2], spongeBuffer[3]}; \
+  for (int i = 0; i < 25; ++i) \
+    spongeBuffer[i] = CREATE3_MIDSTATE[i]; \
   for (int i = 12; i < 32; ++i) \
-    sponge[i - 10] = sponge[i]; \
-  sponge[0] = 0xd6u; \
+    sponge[i - 10] = ((uchar *) proxy)[i]; \
+}
+
+// Builds the CREATE preimage rlp([CREATE_DEPLOYER, nonce]) for the nonce of this work item,
+// scanning CREATE_NONCE_START..CREATE_NONCE_END in batches of CREATE_BATCH_SIZE
+#define RUN_CREATE() { \
+  nonce.uint64_t = CREATE_NONCE_START + (ulong)d_nonce[0] * CREATE_BATCH_SIZE + get_global_id(0); \
+  if (nonce.uint64_t < CREATE_NONCE_START || nonce.uint64_t >= CREATE_NONCE_END) \
+    return; \
   sponge[1] = 0x94u; \
-  sponge[22] = 0x01u; \
-  sponge[23] = 0x01u; \
-  for (int i = 24; i < 135; ++i) \
-    sponge[i] = 0; \
-  sponge[135] = 0x80u; \
-  for (int i = 136; i < 200; ++i) \
+  for (int i = 0; i < 20; ++i) \
+    sponge[2 + i] = CREATE_DEPLOYER[i]; \
+  int len = 22; \
+  if (nonce.uint64_t == 0) { \
+    sponge[len++] = 0x80u; \
+  } else if (nonce.uint64_t < 0x80) { \
+    sponge[len++] = nonce.uint8_t[0]; \
+  } else { \
+    int bytes = 8; \
+    while (!nonce.uint8_t[bytes - 1]) \
+      --bytes; \
+    sponge[len++] = 0x80u + bytes; \
+    for (int i = bytes - 1; i >= 0; --i) \
+      sponge[len++] = nonce.uint8_t[i]; \
+  } \
+  sponge[0] = 0xc0u + len - 1; \
+  sponge[len] = 0x01u; \
+  for (int i = len + 1; i < 200; ++i) \
     sponge[i] = 0; \
+  sponge[135] |= 0x80u; \
 }
 
 __kernel void hashMessage(
   __constant uchar const *d_message,
-  __constant uint const *d_nonce,
+  __constant ulong const *d_nonce,
   __global volatile ulong *restrict solutions
+#ifdef BEST_METRIC
+  , __global volatile uint *best
+#endif
 ) {
   ulong spongeBuffer[25];
 
@@ -695,98 +674,36 @@ __kernel void hashMessage(
 
   nonce_t nonce;
 
-  // Salt hash
-  sponge[21] = d_message[0];
-  sponge[21 + 1] = d_message[1];
-  sponge[21 + 2] = d_message[2];
-  sponge[21 + 3] = d_message[3];
-  nonce.uint32_t[0] = get_global_id(0);
-  nonce.uint32_t[1] = d_nonce[0];
-  sponge[21 + 4] = nonce.uint8_t[0];
-  sponge[21 + 5] = nonce.uint8_t[1];
-  sponge[21 + 6] = nonce.uint8_t[2];
-  sponge[21 + 7] = nonce.uint8_t[3];
-  sponge[21 + 8] = nonce.uint8_t[4];
-  sponge[21 + 9] = nonce.uint8_t[5];
-  sponge[21 + 10] = nonce.uint8_t[6];
-
-  for (int i = 11; i < 32; ++i)
-    sponge[21 + i] = 0;
-
-  // Setup Create2 Hash
-  // write the control character
-  sponge[0] = 0xffu;
-
-  sponge[1] = S2_1;
-  sponge[2] = S2_2;
-  sponge[3] = S2_3;
-  sponge[4] = S2_4;
-  sponge[5] = S2_5;
-  sponge[6] = S2_6;
-  sponge[7] = S2_7;
-  sponge[8] = S2_8;
-  sponge[9] = S2_9;
-  sponge[10] = S2_10;
-  sponge[11] = S2_11;
-  sponge[12] = S2_12;
-  sponge[13] = S2_13;
-  sponge[14] = S2_14;
-  sponge[15] = S2_15;
-  sponge[16] = S2_16;
-  sponge[17] = S2_17;
-  sponge[18] = S2_18;
-  sponge[19] = S2_19;
-  sponge[20] = S2_20;
-  sponge[53] = S2_53;
-  sponge[54] = S2_54;
-  sponge[55] = S2_55;
-  sponge[56] = S2_56;
-  sponge[57] = S2_57;
-  sponge[58] = S2_58;
-  sponge[59] = S2_59;
-  sponge[60] = S2_60;
-  sponge[61] = S2_61;
-  sponge[62] = S2_62;
-  sponge[63] = S2_63;
-  sponge[64] = S2_64;
-  sponge[65] = S2_65;
-  sponge[66] = S2_66;
-  sponge[67] = S2_67;
-  sponge[68] = S2_68;
-  sponge[69] = S2_69;
-  sponge[70] = S2_70;
-  sponge[71] = S2_71;
-  sponge[72] = S2_72;
-  sponge[73] = S2_73;
-  sponge[74] = S2_74;
-  sponge[75] = S2_75;
-  sponge[76] = S2_76;
-  sponge[77] = S2_77;
-  sponge[78] = S2_78;
-  sponge[79] = S2_79;
-  sponge[80] = S2_80;
-  sponge[81] = S2_81;
-  sponge[82] = S2_82;
-  sponge[83] = S2_83;
-  sponge[84] = S2_84;
-
-  sponge[85] = 0x01u;
-
-  // fill padding
+#ifdef CREATE_NONCE_START
+  RUN_CREATE()
+#else
+  // Salt passed to the factory: the fixed bytes of SALT_TEMPLATE with the
+  // random bytes of the message and the counter of this work item written
+  // where the salt layout puts them
+  uchar salt[32];
+  for (int i = 0; i < 32; ++i)
+    salt[i] = SALT_TEMPLATE[i];
+  uint gid = get_global_id(0);
+  ulong launch = d_nonce[0];
+  nonce.uint32_t[0] = gid;
+  nonce.uint32_t[1] = (uint)launch;
+  SALT_MINED()
+
+  // If the factory guards the salt, hash it the way the factory does
+  GUARD_SALT()
+
+  // Start from the Create2 preimage with every byte that is constant for
+  // this launch in place, and write the salt bytes that vary
 #pragma unroll
-  for (int i = 86; i < 135; ++i)
-    sponge[i] = 0;
-
-  // end padding
-  sponge[135] = 0x80u;
-
-  // fill remaining sponge state with zeros
+  for (int i = 0; i < 25; ++i)
+    spongeBuffer[i] = MIDSTATE[i];
 #pragma unroll
-  for (int i = 136; i < 200; ++i)
-    sponge[i] = 0;
+  for (int i = SALT_VARYING_START; i < SALT_VARYING_END; ++i)
+    sponge[21 + i] = salt[i];
 
   // If this is a Create3 operation, setup and perform an additional CREATE hash
   CREATE3()
+#endif
 
   partial_keccakf(spongeBuffer);
 
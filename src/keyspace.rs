@@ -0,0 +1,160 @@
+use crate::{salt_layout::WORK_ITEM_BYTES, Config};
+use alloy_primitives::hex;
+use rand::Rng;
+use std::fmt::Display;
+
+/// Work items a launch can enumerate before the work item bytes of the counter wrap.
+pub const MAX_WORK_SIZE: u64 = 1 << (8 * WORK_ITEM_BYTES);
+
+/// A kernel launch: the random bytes of the salt layout and the index of the launch, enumerated
+/// in the high bytes of the counter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Launch {
+    pub message: Vec<u8>,
+    pub index: u64,
+}
+
+impl Launch {
+    /// The random bytes as passed to the kernel, whose message buffer cannot be empty.
+    pub fn kernel_message(&self) -> &[u8] {
+        match self.message.is_empty() {
+            true => &[0],
+            false => &self.message,
+        }
+    }
+}
+
+/// The launches, or batches of CREATE nonces, a device takes when several devices mine a job:
+/// those whose index is `index` modulo `count`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceShare {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Default for DeviceShare {
+    fn default() -> Self {
+        Self { index: 0, count: 1 }
+    }
+}
+
+impl DeviceShare {
+    /// The batches out of `batches` the device takes, like the launches of a finite keyspace.
+    pub fn batches(&self, batches: u64) -> impl Iterator<Item = u64> {
+        (self.index..batches).step_by(self.count as usize)
+    }
+}
+
+/// Hands out the launches of a device so that no salt is hashed twice.
+///
+/// A message takes as many launches as the high bytes of the counter enumerate, then the
+/// dispatcher rolls over to new random bytes. A layout without random bytes has a single
+/// message, whose launches are split between the devices of the job by their [`DeviceShare`],
+/// and the keyspace is exhausted once the launches of the device are covered. Devices draw their
+/// own random bytes otherwise, and take every launch of their messages.
+#[derive(Clone, Debug)]
+pub struct Keyspace {
+    random_len: usize,
+    work_size: u64,
+    launches_per_message: u128,
+    first_launch: u128,
+    launch_step: u128,
+    message: Vec<u8>,
+    next_launch: u128,
+    launches: u128,
+    exhausted_messages: u64,
+}
+
+impl Keyspace {
+    pub fn new(config: &Config) -> Self {
+        let layout = &config.salt_layout;
+        let share = match layout.random_len() {
+            0 => config.device_share,
+            _ => DeviceShare::default(),
+        };
+        Self {
+            random_len: layout.random_len(),
+            work_size: config.work_size as u64,
+            launches_per_message: 1 << (8 * (layout.counter_len() - WORK_ITEM_BYTES)),
+            first_launch: share.index as u128,
+            launch_step: share.count as u128,
+            message: Vec::new(),
+            next_launch: share.index as u128,
+            launches: 0,
+            exhausted_messages: 0,
+        }
+    }
+
+    /// Launches each message takes before rolling over.
+    pub fn launches_per_message(&self) -> u128 {
+        self.launches_per_message
+    }
+
+    /// Salts of the keyspace the device hashes, or `None` if random bytes make it unbounded.
+    pub fn size(&self) -> Option<u128> {
+        let launches = self
+            .launches_per_message
+            .saturating_sub(self.first_launch)
+            .div_ceil(self.launch_step);
+        (self.random_len == 0).then(|| launches * self.work_size as u128)
+    }
+
+    /// The next launch, drawing new random bytes whenever the launches of the message are used
+    /// up, or `None` once the keyspace is exhausted.
+    pub fn next(&mut self, rng: &mut impl Rng) -> Option<Launch> {
+        if self.next_launch >= self.launches_per_message {
+            if self.random_len == 0 {
+                return None;
+            }
+            log::debug!(
+                "Covered message {}, rolling over",
+                hex::encode(&self.message)
+            );
+            self.exhausted_messages += 1;
+            self.next_launch = self.first_launch;
+        }
+        if self.next_launch == self.first_launch {
+            self.message = (0..self.random_len).map(|_| rng.gen()).collect();
+        }
+        let index = self.next_launch as u64;
+        self.next_launch += self.launch_step;
+        self.launches += 1;
+        Some(Launch {
+            message: self.message.clone(),
+            index,
+        })
+    }
+
+    /// Salts handed out so far.
+    pub fn covered(&self) -> u128 {
+        self.launches.saturating_mul(self.work_size as u128)
+    }
+}
+
+impl Display for Keyspace {
+    /// The launches covered by the device, e.g.
+    /// `message 0a1b2c3d launches 0..=12 of 16777216, 2 messages covered`, with the step between
+    /// them if the device shares the keyspace, e.g. `launches 1..=13 step 2 of 256`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.random_len > 0 {
+            write!(f, "message {} ", hex::encode(&self.message))?;
+        }
+        match self.next_launch > self.first_launch {
+            true => write!(
+                f,
+                "launches {}..={}",
+                self.first_launch,
+                self.next_launch - self.launch_step
+            )?,
+            false => write!(f, "no launches")?,
+        }
+        if self.launch_step > 1 {
+            write!(f, " step {}", self.launch_step)?;
+        }
+        write!(f, " of {}", self.launches_per_message)?;
+        if self.exhausted_messages > 0 {
+            write!(f, ", {} messages covered", self.exhausted_messages)?;
+        }
+        Ok(())
+    }
+}
@@ -1,31 +1,71 @@
-use crate::score::{get_min_difficulty, score_fancy};
-use alloy_primitives::{hex, Address, FixedBytes};
-use itertools::chain;
+use crate::best::{BestMetric, SharedBest};
+use crate::events::Event;
+use crate::factory::{create2_address, create3_address, create_address, FactoryKind};
+use crate::fancy::FancyPredicate;
+use crate::gas::GasScore;
+use crate::init_code::InitCode;
+use crate::keyspace::{DeviceShare, Keyspace, Launch};
+use crate::preimage::SaltPreimage;
+use crate::record::ResultRecord;
+use crate::salt_layout::SaltLayout;
+use crate::score::{FancyScore, ScorerRegistry, Thresholds};
+use alloy_primitives::{hex, Address};
 use ocl::{Buffer, Context, Device, MemFlags, Platform, ProQue, Program, Queue};
-use rand::{thread_rng, Rng};
+use rand::{rngs::ThreadRng, thread_rng};
+use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::{
     fmt::Write as _,
+    ops::Range,
     thread,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant},
 };
 
+pub mod best;
 pub mod cli;
-mod score;
-
-const PROXY_CHILD_CODEHASH: [u8; 32] = [
-    33, 195, 93, 190, 27, 52, 74, 36, 136, 207, 51, 33, 214, 206, 84, 47, 142, 159, 48, 85, 68,
-    255, 9, 228, 153, 58, 98, 49, 154, 73, 124, 31,
-];
+pub mod db;
+pub mod eoa;
+mod error;
+pub mod events;
+pub mod export;
+pub mod factory;
+pub mod fancy;
+pub mod gas;
+pub mod hexspeak;
+pub mod init_code;
+pub mod job;
+pub mod keyspace;
+pub mod midstate;
+pub mod preimage;
+pub mod price;
+pub mod record;
+pub mod results;
+pub mod safe;
+pub mod salt_layout;
+pub mod score;
+pub mod tui;
+
+pub use error::CrunchError;
 
 static KERNEL_SRC: &str = include_str!("./kernels/keccak256.cl");
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CreateXVariant {
-    Create2 { init_code_hash: [u8; 32] },
+    Create2 {
+        init_code_hash: [u8; 32],
+    },
     Create3,
+    /// CREATE from `deployer` at each nonce in the range. The mined "salt" is the nonce as a
+    /// big-endian 32-byte word.
+    Create {
+        deployer: [u8; 20],
+        nonce_range: Range<u64>,
+    },
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum RewardVariant {
     LeadingAny {
         group: u64,
@@ -47,12 +87,190 @@ pub enum RewardVariant {
         leading_zeros_threshold: u8,
         total_zeros_threshold: u8,
     },
+    /// At least `count` leading nibbles equal to `nibble`, or to the first nibble of the address
+    /// when `nibble` is `None`.
+    LeadingNibbles {
+        count: u8,
+        nibble: Option<u8>,
+    },
+    /// At least `count` trailing nibbles equal to `nibble`, or to the last nibble of the address
+    /// when `nibble` is `None`.
+    TrailingNibbles {
+        count: u8,
+        nibble: Option<u8>,
+    },
+    /// Addresses saving at least `min_gas` of calldata gas over `references` references, see
+    /// [`GasScore`].
+    Gas {
+        min_gas: u64,
+        #[serde(default = "gas::one_reference")]
+        references: u64,
+    },
     Matching {
         pattern: Box<str>,
     },
+    /// Addresses scoring at least the minimum difficulty in one of the scoring categories.
+    Fancy,
 }
 
-#[derive(Clone, Copy)]
+impl RewardVariant {
+    /// Whether the address meets the criteria, as checked by `SUCCESS_CONDITION()` in the kernel.
+    pub fn matches(&self, address: &[u8; 20]) -> bool {
+        let leading =
+            |threshold: u8| address.iter().take_while(|b| **b == 0).count() >= threshold as usize;
+        let total =
+            |threshold: u8| address.iter().filter(|b| **b == 0).count() >= threshold as usize;
+        match self {
+            RewardVariant::LeadingAny {
+                group,
+                leading,
+                ones,
+                ints,
+            } => has_leading_any(address, *group, *leading, *ones, *ints),
+            RewardVariant::LeadingZeros { zeros_threshold } => leading(*zeros_threshold),
+            RewardVariant::TotalZeros { zeros_threshold } => total(*zeros_threshold),
+            RewardVariant::LeadingAndTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => leading(*leading_zeros_threshold) && total(*total_zeros_threshold),
+            RewardVariant::LeadingOrTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => leading(*leading_zeros_threshold) || total(*total_zeros_threshold),
+            RewardVariant::LeadingNibbles { count, nibble } => {
+                nibble_run(nibbles(address), *nibble) >= *count as usize
+            }
+            RewardVariant::TrailingNibbles { count, nibble } => {
+                nibble_run(nibbles(address).rev(), *nibble) >= *count as usize
+            }
+            RewardVariant::Gas {
+                min_gas,
+                references,
+            } => GasScore::new(address).saved(*references) >= *min_gas,
+            RewardVariant::Matching { pattern } => pattern
+                .chars()
+                .zip(hex::encode(address).chars())
+                .all(|(expected, actual)| expected == 'X' || expected == actual),
+            // decided by the scoring categories of the config, see `FancyPredicate`
+            RewardVariant::Fancy => true,
+        }
+    }
+
+    /// Expected number of addresses to check for one to match, or `None` for the criteria without
+    /// an estimate.
+    pub fn difficulty(&self) -> Option<f64> {
+        let leading = |threshold: u8| 256f64.powi(threshold as i32);
+        let total = |threshold: u8| 1.0 / zero_bytes_probability(20, threshold as u32);
+        // the leading zero bytes count towards the total, the other bytes make up the rest
+        let both = |leading_threshold: u8, total_threshold: u8| {
+            leading(leading_threshold)
+                / zero_bytes_probability(
+                    20 - leading_threshold as u32,
+                    total_threshold.saturating_sub(leading_threshold) as u32,
+                )
+        };
+        match self {
+            RewardVariant::LeadingZeros { zeros_threshold } => Some(leading(*zeros_threshold)),
+            RewardVariant::TotalZeros { zeros_threshold } => Some(total(*zeros_threshold)),
+            RewardVariant::LeadingAndTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => Some(both(*leading_zeros_threshold, *total_zeros_threshold)),
+            RewardVariant::LeadingOrTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => Some(
+                1.0 / (1.0 / leading(*leading_zeros_threshold)
+                    + 1.0 / total(*total_zeros_threshold)
+                    - 1.0 / both(*leading_zeros_threshold, *total_zeros_threshold)),
+            ),
+            RewardVariant::LeadingNibbles { count, nibble }
+            | RewardVariant::TrailingNibbles { count, nibble } => {
+                // any nibble can start the run
+                let fixed = *count as i32 - nibble.is_none() as i32;
+                Some(16f64.powi(fixed))
+            }
+            RewardVariant::Gas {
+                min_gas,
+                references,
+            } => gas::min_gas_per_reference(*min_gas, *references)
+                .ok()
+                .map(gas::difficulty),
+            RewardVariant::Matching { pattern } => {
+                Some(16f64.powi(pattern.chars().filter(|c| *c != 'X').count() as i32))
+            }
+            RewardVariant::LeadingAny { .. } | RewardVariant::Fancy => None,
+        }
+    }
+}
+
+/// Nibbles of the address, most significant first.
+fn nibbles(address: &[u8; 20]) -> impl DoubleEndedIterator<Item = u8> + '_ {
+    address.iter().flat_map(|byte| [byte >> 4, byte & 0xf])
+}
+
+/// Length of the run of `nibble` the nibbles start with, or of their first nibble if `None`.
+fn nibble_run(nibbles: impl Iterator<Item = u8>, nibble: Option<u8>) -> usize {
+    let mut nibbles = nibbles.peekable();
+    let Some(first) = nibble.or(nibbles.peek().copied()) else {
+        return 0;
+    };
+    nibbles.take_while(|nibble| *nibble == first).count()
+}
+
+/// Probability of at least `k` of `n` random bytes being zero.
+fn zero_bytes_probability(n: u32, k: u32) -> f64 {
+    let p = 1.0f64 / 256.0;
+    (k..=n)
+        .map(|i| {
+            let choose: f64 = (0..i).map(|j| (n - j) as f64 / (j + 1) as f64).product();
+            choose * p.powi(i as i32) * (1.0 - p).powi((n - i) as i32)
+        })
+        .sum()
+}
+
+/// Port of `hasLeadingAny` in the kernel.
+fn has_leading_any(address: &[u8; 20], group: u64, leading: u64, ones: u64, ints: u64) -> bool {
+    let nibbles: Vec<u8> = address.iter().flat_map(|b| [b >> 4, b & 0xf]).collect();
+
+    let int_count = address
+        .chunks(2)
+        .take_while(|word| *word == &address[..2])
+        .count() as u64;
+
+    let mut counts = [0u64; 16];
+    let mut group_len = 0;
+    let mut group_score = 0;
+    for (i, letter) in nibbles.iter().enumerate() {
+        counts[*letter as usize] += 1;
+        group_len = match i > 0 && nibbles[i - 1] == *letter {
+            true => group_len + 1,
+            false => 1,
+        };
+        group_score += match group_len {
+            3 => 3,
+            4.. => 2,
+            _ => 0,
+        };
+    }
+    let leading_score = nibbles
+        .iter()
+        .take_while(|letter| **letter == nibbles[0])
+        .count() as u64;
+    let one_score = counts.iter().filter(|count| **count == 0).count() as u64;
+    // the first 8 nibbles repeat as the last 8, as in etherscan's shortened addresses
+    let etherscan_sim = nibbles[..8] == nibbles[32..];
+    let no_letters = counts[10..].iter().all(|count| *count == 0);
+
+    int_count >= ints
+        || leading_score >= leading
+        || group_score >= group
+        || one_score >= ones
+        || etherscan_sim
+        || no_letters
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SaltVariant {
     CrosschainSender {
         chain_id: [u8; 32],
@@ -67,19 +285,56 @@ pub enum SaltVariant {
     Random,
 }
 
-pub struct Config<'a> {
+pub const DEFAULT_FACTORY: &str = "0x9e3f8eae49e442a323ef2094f277bf62752e6995";
+pub const DEFAULT_WORK_SIZE: usize = 1_000_000_000;
+pub const DEFAULT_RESULT_BUFFER_SIZE: usize = 20_000;
+pub const DEFAULT_OUTPUT_DIR: &str = "output";
+
+/// Offset of the salt layout in salts hashed in a preimage, which are usually a uint256 nonce
+/// kept reasonably small.
+const SALT_PREIMAGE_OFFSET: usize = 21;
+
+#[derive(Clone)]
+pub struct Config {
     pub gpu_device: u8,
+    /// Launches of the keyspace the device takes, among the devices mining the job.
+    pub device_share: DeviceShare,
     pub work_size: usize,
     pub result_buffer_size: usize,
     pub sleep_for: f64,
     pub factory_address: [u8; 20],
+    pub factory_kind: FactoryKind,
     pub salt_variant: SaltVariant,
     pub create_variant: CreateXVariant,
     pub reward: RewardVariant,
-    pub output: &'a str,
+    pub output: String,
+    pub output_dir: String,
+    /// Identifier of the job that produced this config, recorded with every result.
+    pub job_id: Option<String>,
+    /// Where the init code was taken from, if it was not given as a hash.
+    pub init_code_source: Option<String>,
+    /// Preimage the salt is hashed in before the factory deploys with it, for factories whose
+    /// salt depends on the deployment, like Safe proxies and smart accounts.
+    pub salt_preimage: Option<SaltPreimage>,
+    /// Layout of the bytes of the salt left free by the factory.
+    pub salt_layout: SaltLayout,
+    /// Categories results are scored in before being recorded.
+    pub scoring: ScorerRegistry,
+    /// Difficulties results are recorded and priced at.
+    pub thresholds: Thresholds,
+    /// Metric of the best-so-far mode, recording every address better than the best found so
+    /// far in place of the reward criteria.
+    pub best: Option<BestMetric>,
+    /// Best value of the best-so-far metric found by any device mining the job.
+    pub shared_best: SharedBest,
+    /// Number of results of each category kept in the store file of the output directory, if
+    /// any are.
+    pub keep_top: Option<usize>,
+    /// Results database every accepted result is also stored in, if any.
+    pub database: Option<String>,
 }
 
-impl<'a> Config<'a> {
+impl Config {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         gpu_device: u8,
@@ -91,38 +346,418 @@ impl<'a> Config<'a> {
         chain_id: Option<u64>,
         init_code_hash: Option<&str>,
         reward: RewardVariant,
-        output: &'a str,
-    ) -> Result<Self, &'static str> {
-        // convert main arguments from hex string to vector of bytes
-        let factory_address_vec =
-            hex::decode(factory_address_str).expect("could not decode factory address argument");
-        let calling_address_vec = calling_address_str.map(|calling_address| {
-            hex::decode(calling_address).expect("could not decode calling address argument")
-        });
-        let init_code_hash_vec = init_code_hash.map(|init_code_hash| {
-            hex::decode(init_code_hash).expect("could not decode init code hash argument")
-        });
+        output: &str,
+    ) -> Result<Self, CrunchError> {
+        let mut builder = ConfigBuilder::new()
+            .gpu_device(gpu_device)
+            .work_size(work_size)
+            .result_buffer_size(result_buffer_size)
+            .sleep_for(sleep_for)
+            .factory_address(factory_address_str)
+            .reward(reward)
+            .output(output);
+        if let Some(calling_address) = calling_address_str {
+            builder = builder.calling_address(calling_address);
+        }
+        if let Some(chain_id) = chain_id {
+            builder = builder.chain_id(chain_id);
+        }
+        if let Some(init_code_hash) = init_code_hash {
+            builder = builder.init_code_hash(init_code_hash);
+        }
+        builder.build()
+    }
 
-        // convert from vector to fixed array
-        let factory_address = TryInto::<[u8; 20]>::try_into(factory_address_vec)
-            .expect("invalid length for factory address argument");
-        let calling_address = calling_address_vec.map(|calling_address_vec| {
-            TryInto::<[u8; 20]>::try_into(calling_address_vec)
-                .expect("invalid length for calling address argument")
-        });
-        let init_code_hash = init_code_hash_vec.map(|init_code_hash_vec| {
-            TryInto::<[u8; 32]>::try_into(init_code_hash_vec)
-                .expect("invalid length for init code hash argument")
-        });
+    /// Scores an address in the categories of the config.
+    pub fn score(&self, address: &[u8; 20]) -> FancyScore {
+        self.scoring
+            .score(web3::types::Address::from_slice(address), &self.thresholds)
+    }
+
+    /// The acceptance rule a scored address meets, as recorded with it, e.g.
+    /// `leading_zeroes>=4294967296`. Under gas criteria, the rule is followed by the gas the
+    /// address saves, e.g. `gas>=200;saved=240;per_reference=24;zero_bytes=2;leading_zero_bytes=0`
+    /// for 10 references. `None` if the address is not accepted.
+    pub fn acceptance(&self, address: &[u8; 20], score: &FancyScore) -> Option<String> {
+        if let RewardVariant::Gas {
+            min_gas,
+            references,
+        } = self.reward
+        {
+            let gas = GasScore::new(address);
+            return self.reward.matches(address).then(|| {
+                format!(
+                    "gas>={min_gas};saved={};per_reference={};zero_bytes={};leading_zero_bytes={}",
+                    gas.saved(references),
+                    gas.gas,
+                    gas.zero_bytes,
+                    gas.leading_zero_bytes
+                )
+            });
+        }
+        let category = score.accepted_by.as_ref()?;
+        Some(format!("{category}>={}", self.thresholds.minimum(category)))
+    }
+
+    /// Builds the salt passed to the factory from the bytes mined by the kernel, in the order
+    /// of the salt layout. For CREATE deployments, see [`Config::nonce_salt`].
+    pub fn salt(&self, mined: &[u8]) -> [u8; 32] {
+        let (mut salt, offset) = self.salt_template();
+        self.salt_layout.fill(&mut salt[offset..], mined);
+        salt
+    }
+
+    /// Builds the salt found by work item `work_item` of the launch `launch`, given the random
+    /// bytes of the launch.
+    pub fn launch_salt(&self, random: &[u8], launch: u64, work_item: u32) -> [u8; 32] {
+        let counter = (launch as u128) << 32 | work_item as u128;
+        self.salt(&self.salt_layout.mined(random, counter))
+    }
+
+    /// Returns the fixed bytes of the salt, including those of the salt layout, and the offset
+    /// at which the layout starts.
+    pub fn salt_template(&self) -> ([u8; 32], usize) {
+        let (mut salt, offset) = match self.salt_preimage {
+            Some(_) => ([0u8; 32], SALT_PREIMAGE_OFFSET),
+            None => self.factory_kind.salt_template(&self.salt_variant),
+        };
+        let mined = vec![0u8; self.salt_layout.mined_len()];
+        self.salt_layout.fill(&mut salt[offset..], &mined);
+        (salt, offset)
+    }
+
+    /// Returns the preimage the salt is hashed in and the offset of the salt within it, or `None`
+    /// if the factory deploys with the salt as is.
+    pub fn guard_preimage(&self) -> Option<(Vec<u8>, usize)> {
+        match &self.salt_preimage {
+            Some(salt_preimage) => salt_preimage.template(),
+            None => self.factory_kind.guard_preimage(&self.salt_variant),
+        }
+    }
+
+    /// Computes the salt the factory ultimately deploys with. Salt preimages are hashed the way
+    /// the factory does rather than through their template, to catch templates that do not
+    /// match the factory.
+    pub fn guard_salt(&self, salt: &[u8; 32]) -> [u8; 32] {
+        match &self.salt_preimage {
+            Some(salt_preimage) => salt_preimage.hash(salt),
+            None => self.factory_kind.guard_salt(&self.salt_variant, salt),
+        }
+    }
+
+    /// Encodes a CREATE nonce the way it is recorded in place of a salt.
+    pub fn nonce_salt(nonce: u64) -> [u8; 32] {
+        let mut salt = [0u8; 32];
+        salt[24..].copy_from_slice(&nonce.to_be_bytes());
+        salt
+    }
+
+    /// Computes the address the factory deploys to when called with the given salt.
+    pub fn derive_address(&self, salt: &[u8; 32]) -> [u8; 20] {
+        if let CreateXVariant::Create { deployer, .. } = self.create_variant {
+            return create_address(
+                &deployer,
+                u64::from_be_bytes(salt[24..].try_into().unwrap()),
+            );
+        }
+        let salt = self.guard_salt(salt);
+        match self.create_variant {
+            CreateXVariant::Create2 { init_code_hash } => {
+                create2_address(&self.factory_address, &salt, &init_code_hash)
+            }
+            CreateXVariant::Create3 => create3_address(
+                &self.factory_address,
+                &salt,
+                &self.factory_kind.proxy_codehash(),
+            ),
+            CreateXVariant::Create { .. } => unreachable!(),
+        }
+    }
+}
+
+/// Builder for [`Config`]. Every input is validated when calling [`ConfigBuilder::build`].
+#[derive(Clone)]
+pub struct ConfigBuilder {
+    gpu_device: u8,
+    device_share: DeviceShare,
+    work_size: usize,
+    result_buffer_size: usize,
+    sleep_for: f64,
+    factory_address: Option<String>,
+    factory_kind: Option<FactoryKind>,
+    calling_address: Option<String>,
+    chain_id: Option<u64>,
+    init_code_hash: Option<String>,
+    init_code_source: Option<String>,
+    salt_preimage: Option<SaltPreimage>,
+    salt_layout: Option<SaltLayout>,
+    deployer: Option<String>,
+    nonce_range: Option<Range<u64>>,
+    reward: Option<RewardVariant>,
+    scoring: ScorerRegistry,
+    thresholds: Thresholds,
+    best: Option<BestMetric>,
+    keep_top: Option<usize>,
+    database: Option<String>,
+    output: String,
+    output_dir: String,
+    job_id: Option<String>,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self {
+            gpu_device: 0,
+            device_share: DeviceShare::default(),
+            work_size: DEFAULT_WORK_SIZE,
+            result_buffer_size: DEFAULT_RESULT_BUFFER_SIZE,
+            sleep_for: 0.0,
+            factory_address: None,
+            factory_kind: None,
+            calling_address: None,
+            chain_id: None,
+            init_code_hash: None,
+            init_code_source: None,
+            salt_preimage: None,
+            salt_layout: None,
+            deployer: None,
+            nonce_range: None,
+            reward: None,
+            scoring: ScorerRegistry::default(),
+            thresholds: Thresholds::default(),
+            best: None,
+            keep_top: None,
+            database: None,
+            output: "output.txt".to_string(),
+            output_dir: DEFAULT_OUTPUT_DIR.to_string(),
+            job_id: None,
+        }
+    }
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn gpu_device(mut self, gpu_device: u8) -> Self {
+        self.gpu_device = gpu_device;
+        self
+    }
+
+    /// Splits the launches of a keyspace without random bytes between `count` devices, of which
+    /// this is the one at `index`.
+    pub fn device_share(mut self, index: u64, count: u64) -> Self {
+        self.device_share = DeviceShare { index, count };
+        self
+    }
+
+    pub fn work_size(mut self, work_size: usize) -> Self {
+        self.work_size = work_size;
+        self
+    }
+
+    pub fn result_buffer_size(mut self, result_buffer_size: usize) -> Self {
+        self.result_buffer_size = result_buffer_size;
+        self
+    }
+
+    pub fn sleep_for(mut self, sleep_for: f64) -> Self {
+        self.sleep_for = sleep_for;
+        self
+    }
+
+    /// Sets the factory address. Without it, the canonical address of the factory kind is used.
+    pub fn factory_address(mut self, factory_address: impl Into<String>) -> Self {
+        self.factory_address = Some(factory_address.into());
+        self
+    }
+
+    /// Sets the factory kind. Without it, the kind is recognized from the factory address,
+    /// falling back to [`FactoryKind::CreateX`] like the default factory.
+    pub fn factory_kind(mut self, factory_kind: FactoryKind) -> Self {
+        self.factory_kind = Some(factory_kind);
+        self
+    }
+
+    pub fn calling_address(mut self, calling_address: impl Into<String>) -> Self {
+        self.calling_address = Some(calling_address.into());
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Sets the init code hash, selecting a CREATE2 deployment. Without it, CREATE3 is used.
+    pub fn init_code_hash(mut self, init_code_hash: impl Into<String>) -> Self {
+        self.init_code_hash = Some(init_code_hash.into());
+        self.init_code_source = None;
+        self
+    }
+
+    /// Sets the init code hash from the init code itself, remembering where it came from.
+    pub fn init_code(mut self, init_code: &InitCode) -> Self {
+        self.init_code_hash = Some(hex::encode(init_code.hash()));
+        self.init_code_source = Some(init_code.describe());
+        self
+    }
+
+    /// Sets the preimage the salt is hashed in, for factories that guard the salt with data of
+    /// the deployment.
+    pub fn salt_preimage(mut self, salt_preimage: SaltPreimage) -> Self {
+        self.salt_preimage = Some(salt_preimage);
+        self
+    }
+
+    /// Sets which bytes of the salt are fixed, random and enumerated. Defaults to
+    /// [`SaltLayout::default`].
+    pub fn salt_layout(mut self, salt_layout: SaltLayout) -> Self {
+        self.salt_layout = Some(salt_layout);
+        self
+    }
+
+    /// Selects a CREATE deployment at each of the nonces, mined instead of a salt.
+    pub fn nonce_range(mut self, nonce_range: Range<u64>) -> Self {
+        self.nonce_range = Some(nonce_range);
+        self
+    }
+
+    /// Sets the deployer of a CREATE deployment. Without it, the factory deploys the contract.
+    pub fn deployer(mut self, deployer: impl Into<String>) -> Self {
+        self.deployer = Some(deployer.into());
+        self
+    }
+
+    /// Sets the criteria addresses are searched for. Defaults to [`RewardVariant::LeadingAny`]
+    /// with a group of 24, 7 leading, 9 ones and 4 ints; fancy addresses are searched for with
+    /// [`RewardVariant::Fancy`], which the best-so-far mode defaults to.
+    pub fn reward(mut self, reward: RewardVariant) -> Self {
+        self.reward = Some(reward);
+        self
+    }
+
+    /// Sets the categories results are scored in. Defaults to the built-in categories.
+    pub fn scoring(mut self, scoring: ScorerRegistry) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Sets the difficulties results are recorded and priced at. Defaults to
+    /// [`Thresholds::default`].
+    pub fn thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Selects the best-so-far mode, ranking addresses by the metric instead of checking them
+    /// against the reward criteria, which must be left to [`RewardVariant::Fancy`].
+    pub fn best(mut self, best: BestMetric) -> Self {
+        self.best = Some(best);
+        self
+    }
+
+    pub fn keep_top(mut self, keep_top: usize) -> Self {
+        self.keep_top = Some(keep_top);
+        self
+    }
+
+    pub fn database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    pub fn output(mut self, output: impl Into<String>) -> Self {
+        self.output = output.into();
+        self
+    }
+
+    pub fn output_dir(mut self, output_dir: impl Into<String>) -> Self {
+        self.output_dir = output_dir.into();
+        self
+    }
+
+    pub fn job_id(mut self, job_id: impl Into<String>) -> Self {
+        self.job_id = Some(job_id.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Config, CrunchError> {
+        // convert main arguments from hex strings to fixed arrays
+        let (factory_address, factory_kind) = match (&self.factory_address, self.factory_kind) {
+            (Some(address), Some(kind)) => (parse_address("factory address", address)?, kind),
+            (Some(address), None) => {
+                let address = parse_address("factory address", address)?;
+                (
+                    address,
+                    FactoryKind::from_address(&address).unwrap_or_default(),
+                )
+            }
+            (None, None) => (
+                parse_address("factory address", DEFAULT_FACTORY)?,
+                FactoryKind::default(),
+            ),
+            (None, Some(kind)) => {
+                let address = kind.default_address().ok_or_else(|| {
+                    CrunchError::UnsupportedFactory(format!(
+                        "{kind} has no canonical address, the factory address must be given"
+                    ))
+                })?;
+                (parse_address("factory address", address)?, kind)
+            }
+        };
+        let calling_address = self
+            .calling_address
+            .as_deref()
+            .map(|calling_address| parse_address("caller address", calling_address))
+            .transpose()?;
+        let init_code_hash = self
+            .init_code_hash
+            .as_deref()
+            .map(|init_code_hash| decode_fixed::<32>("init code hash", init_code_hash))
+            .transpose()?;
+        let chain_id = self.chain_id;
+        let reward = match (self.reward.clone(), self.best) {
+            (Some(reward), _) => reward,
+            (None, Some(_)) => RewardVariant::Fancy,
+            (None, None) => RewardVariant::LeadingAny {
+                group: 24,
+                leading: 7,
+                ones: 9,
+                ints: 4,
+            },
+        };
         let chain_id = chain_id.map(|chain_id| {
             let mut arr = [0u8; 32];
             arr[24..].copy_from_slice(&chain_id.to_be_bytes());
             arr
         });
 
-        let create_variant = match init_code_hash {
-            Some(init_code_hash) => CreateXVariant::Create2 { init_code_hash },
-            None => CreateXVariant::Create3 {},
+        let create_variant = match (self.nonce_range, init_code_hash) {
+            (Some(nonce_range), None) => {
+                if nonce_range.is_empty() {
+                    return Err(CrunchError::InvalidNonceRange(format!(
+                        "{}..{} is empty",
+                        nonce_range.start, nonce_range.end
+                    )));
+                }
+                let deployer = match &self.deployer {
+                    Some(deployer) => parse_address("deployer address", deployer)?,
+                    None => factory_address,
+                };
+                CreateXVariant::Create {
+                    deployer,
+                    nonce_range,
+                }
+            }
+            (Some(_), Some(_)) => {
+                return Err(CrunchError::InvalidNonceRange(
+                    "CREATE deployments do not take an init code hash".to_string(),
+                ))
+            }
+            (None, Some(init_code_hash)) => CreateXVariant::Create2 { init_code_hash },
+            (None, None) => CreateXVariant::Create3 {},
         };
 
         match &reward {
@@ -141,23 +776,47 @@ impl<'a> Config<'a> {
                 validate_zeros_threshold(leading_zeros_threshold)?;
                 validate_zeros_threshold(total_zeros_threshold)?;
             }
+            RewardVariant::LeadingNibbles { count, nibble }
+            | RewardVariant::TrailingNibbles { count, nibble } => {
+                if *count == 0 || *count > 40 {
+                    return Err(CrunchError::InvalidThreshold {
+                        value: *count,
+                        max: 40,
+                    });
+                }
+                if nibble.is_some_and(|nibble| nibble > 0xf) {
+                    return Err(CrunchError::InvalidPattern(
+                        "nibble must be between 0 and f",
+                    ));
+                }
+            }
+            RewardVariant::Gas {
+                min_gas,
+                references,
+            } => {
+                gas::min_gas_per_reference(*min_gas, *references)?;
+            }
             RewardVariant::Matching { pattern } => {
                 if pattern.len() != 40 {
-                    return Err("matching pattern must be 40 characters long");
+                    return Err(CrunchError::InvalidPattern(
+                        "matching pattern must be 40 characters long",
+                    ));
                 }
                 if !pattern.chars().all(|c| c == 'X' || c.is_ascii_hexdigit()) {
-                    return Err("matching pattern must only contain 'X' or hex characters");
+                    return Err(CrunchError::InvalidPattern(
+                        "matching pattern must only contain 'X' or hex characters",
+                    ));
                 }
             }
-            RewardVariant::LeadingAny { .. } => {}
+            RewardVariant::LeadingAny { .. } | RewardVariant::Fancy => {}
         }
 
-        fn validate_zeros_threshold(threhsold: &u8) -> Result<(), &'static str> {
-            if threhsold == &0u8 {
-                return Err("threshold must be greater than 0");
-            }
-            if threhsold > &20u8 {
-                return Err("threshold must be less than 20");
+        fn validate_zeros_threshold(threshold: &u8) -> Result<(), CrunchError> {
+            if *threshold == 0 || *threshold > 20 {
+                return Err(CrunchError::InvalidThreshold {
+                    value: *threshold,
+                    max: 20,
+                });
             }
 
             Ok(())
@@ -176,283 +835,677 @@ impl<'a> Config<'a> {
             }
             _ => SaltVariant::Random,
         };
-
-        if factory_address_str.chars().any(|c| c.is_uppercase()) {
-            let factory_address_str = match factory_address_str.strip_prefix("0x") {
-                Some(_) => factory_address_str.to_string(),
-                None => format!("0x{}", factory_address_str),
-            };
-            match Address::parse_checksummed(factory_address_str, None) {
-                Ok(_) => {}
-                Err(_) => {
-                    return Err("factory address uses invalid checksum");
-                }
+        factory_kind.validate(&create_variant, &salt_variant)?;
+        match &self.salt_preimage {
+            Some(_) if factory_kind.guard_preimage(&salt_variant).is_some() => {
+                return Err(CrunchError::UnsupportedFactory(format!(
+                    "{factory_kind} guards the salt on its own"
+                )))
             }
+            Some(salt_preimage) => salt_preimage.validate()?,
+            None if factory_kind == FactoryKind::SafeProxy => {
+                return Err(CrunchError::UnsupportedFactory(format!(
+                    "{factory_kind} requires the initializer of the proxy"
+                )))
+            }
+            _ => {}
         }
-
-        if calling_address.is_some() {
-            let calling_address_str = calling_address_str.unwrap();
-            if calling_address_str.chars().any(|c| c.is_uppercase()) {
-                let calling_address_str = match calling_address_str.strip_prefix("0x") {
-                    Some(_) => calling_address_str.to_string(),
-                    None => format!("0x{}", calling_address_str),
-                };
-                match Address::parse_checksummed(calling_address_str, None) {
-                    Ok(_) => {}
-                    Err(_) => {
-                        return Err("caller address uses invalid checksum");
-                    }
-                }
-            };
+        if self.scoring.is_empty() {
+            return Err(CrunchError::InvalidScoring(
+                "at least one category must be enabled".to_string(),
+            ));
+        }
+        self.thresholds.validate(&self.scoring)?;
+        if self.best.is_some() {
+            if reward != RewardVariant::Fancy {
+                return Err(CrunchError::InvalidJob(
+                    "best-so-far mode ranks addresses by its metric, without reward criteria"
+                        .to_string(),
+                ));
+            }
+            if let CreateXVariant::Create { .. } = create_variant {
+                return Err(CrunchError::InvalidJob(
+                    "best-so-far mode mines salts, not nonce ranges".to_string(),
+                ));
+            }
+        }
+        let salt_layout = match (self.salt_layout, &create_variant) {
+            (Some(_), CreateXVariant::Create { .. }) => {
+                return Err(CrunchError::InvalidSaltLayout(
+                    "CREATE deployments mine nonces, not salts".to_string(),
+                ))
+            }
+            (salt_layout, _) => salt_layout.unwrap_or_default(),
+        };
+        let mined_offset = match self.salt_preimage {
+            Some(_) => SALT_PREIMAGE_OFFSET,
+            None => factory_kind.salt_template(&salt_variant).1,
         };
+        salt_layout.validate(32 - mined_offset)?;
+        if factory_kind == FactoryKind::CreateX
+            && salt_variant == SaltVariant::Random
+            && self.salt_preimage.is_none()
+        {
+            salt_layout.validate_createx_random()?;
+        }
+        if self.work_size == 0 {
+            return Err(CrunchError::InvalidWorkSize(
+                "at least one work item is required".to_string(),
+            ));
+        }
+        // the counter of a salt holds the work item in 4 bytes, larger launches would rehash salts
+        if !matches!(create_variant, CreateXVariant::Create { .. })
+            && self.work_size as u64 > keyspace::MAX_WORK_SIZE
+        {
+            return Err(CrunchError::InvalidWorkSize(format!(
+                "{} work items alias salts, a launch enumerates at most {}",
+                self.work_size,
+                keyspace::MAX_WORK_SIZE
+            )));
+        }
+        if self.device_share.index >= self.device_share.count {
+            return Err(CrunchError::InvalidJob(format!(
+                "device {} of a share of {} devices",
+                self.device_share.index, self.device_share.count
+            )));
+        }
+        if self.keep_top == Some(0) {
+            return Err(CrunchError::InvalidJob(
+                "at least one result of each category must be kept".to_string(),
+            ));
+        }
 
-        Ok(Self {
-            gpu_device,
-            sleep_for,
-            work_size,
-            result_buffer_size,
+        Ok(Config {
+            gpu_device: self.gpu_device,
+            device_share: self.device_share,
+            sleep_for: self.sleep_for,
+            work_size: self.work_size,
+            result_buffer_size: self.result_buffer_size,
             factory_address,
+            factory_kind,
             salt_variant,
             create_variant,
             reward,
-            output,
+            output: self.output,
+            output_dir: self.output_dir,
+            job_id: self.job_id,
+            init_code_source: self.init_code_source,
+            salt_preimage: self.salt_preimage,
+            salt_layout,
+            scoring: self.scoring,
+            thresholds: self.thresholds,
+            best: self.best,
+            shared_best: SharedBest::default(),
+            keep_top: self.keep_top,
+            database: self.database,
         })
     }
 }
 
+/// Parses the nibble of a run of leading or trailing nibbles: a hex digit, or `any` for `None`.
+pub fn parse_nibble(value: &str) -> Result<Option<u8>, CrunchError> {
+    match value {
+        "any" => Ok(None),
+        _ if value.len() == 1 => u8::from_str_radix(value, 16)
+            .map(Some)
+            .map_err(|_| CrunchError::InvalidPattern("nibble must be a hex digit or any")),
+        _ => Err(CrunchError::InvalidPattern(
+            "nibble must be a hex digit or any",
+        )),
+    }
+}
+
+/// Parses a range of nonces given as `start..end`, `start..=end` or a single nonce.
+pub fn parse_nonce_range(value: &str) -> Result<Range<u64>, CrunchError> {
+    let parse = |nonce: &str| {
+        nonce
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| CrunchError::InvalidNonceRange(format!("invalid nonce: {nonce}")))
+    };
+    let range = if let Some((start, end)) = value.split_once("..=") {
+        let end = parse(end)?;
+        let end = end.checked_add(1).ok_or_else(|| {
+            CrunchError::InvalidNonceRange(format!("{value} ends after the last nonce"))
+        })?;
+        parse(start)?..end
+    } else if let Some((start, end)) = value.split_once("..") {
+        parse(start)?..parse(end)?
+    } else {
+        let nonce = parse(value)?;
+        nonce..nonce.saturating_add(1)
+    };
+    Ok(range)
+}
+
+/// Decodes a hex string (with or without `0x` prefix) into a fixed-size array.
+fn decode_fixed<const N: usize>(field: &'static str, value: &str) -> Result<[u8; N], CrunchError> {
+    let bytes = hex::decode(value).map_err(|_| CrunchError::InvalidHex {
+        field,
+        value: value.to_string(),
+    })?;
+    let actual = bytes.len();
+    bytes.try_into().map_err(|_| CrunchError::InvalidLength {
+        field,
+        expected: N,
+        actual,
+    })
+}
+
+/// Decodes an address and, if it contains uppercase characters, verifies its EIP-55 checksum.
+pub fn parse_address(field: &'static str, value: &str) -> Result<[u8; 20], CrunchError> {
+    let address = decode_fixed::<20>(field, value)?;
+
+    if value.chars().any(|c| c.is_uppercase()) {
+        let value = match value.strip_prefix("0x") {
+            Some(_) => value.to_string(),
+            None => format!("0x{}", value),
+        };
+        if Address::parse_checksummed(value, None).is_err() {
+            return Err(CrunchError::InvalidChecksum { field });
+        }
+    }
+
+    Ok(address)
+}
+
 /// Adapted from https://github.com/0age/create2crunch
 ///
-pub fn gpu(config: Config) -> ocl::Result<()> {
+pub fn gpu(config: Config) -> Result<(), CrunchError> {
     // set up a platform to use
     let platform = Platform::new(ocl::core::default_platform()?);
 
     //make sure output directory exists
 
-    if std::fs::metadata("output").is_err() {
+    if std::fs::metadata(&config.output_dir).is_err() {
         log::info!("Creating output directory");
-        std::fs::create_dir("output")?;
+        std::fs::create_dir_all(&config.output_dir)?;
     }
     // set up the device to use
-    let device = Device::by_idx_wrap(platform, config.gpu_device as usize)?;
+    let devices = Device::list_all(platform)?;
+    let device = *devices
+        .get(config.gpu_device as usize)
+        .ok_or(CrunchError::DeviceNotFound {
+            index: config.gpu_device as usize,
+            available: devices.len(),
+        })?;
+
+    let name = device.name().unwrap_or("Unknown device".to_string());
+    match tui::active() {
+        Some(dashboard) => dashboard.add_device(config.gpu_device, name),
+        None if events::enabled() => log::info!("Using device: {}", name),
+        None => println!("Using device: {}", name),
+    }
+    // set up the "proqueue" (or amalgamation of various elements) to use
+    let build = |config: &Config| -> Result<ProQue, CrunchError> {
+        // set up the context to use
+        let context = Context::builder()
+            .platform(platform)
+            .devices(device)
+            .build()?;
 
-    println!(
-        "Using device: {}",
-        device.name().unwrap_or("Unknown device".to_string())
-    );
-    // set up the context to use
-    let context = Context::builder()
-        .platform(platform)
-        .devices(device)
-        .build()?;
+        // set up the program to use
+        let program = Program::builder()
+            .devices(device)
+            .src(mk_kernel_src(config))
+            .build(&context)
+            .map_err(|e| CrunchError::KernelBuild { log: e.to_string() })?;
 
-    // set up the program to use
-    let program = Program::builder()
-        .devices(device)
-        .src(mk_kernel_src(&config))
-        .build(&context)?;
+        // set up the queue to use
+        let queue = Queue::new(&context, device, None)?;
 
-    // set up the queue to use
-    let queue = Queue::new(&context, device, None)?;
+        Ok(ProQue::new(context, queue, program, Some(config.work_size)))
+    };
 
-    // set up the "proqueue" (or amalgamation of various elements) to use
-    let ocl_pq = ProQue::new(context, queue, program, Some(config.work_size));
+    // the best-so-far mode rebuilds the kernel as the best improves
+    if config.best.is_some() {
+        return best::mine(&config, build);
+    }
+    let ocl_pq = build(&config)?;
 
-    // create a random number generator
-    let mut rng = thread_rng();
+    if let CreateXVariant::Create {
+        ref nonce_range, ..
+    } = config.create_variant
+    {
+        return scan_nonces(&config, &ocl_pq, nonce_range.clone());
+    }
 
-    // the last work duration in milliseconds
-    let mut work_duration_millis: u64 = 0;
+    let mut launcher = Launcher::new(&config);
+    let mut recording: Vec<thread::JoinHandle<()>> = Vec::new();
 
-    let mut number_found = 0;
+    // begin searching for addresses
+    while let Some(batch) = launcher.launch(&ocl_pq, None)? {
+        launcher.report(batch.elapsed, launcher.keyspace().to_string());
 
-    let mut total_processed = 0;
+        // record the solutions on another thread while the next launch runs
+        if batch.solutions.iter().all(|&word| word == 0) {
+            continue;
+        }
+        let total_processed = launcher.total_processed();
+        let config = config.clone();
+        recording.retain(|handle| !handle.is_finished());
+        recording.push(std::thread::spawn(move || {
+            let mut solution_count_rej = 0;
+            let mut solution_count_acc = 0;
+            for solution in batch.solutions.chunks(4) {
+                if solution.iter().all(|&word| word == 0) {
+                    continue;
+                }
 
-    // begin searching for addresses
-    loop {
-        // reset nonce & create a buffer to view it in little-endian
-        // for more uniformly distributed nonces, we shall initialize it to a random value
-        let mut nonce: [u32; 1] = rng.gen();
+                // get the salt that results from the hash
+                let launch = &batch.launch;
+                let salt = config.launch_salt(&launch.message, launch.index, solution[0] as u32);
+                let address = solution_address(solution);
+
+                if record_solution(&config, salt, address, total_processed) {
+                    solution_count_acc += 1;
+                } else {
+                    solution_count_rej += 1;
+                }
+            }
+
+            log::info!(
+                "Found {} solutions, {} accepted, {} rejected",
+                solution_count_acc + solution_count_rej,
+                solution_count_acc,
+                solution_count_rej
+            );
+        }));
+    }
+
+    log::info!(
+        "Keyspace exhausted after {} salts, covered {}",
+        launcher.keyspace().covered(),
+        launcher.keyspace()
+    );
+    for handle in recording {
+        let _ = handle.join();
+    }
+    Ok(())
+}
+
+/// A launch of the kernel and the solutions it reported, all zero if it found none.
+pub(crate) struct Batch {
+    pub launch: Launch,
+    pub solutions: Vec<u64>,
+    pub elapsed: Duration,
+}
+
+/// The launch loop shared by the threshold and the best-so-far modes: hands the launches of the
+/// keyspace to the kernel one after the other, and reads back the solutions of each.
+pub(crate) struct Launcher {
+    config: Config,
+    keyspace: Keyspace,
+    rng: ThreadRng,
+    total_processed: u64,
+}
+
+impl Launcher {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            // hands out the random bytes and launch index of every launch, so that no salt is
+            // rehashed
+            keyspace: Keyspace::new(config),
+            rng: thread_rng(),
+            total_processed: 0,
+        }
+    }
+
+    pub(crate) fn keyspace(&self) -> &Keyspace {
+        &self.keyspace
+    }
+
+    pub(crate) fn total_processed(&self) -> u64 {
+        self.total_processed
+    }
+
+    /// Enqueues the kernel on the next launch of the keyspace and waits for its solutions, or
+    /// returns `None` once the keyspace is exhausted. Kernels of the best-so-far mode are passed
+    /// the running `best`, which they only report improvements over.
+    pub(crate) fn launch(
+        &mut self,
+        ocl_pq: &ProQue,
+        best: Option<u32>,
+    ) -> Result<Option<Batch>, CrunchError> {
+        tui::wait_while_paused();
+        let Some(launch) = self.keyspace.next(&mut self.rng) else {
+            return Ok(None);
+        };
+        let started = Instant::now();
+
+        // build a corresponding buffer for passing the message to the kernel
+        let message = launch.kernel_message();
+        let message_buffer = Buffer::builder()
+            .queue(ocl_pq.queue().clone())
+            .flags(MemFlags::new().read_only())
+            .len(message.len())
+            .copy_host_slice(message)
+            .build()?;
 
-        // build a corresponding buffer for passing the nonce to the kernel
-        let mut nonce_buffer = Buffer::builder()
+        // build a corresponding buffer for passing the launch index to the kernel
+        let nonce_buffer = Buffer::builder()
             .queue(ocl_pq.queue().clone())
             .flags(MemFlags::new().read_only())
             .len(1)
-            .copy_host_slice(&nonce)
+            .copy_host_slice(&[launch.index])
             .build()?;
 
         // establish a buffer for nonces that result in desired addresses
-        let mut solutions: Vec<u64> = vec![0; 4 * config.result_buffer_size];
+        let mut solutions: Vec<u64> = vec![0; 4 * self.config.result_buffer_size];
         let solutions_buffer = Buffer::builder()
             .queue(ocl_pq.queue().clone())
             .flags(MemFlags::new().write_only())
-            .len(4 * config.result_buffer_size)
+            .len(4 * self.config.result_buffer_size)
             .copy_host_slice(&solutions)
             .build()?;
-        // construct the 4-byte message to hash, leaving last 8 of salt empty
-        let mut salt;
-
-        // repeatedly enqueue kernel to search for new addresses
-        'middle: loop {
-            salt = FixedBytes::<4>::random();
-
-            // build a corresponding buffer for passing the message to the kernel
-            let message_buffer = Buffer::builder()
-                .queue(ocl_pq.queue().clone())
-                .flags(MemFlags::new().read_only())
-                .len(4)
-                .copy_host_slice(&salt[..])
-                .build()?;
-
-            // build the kernel and define the type of each buffer
-            let kern = ocl_pq
-                .kernel_builder("hashMessage")
-                .arg_named("message", None::<&Buffer<u8>>)
-                .arg_named("nonce", None::<&Buffer<u32>>)
-                .arg_named("solutions", None::<&Buffer<u64>>)
-                .build()?;
-
-            // set each buffer
-            kern.set_arg("message", Some(&message_buffer))?;
-            kern.set_arg("nonce", Some(&nonce_buffer))?;
-            kern.set_arg("solutions", &solutions_buffer)?;
-
-            // enqueue the kernel
-            unsafe { kern.enq()? };
-
-            // calculate the current time
-            let mut now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-
-            // record the start time of the work
-            let work_start_time_millis = now.as_secs() * 1000 + now.subsec_nanos() as u64 / 1000000;
-
-            //if work_duration_millis > 0  {
-            total_processed += config.work_size as u64;
-            println!(
-                "Processed: {:.1}GH, message {}, nonce {}, last {} took {}ms. Avg {:.1}Mh/s",
-                total_processed as f64 / 1.0E9,
-                hex::encode(salt),
-                nonce[0] as u16,
+        let best_buffer = match best {
+            Some(best) => Some(
+                Buffer::builder()
+                    .queue(ocl_pq.queue().clone())
+                    .flags(MemFlags::new().read_write())
+                    .len(1)
+                    .copy_host_slice(&[best])
+                    .build()?,
+            ),
+            None => None,
+        };
+
+        // build the kernel, set each buffer and enqueue it
+        let mut kern = ocl_pq.kernel_builder("hashMessage");
+        kern.arg(&message_buffer)
+            .arg(&nonce_buffer)
+            .arg(&solutions_buffer);
+        if let Some(best_buffer) = &best_buffer {
+            kern.arg(best_buffer);
+        }
+        unsafe { kern.build()?.enq()? };
+
+        thread::sleep(tui::sleep_for(self.config.sleep_for));
+
+        // read the solutions from the device
+        solutions_buffer.read(&mut solutions).enq()?;
+        self.total_processed += self.config.work_size as u64;
+
+        Ok(Some(Batch {
+            launch,
+            solutions,
+            elapsed: started.elapsed(),
+        }))
+    }
+
+    /// Reports the progress of a launch that took `elapsed`, at the position of the device.
+    pub(crate) fn report(&self, elapsed: Duration, position: String) {
+        let config = &self.config;
+        match tui::active() {
+            Some(dashboard) => dashboard.batch(
+                config.gpu_device,
+                config.work_size as u64,
+                elapsed,
+                position,
+            ),
+            None if events::enabled() => events::emit(&Event::progress(
+                Some(config.gpu_device),
+                self.total_processed,
+                config.work_size as u64,
+                elapsed,
+                position,
+            )),
+            None => println!(
+                "Processed: {:.1}GH, {}, last {} took {}ms. Avg {:.1}Mh/s",
+                self.total_processed as f64 / 1.0E9,
+                position,
                 config.work_size,
-                work_duration_millis,
-                config.work_size as f64 / work_duration_millis as f64 / 1000.0
-            );
-            //}
+                elapsed.as_millis(),
+                config.work_size as f64 / elapsed.as_secs_f64() / 1.0E6
+            ),
+        }
+    }
+}
 
-            thread::sleep(std::time::Duration::from_secs_f64(config.sleep_for));
+/// Checks every nonce of a CREATE deployment once, in batches of `work_size` nonces split between
+/// the devices of the job, and returns when the batches of the device are exhausted.
+fn scan_nonces(
+    config: &Config,
+    ocl_pq: &ProQue,
+    nonce_range: Range<u64>,
+) -> Result<(), CrunchError> {
+    // the kernel does not use the message when mining nonces
+    let message_buffer = Buffer::builder()
+        .queue(ocl_pq.queue().clone())
+        .flags(MemFlags::new().read_only())
+        .len(4)
+        .copy_host_slice(&[0u8; 4])
+        .build()?;
+    let mut solutions: Vec<u64> = vec![0; 4 * config.result_buffer_size];
+    let solutions_buffer = Buffer::builder()
+        .queue(ocl_pq.queue().clone())
+        .flags(MemFlags::new().read_write())
+        .len(4 * config.result_buffer_size)
+        .copy_host_slice(&solutions)
+        .build()?;
 
-            // read the solutions from the device
-            solutions_buffer.read(&mut solutions).enq()?;
+    let batches = (nonce_range.end - nonce_range.start).div_ceil(config.work_size as u64);
+    let batch_nonces = |batch: u64| {
+        let start = nonce_range.start + batch * config.work_size as u64;
+        start
+            ..start
+                .saturating_add(config.work_size as u64)
+                .min(nonce_range.end)
+    };
+    let share: u64 = config
+        .device_share
+        .batches(batches)
+        .map(|batch| {
+            let nonces = batch_nonces(batch);
+            nonces.end - nonces.start
+        })
+        .sum();
+    let mut total_processed = 0;
+    let mut solution_count_acc = 0;
+    let mut solution_count_rej = 0;
 
-            // record the end time of the work and compute how long the work took
-            now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-            work_duration_millis = (now.as_secs() * 1000 + now.subsec_nanos() as u64 / 1000000)
-                - work_start_time_millis;
+    for batch in config.device_share.batches(batches) {
+        tui::wait_while_paused();
+        let batch_started = Instant::now();
+        let nonce_buffer = Buffer::builder()
+            .queue(ocl_pq.queue().clone())
+            .flags(MemFlags::new().read_only())
+            .len(1)
+            .copy_host_slice(&[batch])
+            .build()?;
 
-            // if at least one solution is found, end the loop
+        let kern = ocl_pq
+            .kernel_builder("hashMessage")
+            .arg_named("message", Some(&message_buffer))
+            .arg_named("nonce", Some(&nonce_buffer))
+            .arg_named("solutions", Some(&solutions_buffer))
+            .build()?;
+        unsafe { kern.enq()? };
+
+        solutions_buffer.read(&mut solutions).enq()?;
+
+        let Range { start, end } = batch_nonces(batch);
+        total_processed += end - start;
+        match tui::active() {
+            Some(dashboard) => dashboard.batch(
+                config.gpu_device,
+                end - start,
+                batch_started.elapsed(),
+                format!("nonce {} of {}", end - 1, nonce_range.end - 1),
+            ),
+            None if events::enabled() => events::emit(&Event::progress(
+                Some(config.gpu_device),
+                total_processed,
+                end - start,
+                batch_started.elapsed(),
+                format!("nonce {} of {}", end - 1, nonce_range.end - 1),
+            )),
+            None => println!(
+                "Processed: {} of {} nonces, last nonce {}",
+                total_processed,
+                share,
+                end - 1
+            ),
+        }
 
-            for i in 0..config.result_buffer_size {
-                // get the salt that results from the hash
-                let solution = solutions[i * 4];
-                if solution != 0 {
-                    break 'middle;
-                }
+        // nonce 0 is valid, so a solution is only missing if its address is empty too
+        for solution in solutions.chunks(4) {
+            if solution.iter().all(|&word| word == 0) {
+                continue;
             }
+            let salt = Config::nonce_salt(solution[0]);
+            if record_solution(config, salt, solution_address(solution), total_processed) {
+                solution_count_acc += 1;
+            } else {
+                solution_count_rej += 1;
+            }
+        }
 
-            // if no solution has yet been found, increment the nonce
-            nonce[0] += 1;
-
-            // update the nonce buffer with the incremented nonce value
-            nonce_buffer = Buffer::builder()
-                .queue(ocl_pq.queue().clone())
-                .flags(MemFlags::new().read_write())
-                .len(1)
-                .copy_host_slice(&nonce)
-                .build()?;
-        }
-        let mut solution_count_rej = 0;
-        let mut solution_count_acc = 0;
-
-        let res_buffer_size = config.result_buffer_size;
-        let salt_variant = config.salt_variant;
-        //spawn new thread
-        std::thread::spawn(move || {
-            for i in 0..res_buffer_size {
-                // get the salt that results from the hash
-                let solution = solutions[i * 4];
-                if solution == 0 {
-                    continue;
-                }
-                let solution = solution.to_le_bytes();
-
-                let mined_salt = chain!(salt, solution[..7].iter().copied());
-
-                let salt: Vec<u8> = match salt_variant {
-                    SaltVariant::CrosschainSender {
-                        chain_id: _,
-                        calling_address,
-                    } => chain!(calling_address, [1u8], mined_salt).collect(),
-                    SaltVariant::Crosschain { chain_id: _ } => {
-                        chain!([0u8; 20], [1u8], mined_salt).collect()
-                    }
-                    SaltVariant::Sender { calling_address } => {
-                        chain!(calling_address, [0u8], mined_salt).collect()
-                    }
-                    SaltVariant::Random => chain!(mined_salt, [0u8; 21]).collect(),
-                };
+        if solutions.iter().any(|&word| word != 0) {
+            solutions.fill(0);
+            solutions_buffer.write(&solutions).enq()?;
+        }
+    }
+
+    log::info!(
+        "Scanned {} nonces: {} solutions accepted, {} rejected",
+        total_processed,
+        solution_count_acc,
+        solution_count_rej
+    );
+    Ok(())
+}
 
-                // get the address that results from the hash
-                let address = solutions[i * 4 + 1]
-                    .to_be_bytes()
-                    .into_iter()
-                    .chain(solutions[i * 4 + 2].to_be_bytes())
-                    .chain(solutions[i * 4 + 3].to_be_bytes()[..4].to_vec())
-                    .collect::<Vec<u8>>();
+/// Reads the address the kernel passes back in the last three words of a solution.
+fn solution_address(solution: &[u64]) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address[..8].copy_from_slice(&solution[1].to_be_bytes());
+    address[8..16].copy_from_slice(&solution[2].to_be_bytes());
+    address[16..].copy_from_slice(&solution[3].to_be_bytes()[..4]);
+    address
+}
 
-                let score = score_fancy(web3::types::Address::from_slice(address.as_slice()));
+/// Checks a solution found by the kernel and, if it scores high enough, appends it to
+/// `addr_<address>.csv`. Returns whether the solution was accepted.
+fn record_solution(
+    config: &Config,
+    salt: [u8; 32],
+    address: [u8; 20],
+    total_processed: u64,
+) -> bool {
+    if !verify_solution(config, &salt, &address) {
+        return false;
+    }
 
-                if score.total_score < get_min_difficulty() {
-                    solution_count_rej += 1;
-                } else {
-                    solution_count_acc += 1;
-                    log::info!(
-                        "Found accepted solution: address: {}, score: {}, category: {}",
-                        score.address_mixed_case,
-                        score.total_score,
-                        score.category
-                    );
-                    //get cargo version
-                    let version = env!("CARGO_PKG_VERSION");
-                    let output = format!(
-                        "0x{},0x{},0x{},{}_{}",
-                        hex::encode(salt),
-                        hex::encode(&address),
-                        hex::encode(config.factory_address),
-                        version,
-                        total_processed / 1000000000
-                    );
-
-                    number_found += 1;
-                    println!("{}", output);
-                    let mut file = OpenOptions::new()
-                        .write(true)
-                        .append(true)
-                        .create(true)
-                        .open(format!("output/addr_{}.csv", hex::encode(&address)))
-                        .unwrap();
-
-                    // Write lines to the file
-                    writeln!(file, "{}", output).unwrap();
-                }
-            }
+    let score = config.score(&address);
+    let Some(acceptance) = config.acceptance(&address, &score) else {
+        return false;
+    };
+    log::info!(
+        "Found accepted solution: address: {}, score: {}, category: {}",
+        score.address_mixed_case,
+        score.total_score,
+        score.category
+    );
+    log_gas_savings(config, &address);
+    record_accepted(config, salt, address, total_processed, acceptance);
+    true
+}
 
-            log::info!(
-                "Found {} solutions, {} accepted, {} rejected",
-                number_found,
-                solution_count_acc,
-                solution_count_rej
-            );
-        });
+/// Reports the gas an accepted address saves, under gas criteria.
+fn log_gas_savings(config: &Config, address: &[u8; 20]) {
+    if let RewardVariant::Gas { references, .. } = config.reward {
+        let gas = GasScore::new(address);
+        log::info!(
+            "Saves {}, {} gas over {} references",
+            gas,
+            gas.saved(references),
+            references
+        );
+    }
+}
+
+/// Checks that the factory deploys to the address the kernel found for the salt, guarding
+/// against the kernel and the factory deriving addresses differently.
+fn verify_solution(config: &Config, salt: &[u8; 32], address: &[u8; 20]) -> bool {
+    let expected = config.derive_address(salt);
+    if expected != *address {
+        log::error!(
+            "Discarding solution 0x{}: the kernel found 0x{}, but the {} factory deploys to 0x{}",
+            hex::encode(salt),
+            hex::encode(address),
+            config.factory_kind,
+            hex::encode(expected)
+        );
+        return false;
+    }
+    true
+}
+
+/// Appends an accepted solution to `addr_<address>.csv`, along with the rule it was accepted by.
+fn record_accepted(
+    config: &Config,
+    salt: [u8; 32],
+    address: [u8; 20],
+    total_processed: u64,
+    acceptance: String,
+) {
+    //get cargo version
+    let version = env!("CARGO_PKG_VERSION");
+    let (factory, init_code_hash, init_code_source) = match config.create_variant {
+        CreateXVariant::Create2 { init_code_hash } => (
+            config.factory_address,
+            Some(init_code_hash),
+            config.init_code_source.as_deref().unwrap_or("hash"),
+        ),
+        CreateXVariant::Create3 => (config.factory_address, None, "hash"),
+        CreateXVariant::Create { deployer, .. } => (deployer, None, "nonce"),
+    };
+    let output = ResultRecord {
+        salt,
+        address,
+        factory,
+        miner: format!("{}_{}", version, total_processed / 1000000000),
+        job_id: config.job_id.clone().unwrap_or_default(),
+        init_code_hash,
+        init_code_source: init_code_source.to_string(),
+        acceptance,
+    };
+
+    match tui::active() {
+        Some(dashboard) => dashboard.accepted(&config.score(&address)),
+        None if events::enabled() => events::emit(&Event::solution(
+            Some(config.gpu_device),
+            &output,
+            &config.score(&address),
+        )),
+        None => println!("{}", output),
+    }
+    append_record(config, &output);
+}
+
+/// Appends a record to `addr_<address>.csv` in the output directory, keeps it in the store file
+/// if it ranks among the top results and stores it in the results database.
+fn append_record(config: &Config, record: &ResultRecord) {
+    if let Some(count) = config.keep_top {
+        results::retain(config, record, count);
+    }
+    if let Some(database) = &config.database {
+        db::record(config, database, record);
+    }
+    let path = format!(
+        "{}/addr_{}.csv",
+        config.output_dir,
+        hex::encode(record.address)
+    );
+    let written = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", record));
+    if let Err(e) = written {
+        log::error!("Failed to write solution to {}: {}", path, e);
     }
 }
 
@@ -461,27 +1514,47 @@ pub fn gpu(config: Config) -> ocl::Result<()> {
 pub fn mk_kernel_src(config: &Config) -> String {
     let mut src = String::with_capacity(2048 + KERNEL_SRC.len());
 
-    let (caller, chain_id) = match config.salt_variant {
-        SaltVariant::CrosschainSender {
-            chain_id,
-            calling_address,
-        } => {
-            writeln!(src, "#define GENERATE_SEED() SENDER_XCHAIN()").unwrap();
-            (calling_address, Some(chain_id))
-        }
-        SaltVariant::Crosschain { chain_id } => {
-            writeln!(src, "#define GENERATE_SEED() XCHAIN()").unwrap();
-            ([0u8; 20], Some(chain_id))
-        }
-        SaltVariant::Sender { calling_address } => {
-            writeln!(src, "#define GENERATE_SEED() SENDER()").unwrap();
-            (calling_address, None)
-        }
-        SaltVariant::Random => {
-            writeln!(src, "#define GENERATE_SEED() RANDOM()").unwrap();
-            ([0u8; 20], None)
+    let (salt_template, mined_offset) = config.salt_template();
+    src.push_str(&config.salt_layout.opencl(mined_offset));
+    writeln!(
+        src,
+        "__constant uchar SALT_TEMPLATE[32] = {{{}}};",
+        salt_template.map(|x| format!("{x}u")).join(", ")
+    )
+    .unwrap();
+
+    match config.guard_preimage() {
+        Some((guard_template, salt_offset)) => {
+            writeln!(src, "#define GUARD_LEN {}", guard_template.len()).unwrap();
+            writeln!(src, "#define GUARD_SALT_OFFSET {salt_offset}").unwrap();
+            writeln!(
+                src,
+                "__constant uchar GUARD_TEMPLATE[{}] = {{{}}};",
+                guard_template.len(),
+                guard_template
+                    .iter()
+                    .map(|x| format!("{x}u"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .unwrap();
+            // preimages longer than a keccak block are absorbed block by block
+            match guard_template.len() < 136 {
+                true => writeln!(src, "#define GUARD_SALT() RUN_GUARD_SALT()").unwrap(),
+                false => {
+                    writeln!(
+                        src,
+                        "#define GUARD_BLOCKS {}",
+                        guard_template.len() / 136 + 1
+                    )
+                    .unwrap();
+                    writeln!(src, "#define GUARD_SALT() RUN_GUARD_SALT_BLOCKS()").unwrap();
+                }
+            }
         }
-    };
+        None => writeln!(src, "#define GUARD_SALT()").unwrap(),
+    }
+
     writeln!(
         src,
         "#define RESULT_BUFFER_SIZE {}",
@@ -489,14 +1562,23 @@ pub fn mk_kernel_src(config: &Config) -> String {
     )
     .unwrap();
 
+    let best_metric = config.best.as_ref().and_then(BestMetric::opencl);
     match &config.reward {
+        // in best-so-far mode, the counted metrics replace the reward criteria
+        _ if best_metric.is_some() => {
+            writeln!(src, "#define BEST_METRIC(d) {}", best_metric.unwrap()).unwrap();
+            writeln!(
+                src,
+                "#define SUCCESS_CONDITION() improvesBest(digest, best)"
+            )
+            .unwrap();
+        }
         RewardVariant::LeadingAny {
             group,
             leading,
             ones,
             ints,
         } => {
-            writeln!(src, "#define LEADING_ZEROES 0").unwrap();
             writeln!(src, "#define LEADING {leading}").unwrap();
             writeln!(src, "#define GROUP {group}").unwrap();
             writeln!(src, "#define ONES {ones}").unwrap();
@@ -505,11 +1587,11 @@ pub fn mk_kernel_src(config: &Config) -> String {
             writeln!(src, "#define SUCCESS_CONDITION() hasLeadingAny(digest)").unwrap();
         }
         RewardVariant::LeadingZeros { zeros_threshold } => {
-            writeln!(src, "#define LEADING_ZEROES {zeros_threshold}").unwrap();
+            let condition = nibble_condition(zeros_threshold * 2, false, Some(0));
+            writeln!(src, "#define hasLeading(d) ({condition})").unwrap();
             writeln!(src, "#define SUCCESS_CONDITION() hasLeading(digest)").unwrap();
         }
         RewardVariant::TotalZeros { zeros_threshold } => {
-            writeln!(src, "#define LEADING_ZEROES 0").unwrap();
             writeln!(src, "#define TOTAL_ZEROES {zeros_threshold}").unwrap();
             writeln!(src, "#define SUCCESS_CONDITION() hasTotal(digest)").unwrap();
         }
@@ -517,7 +1599,8 @@ pub fn mk_kernel_src(config: &Config) -> String {
             leading_zeros_threshold,
             total_zeros_threshold,
         } => {
-            writeln!(src, "#define LEADING_ZEROES {leading_zeros_threshold}").unwrap();
+            let condition = nibble_condition(leading_zeros_threshold * 2, false, Some(0));
+            writeln!(src, "#define hasLeading(d) ({condition})").unwrap();
             writeln!(src, "#define TOTAL_ZEROES {total_zeros_threshold}").unwrap();
             writeln!(
                 src,
@@ -529,7 +1612,8 @@ pub fn mk_kernel_src(config: &Config) -> String {
             leading_zeros_threshold,
             total_zeros_threshold,
         } => {
-            writeln!(src, "#define LEADING_ZEROES {leading_zeros_threshold}").unwrap();
+            let condition = nibble_condition(leading_zeros_threshold * 2, false, Some(0));
+            writeln!(src, "#define hasLeading(d) ({condition})").unwrap();
             writeln!(src, "#define TOTAL_ZEROES {total_zeros_threshold}").unwrap();
             writeln!(
                 src,
@@ -537,43 +1621,114 @@ pub fn mk_kernel_src(config: &Config) -> String {
             )
             .unwrap();
         }
+        RewardVariant::LeadingNibbles { count, nibble } => {
+            let condition = nibble_condition(*count, false, *nibble);
+            writeln!(src, "#define hasNibbles(d) ({condition})").unwrap();
+            writeln!(src, "#define SUCCESS_CONDITION() hasNibbles(digest)").unwrap();
+        }
+        RewardVariant::TrailingNibbles { count, nibble } => {
+            let condition = nibble_condition(*count, true, *nibble);
+            writeln!(src, "#define hasNibbles(d) ({condition})").unwrap();
+            writeln!(src, "#define SUCCESS_CONDITION() hasNibbles(digest)").unwrap();
+        }
+        RewardVariant::Gas {
+            min_gas,
+            references,
+        } => {
+            let min_gas = gas::min_gas_per_reference(*min_gas, *references)
+                .expect("gas threshold is validated by the config");
+            writeln!(src, "#define MIN_GAS {min_gas}").unwrap();
+            writeln!(
+                src,
+                "#define SUCCESS_CONDITION() (gasSaved(digest) >= MIN_GAS)"
+            )
+            .unwrap();
+        }
         RewardVariant::Matching { pattern } => {
-            writeln!(src, "#define LEADING_ZEROES 0").unwrap();
             writeln!(src, "#define PATTERN() \"{pattern}\"").unwrap();
             writeln!(src, "#define SUCCESS_CONDITION() isMatching(digest)").unwrap();
         }
+        RewardVariant::Fancy => {
+            let predicate = FancyPredicate::new(&config.scoring, &config.thresholds);
+            src.push_str(&predicate.opencl());
+            writeln!(src, "#define SUCCESS_CONDITION() isFancy(digest)").unwrap();
+        }
     };
 
-    let init_code_hash = match config.create_variant {
-        CreateXVariant::Create2 { init_code_hash } => {
-            writeln!(src, "#define CREATE3()").unwrap();
-            init_code_hash
-        }
+    match config.create_variant {
+        CreateXVariant::Create2 { .. } => writeln!(src, "#define CREATE3()").unwrap(),
         CreateXVariant::Create3 => {
+            src.push_str(&midstate::create3().opencl("CREATE3_MIDSTATE"));
             writeln!(src, "#define CREATE3() RUN_CREATE3()").unwrap();
-            PROXY_CHILD_CODEHASH
+        }
+        CreateXVariant::Create {
+            deployer,
+            ref nonce_range,
+        } => {
+            writeln!(src, "#define CREATE_NONCE_START {}ul", nonce_range.start).unwrap();
+            writeln!(src, "#define CREATE_NONCE_END {}ul", nonce_range.end).unwrap();
+            writeln!(src, "#define CREATE_BATCH_SIZE {}ul", config.work_size).unwrap();
+            writeln!(
+                src,
+                "__constant uchar CREATE_DEPLOYER[20] = {{{}}};",
+                deployer.map(|x| format!("{x}u")).join(", ")
+            )
+            .unwrap();
+            src.push_str(KERNEL_SRC);
+            return src;
         }
     };
 
-    let caller = caller.iter();
-    let chain_id = chain_id
-        .iter()
-        .flatten()
-        .enumerate()
-        .map(|(i, x)| (i + 20, x));
-    caller.enumerate().chain(chain_id).for_each(|(i, x)| {
-        writeln!(src, "#define S1_{} {}u", i + 12, x).unwrap();
-    });
-
-    let factory = config.factory_address.iter();
-    let hash = init_code_hash.iter();
-    let hash = hash.enumerate().map(|(i, x)| (i + 52, x));
-
-    for (i, x) in factory.enumerate().chain(hash) {
-        writeln!(src, "#define S2_{} {}u", i + 1, x).unwrap();
-    }
+    // the kernel starts from the CREATE2 preimage with every byte constant for the launch in
+    // place, and only writes the salt bytes that vary between work items
+    let midstate = midstate::create2(config).expect("CREATE scans have no salt");
+    src.push_str(&midstate.opencl("MIDSTATE"));
+    let salt_offset = midstate::CREATE2_SALT_OFFSET;
+    writeln!(
+        src,
+        "#define SALT_VARYING_START {}",
+        midstate.varying.start - salt_offset
+    )
+    .unwrap();
+    writeln!(
+        src,
+        "#define SALT_VARYING_END {}",
+        midstate.varying.end - salt_offset
+    )
+    .unwrap();
 
     src.push_str(KERNEL_SRC);
 
     src
 }
+
+/// Condition checking that the first `count` nibbles of a digest `d`, or the last ones if
+/// `trailing`, all equal `nibble`, or the nibble at that end if `None`. The nibbles are masked
+/// out of the five words of the digest, so the check takes at most five comparisons.
+fn nibble_condition(count: u8, trailing: bool, nibble: Option<u8>) -> String {
+    let mut mask = [0u8; 20];
+    for i in 0..count as usize {
+        let position = if trailing { 39 - i } else { i };
+        mask[position / 2] |= if position % 2 == 0 { 0xf0 } else { 0x0f };
+    }
+    let repeated = match (nibble, trailing) {
+        (Some(nibble), _) => format!("0x{:08x}u", nibble as u32 * 0x11111111),
+        (None, false) => "(((d)[0] >> 4) * 0x11111111u)".to_string(),
+        (None, true) => "(((d)[19] & 0xfu) * 0x11111111u)".to_string(),
+    };
+    let words: Vec<String> = mask
+        .chunks(4)
+        .enumerate()
+        .filter_map(|(i, word)| {
+            let word = u32::from_le_bytes(word.try_into().unwrap());
+            match (word, nibble) {
+                (0, _) => None,
+                (_, Some(0)) => Some(format!("!(((uint *)(d))[{i}] & 0x{word:08x}u)")),
+                _ => Some(format!(
+                    "!((((uint *)(d))[{i}] ^ {repeated}) & 0x{word:08x}u)"
+                )),
+            }
+        })
+        .collect();
+    words.join(" && ")
+}
@@ -1,9 +1,34 @@
+use alloy_primitives::{hex, Address};
 use clap::Parser;
 use createxcrunch::{
-    cli::{Cli, Commands},
-    gpu, Config, RewardVariant,
+    best::SharedBest,
+    cli::{
+        Cli, CliArgs, CombineArgs, Commands, DbCommands, EoaArgs, ExportArgs, PriceArgs,
+        ResultsArgs, ResultsCommands, ScoreArgs,
+    },
+    db::{self, Query, ResultsDb},
+    eoa,
+    events::{self, Event, ProgressFormat},
+    export::{parse_amount, CreateXCall, Target},
+    fancy::FancyPredicate,
+    gpu,
+    hexspeak::DictionarySpec,
+    init_code::ConstructorArgs,
+    job::{CreateKind, Job},
+    parse_address, parse_nibble,
+    preimage::PreimageSpec,
+    price::{self, parse_hashrate, Pricing, ReportFormat},
+    record::ResultRecord,
+    results::{self, ResultStore, STORE_FILE},
+    safe::SafeSetup,
+    score::{
+        parse_category_minimum, parse_difficulty, FancyScore, ScoringSpec, ThresholdSpec, TopScores,
+    },
+    tui::{self, Dashboard},
+    Config, CreateXVariant, CrunchError, RewardVariant,
 };
-use std::env;
+use secp256k1::Secp256k1;
+use std::{env, io, path::Path, process, sync::mpsc, thread};
 
 fn main() {
     let cli = Cli::parse();
@@ -13,73 +38,753 @@ fn main() {
         env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
     );
 
-    env_logger::init();
+    // the dashboard shows log lines in its own pane
+    let tui = cli.command.cli_args().is_some_and(|args| args.tui);
+    let terminal = console::Term::stdout().is_term();
+    let mut logger = env_logger::Builder::from_default_env();
+    if tui && terminal {
+        logger.target(env_logger::Target::Pipe(Box::new(tui::LogPane)));
+    }
+    logger.init();
+    if tui && !terminal {
+        log::warn!("Standard output is not a terminal, logging in place of the dashboard");
+    }
 
+    if let Some(args) = cli.command.cli_args() {
+        if args.progress_format == ProgressFormat::Json {
+            events::install(args.progress_stream);
+        }
+    }
+
+    let result = run(cli, tui && terminal);
+    tui::stop();
+    events::emit(&Event::stopped(&result));
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(e.exit_code());
+    }
+}
+
+fn run(cli: Cli, tui: bool) -> Result<(), CrunchError> {
     match cli.command {
         Commands::Create2(args) => {
-            let gpu_device_id = args.cli_args.gpu_device_id;
-            let factory = args.cli_args.factory;
-            let caller = args.cli_args.caller;
-            let chain_id = args.cli_args.chain_id;
-            let init_code_hash = args.init_code_hash;
-            let reward = RewardVariant::LeadingAny {
-                group: args.cli_args.group,
-                leading: args.cli_args.leading,
-                ones: args.cli_args.ones,
-                ints: args.cli_args.ints,
-            };
-            let output = args.cli_args.output;
-
-            match Config::new(
-                gpu_device_id,
-                args.cli_args.work_size,
-                args.cli_args.result_buffer_size,
-                args.cli_args.sleep_for,
-                &factory,
-                caller.as_deref(),
-                chain_id,
-                Some(&init_code_hash),
-                reward,
-                &output,
-            ) {
-                Ok(config) => match gpu(config) {
-                    Ok(_) => (),
-                    Err(e) => panic!("{}", e),
-                },
-                Err(e) => panic!("{}", e),
+            let mut job = job_from_args(args.cli_args)?;
+            job.create = Some(CreateKind::Create2);
+            let constructor_args = args.constructor_args.map(|mut args| ConstructorArgs {
+                signature: args.remove(0),
+                values: args,
+            });
+            let salt_preimage = match args.account.is_some() || args.salt_preimage.is_some() {
+                true => Some(PreimageSpec {
+                    account: args.account,
+                    owners: args.owners,
+                    data: args.account_data,
+                    template: args.salt_preimage,
+                    offset: args.salt_offset,
+                }),
+                false => None,
             };
+            let job = job.merge(Job {
+                salt_preimage,
+                init_code_hash: args.init_code_hash,
+                init_code: args.init_code,
+                init_code_file: args.init_code_file,
+                artifact: args.artifact,
+                constructor_args,
+                ..Default::default()
+            });
+            mine(job, tui)
         }
         Commands::Create3(args) => {
-            let gpu_device_id = args.gpu_device_id;
-            let factory = args.factory;
-            let caller = args.caller;
-            let chain_id = args.chain_id;
-            let reward = RewardVariant::LeadingAny {
-                group: args.group,
-                leading: args.leading,
-                ones: args.ones,
-                ints: args.ints,
+            let mut job = job_from_args(args)?;
+            job.create = Some(CreateKind::Create3);
+            job.init_code_hash = None;
+            job.init_code = None;
+            job.init_code_file = None;
+            job.artifact = None;
+            job.constructor_args = None;
+            mine(job, tui)
+        }
+        Commands::Create(args) => {
+            let mut job = job_from_args(args.cli_args)?;
+            job.create = Some(CreateKind::Create);
+            let job = job.merge(Job {
+                deployer: args.deployer,
+                nonces: args.nonces,
+                ..Default::default()
+            });
+            mine(job, tui)
+        }
+        Commands::Safe(args) => {
+            let mut job = job_from_args(args.cli_args)?;
+            job.create = Some(CreateKind::Create2);
+            job.init_code_hash = None;
+            job.init_code = None;
+            job.init_code_file = None;
+            job.artifact = None;
+            job.constructor_args = None;
+            let job = job.merge(Job {
+                safe: Some(SafeSetup {
+                    singleton: args.singleton,
+                    proxy_code: args.proxy_code,
+                    initializer: args.initializer,
+                    owners: args.owners,
+                    threshold: args.threshold,
+                    fallback_handler: args.fallback_handler,
+                }),
+                ..Default::default()
+            });
+            mine(job, tui)
+        }
+        Commands::Export(args) => export(args),
+        Commands::Eoa(args) => mine_eoa(args),
+        Commands::Combine(args) => combine(args),
+        Commands::Price(args) => price(args),
+        Commands::Score(args) => score(args),
+        Commands::Results(command) => manage_results(command),
+        Commands::Db(command) => query_db(command),
+    }
+}
+
+/// The value of an environment variable that a command line option replaced, read for one more
+/// release when the option is not given.
+fn deprecated_env(key: &str, option: &str) -> Option<String> {
+    let value = std::env::var(key).ok()?;
+    log::warn!("{key} is deprecated and will be ignored in the next release, use {option} instead");
+    Some(value)
+}
+
+/// Loads the job file, if any, and overrides its values with the flags given on the command line.
+fn job_from_args(args: CliArgs) -> Result<Job, CrunchError> {
+    let job = match &args.job {
+        Some(path) => Job::load(path)?,
+        None => Job::default(),
+    };
+
+    let nibble = match &args.nibble {
+        Some(nibble) => parse_nibble(nibble)?,
+        None => Some(0),
+    };
+    let reward = match (args.pattern, args.zeros, args.total) {
+        (Some(pattern), _, _) => Some(RewardVariant::Matching { pattern }),
+        _ if args.leading_nibbles.is_some() => Some(RewardVariant::LeadingNibbles {
+            count: args.leading_nibbles.unwrap(),
+            nibble,
+        }),
+        _ if args.trailing_nibbles.is_some() => Some(RewardVariant::TrailingNibbles {
+            count: args.trailing_nibbles.unwrap(),
+            nibble,
+        }),
+        _ if args.fancy => Some(RewardVariant::Fancy),
+        _ if args.gas.is_some() => Some(RewardVariant::Gas {
+            min_gas: args.gas.unwrap(),
+            references: args.gas_references.unwrap_or(1),
+        }),
+        (None, Some(leading_zeros_threshold), Some(total_zeros_threshold)) if args.either => {
+            Some(RewardVariant::LeadingOrTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            })
+        }
+        (None, Some(leading_zeros_threshold), Some(total_zeros_threshold)) => {
+            Some(RewardVariant::LeadingAndTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            })
+        }
+        (None, Some(zeros_threshold), None) => {
+            Some(RewardVariant::LeadingZeros { zeros_threshold })
+        }
+        (None, None, Some(zeros_threshold)) => Some(RewardVariant::TotalZeros { zeros_threshold }),
+        (None, None, None)
+            if args.group.is_some()
+                || args.leading.is_some()
+                || args.ones.is_some()
+                || args.ints.is_some() =>
+        {
+            Some(RewardVariant::LeadingAny {
+                group: args.group.unwrap_or(24),
+                leading: args.leading.unwrap_or(7),
+                ones: args.ones.unwrap_or(9),
+                ints: args.ints.unwrap_or(4),
+            })
+        }
+        (None, None, None) => None,
+    };
+
+    // a word list replaces the dictionary of the job, keeping the rest of its scoring
+    let scoring = args.dictionary.map(|file| ScoringSpec {
+        dictionary: Some(DictionarySpec {
+            file: Some(file),
+            ..Default::default()
+        }),
+        ..job.scoring.clone().unwrap_or_default()
+    });
+
+    let thresholds = ThresholdSpec {
+        min_difficulty: args
+            .min_difficulty
+            .or_else(|| deprecated_env("MIN_DIFFICULTY", "--min-difficulty"))
+            .as_deref()
+            .map(parse_difficulty)
+            .transpose()?,
+        base_difficulty: args
+            .base_difficulty
+            .or_else(|| deprecated_env("BASE_DIFFICULTY", "--base-difficulty"))
+            .as_deref()
+            .map(parse_difficulty)
+            .transpose()?,
+        categories: args
+            .category_min
+            .iter()
+            .map(|value| parse_category_minimum(value))
+            .collect::<Result<_, _>>()?,
+    };
+
+    Ok(job.merge(Job {
+        factory: args.factory,
+        factory_kind: args.factory_kind,
+        caller: args.caller,
+        chain_id: args.chain_id,
+        reward,
+        scoring,
+        thresholds: (thresholds != ThresholdSpec::default()).then_some(thresholds),
+        salt_layout: args.salt_layout,
+        best: args.best,
+        keep_top: args.keep_top,
+        database: args.db,
+        devices: args.gpu_device_id,
+        work_size: args.work_size,
+        result_buffer_size: args.result_buffer_size,
+        sleep_for: args.sleep_for,
+        output: args.output,
+        output_dir: args.output_dir,
+        ..Default::default()
+    }))
+}
+
+/// Validates the job, records it in the output directory and mines on each of its devices,
+/// showing the dashboard if `tui` is set.
+fn mine(job: Job, tui: bool) -> Result<(), CrunchError> {
+    let mut configs = job.configs()?;
+    // the devices of the job record only the addresses beating the best of every device
+    let shared_best = SharedBest::default();
+    for config in &mut configs {
+        config.shared_best = shared_best.clone();
+    }
+    let path = job.save(&configs[0].output_dir)?;
+    log::info!("Running job {} (saved to {})", job.id(), path.display());
+    match &configs[0].create_variant {
+        CreateXVariant::Create2 { init_code_hash } => log::info!(
+            "Init code hash: {} (from {})",
+            hex::encode_prefixed(init_code_hash),
+            configs[0].init_code_source.as_deref().unwrap_or("hash")
+        ),
+        CreateXVariant::Create {
+            deployer,
+            nonce_range,
+        } => log::info!(
+            "Deployer: {}, nonces {}..{}",
+            hex::encode_prefixed(deployer),
+            nonce_range.start,
+            nonce_range.end
+        ),
+        CreateXVariant::Create3 => {}
+    }
+    if job.scoring.is_some() {
+        log::info!(
+            "Scoring categories: {}",
+            configs[0].scoring.names().collect::<Vec<_>>().join(", ")
+        );
+    }
+    log::info!("Thresholds: {}", configs[0].thresholds);
+    if let Some(database) = &configs[0].database {
+        ResultsDb::open(database)?.insert_job(&job)?;
+        log::info!("Storing results in {}", database);
+    }
+    if let Some(keep_top) = configs[0].keep_top {
+        log::info!(
+            "Keeping the top {} results of each category in {}/{}",
+            keep_top,
+            configs[0].output_dir,
+            STORE_FILE
+        );
+    }
+    match configs[0].best {
+        Some(metric) => log::info!("Recording every address improving the best {metric}"),
+        None => {
+            if let Some(difficulty) = configs[0].reward.difficulty() {
+                log::info!("Expected addresses per match: {difficulty:.4e}");
+            }
+        }
+    }
+    if configs[0].reward == RewardVariant::Fancy {
+        let predicate = FancyPredicate::new(&configs[0].scoring, &configs[0].thresholds);
+        if !predicate.cpu_only().is_empty() {
+            log::warn!(
+                "Not checked on the GPU, only scored on the addresses found for other categories: {}",
+                predicate.cpu_only().join(", ")
+            );
+        }
+    }
+    if let Some(safe) = &job.safe {
+        log::info!(
+            "Safe initializer: {}",
+            hex::encode_prefixed(safe.initializer()?)
+        );
+    }
+
+    let expected = match (configs[0].best, &configs[0].reward) {
+        (Some(_), _) => None,
+        // fancy addresses are found at least as often as in the easiest category
+        (None, RewardVariant::Fancy) => {
+            let thresholds = &configs[0].thresholds;
+            Some(
+                thresholds
+                    .category_minimums
+                    .values()
+                    .fold(thresholds.min_difficulty, |a, b| a.min(*b)),
+            )
+        }
+        (None, reward) => reward.difficulty(),
+    };
+    if tui {
+        tui::start(Dashboard::new(job.id(), expected, configs[0].sleep_for));
+    }
+    events::emit(&Event::Started {
+        job_id: job.id(),
+        devices: configs.iter().map(|config| config.gpu_device).collect(),
+        output_dir: configs[0].output_dir.clone(),
+        expected_difficulty: expected,
+    });
+
+    if configs.len() == 1 {
+        return mine_device(configs.remove(0));
+    }
+
+    // devices sharing a finite keyspace finish their share on their own, the job ends once all
+    // of them did or at the first error
+    let devices = configs.len();
+    let (tx, rx) = mpsc::channel();
+    for config in configs {
+        let tx = tx.clone();
+        thread::spawn(move || tx.send(mine_device(config)));
+    }
+    for _ in 0..devices {
+        rx.recv().expect("mining threads exited without a result")?;
+    }
+    Ok(())
+}
+
+/// Mines on the device of the config, reporting the error it stops with.
+fn mine_device(config: Config) -> Result<(), CrunchError> {
+    let device = config.gpu_device;
+    gpu(config).inspect_err(|e| events::emit(&Event::device_error(device, e)))
+}
+
+/// Searches offsets to the public key on the CPU, using the reward and output settings of the job.
+fn mine_eoa(args: EoaArgs) -> Result<(), CrunchError> {
+    let public_key = eoa::parse_public_key(&args.public_key)?;
+    if args.cli_args.tui {
+        log::warn!("The dashboard shows GPU mining only, logging instead");
+    }
+    let job = job_from_args(args.cli_args)?;
+    let config = job.builder()?.build()?;
+    if config.best.is_some() {
+        return Err(CrunchError::InvalidJob(
+            "best-so-far mode runs on the GPU only".to_string(),
+        ));
+    }
+    let path = job.save(&config.output_dir)?;
+    log::info!("Running job {} (saved to {})", job.id(), path.display());
+    if let Some(database) = &config.database {
+        ResultsDb::open(database)?.insert_job(&job)?;
+    }
+    log::info!("Thresholds: {}", config.thresholds);
+    if let Some(difficulty) = config.reward.difficulty() {
+        log::info!("Expected addresses per match: {difficulty:.4e}");
+    }
+    events::emit(&Event::Started {
+        job_id: job.id(),
+        devices: Vec::new(),
+        output_dir: config.output_dir.clone(),
+        expected_difficulty: config.reward.difficulty(),
+    });
+    eoa::mine(&public_key, &config)
+}
+
+/// Reconstructs the private key of an address found by the eoa subcommand.
+fn combine(args: CombineArgs) -> Result<(), CrunchError> {
+    let record = match Path::new(&args.record).is_file() {
+        true => ResultRecord::load(&args.record)?,
+        false => args.record.parse()?,
+    };
+    if record.init_code_source != "eoa" {
+        return Err(CrunchError::InvalidRecord(format!(
+            "{} is a contract address, not an EOA mined by the eoa subcommand",
+            hex::encode_prefixed(record.address)
+        )));
+    }
+
+    let secret_key = eoa::combine(&eoa::parse_secret_key(&args.private_key)?, &record.salt)?;
+    let address = eoa::address(&secret_key.public_key(&Secp256k1::signing_only()));
+    if address != record.address {
+        return Err(CrunchError::InvalidKey(format!(
+            "the combined key controls {}, not {}; was the offset mined for another public key?",
+            hex::encode_prefixed(address),
+            hex::encode_prefixed(record.address)
+        )));
+    }
+    println!("Address: {}", Address::from(address).to_checksum(None));
+    println!(
+        "Private key: {}",
+        hex::encode_prefixed(secret_key.secret_bytes())
+    );
+    Ok(())
+}
+
+/// Prints the CreateX call deploying a result and writes a Foundry script performing it.
+fn export(args: ExportArgs) -> Result<(), CrunchError> {
+    let record = match Path::new(&args.record).is_file() {
+        true => ResultRecord::load(&args.record)?,
+        false => args.record.parse()?,
+    };
+    if record.init_code_source == "nonce" {
+        return Err(CrunchError::InvalidExport(format!(
+            "{} is deployed with CREATE by {} at nonce {}, not through CreateX's CREATE2 or CREATE3",
+            hex::encode_prefixed(record.address),
+            hex::encode_prefixed(record.factory),
+            u64::from_be_bytes(record.salt[24..].try_into().unwrap())
+        )));
+    }
+
+    let targets = match &args.clone {
+        Some(implementation) => vec![Target::Create2Clone {
+            implementation: parse_address("implementation address", implementation)?,
+        }],
+        None => {
+            let job = Job {
+                init_code: args.init_code,
+                init_code_file: args.init_code_file,
+                artifact: args.artifact,
+                constructor_args: args.constructor_args.map(|mut args| ConstructorArgs {
+                    signature: args.remove(0),
+                    values: args,
+                }),
+                ..Default::default()
+            };
+            let init_code = job.init_code()?.ok_or_else(|| {
+                CrunchError::InvalidExport(
+                    "one of --init-code, --init-code-file, --artifact or --clone is required"
+                        .to_string(),
+                )
+            })?;
+            match (record.init_code_hash, record.init_code_source.is_empty()) {
+                (Some(init_code_hash), _) if init_code_hash != init_code.hash() => {
+                    return Err(CrunchError::InvalidExport(format!(
+                        "the init code hashes to {}, but the salt was mined for {}",
+                        hex::encode_prefixed(init_code.hash()),
+                        hex::encode_prefixed(init_code_hash)
+                    )))
+                }
+                (Some(_), _) => vec![Target::Create2 {
+                    init_code: init_code.bytecode,
+                }],
+                (None, false) => vec![Target::Create3 {
+                    init_code: init_code.bytecode,
+                }],
+                // records written before the init code hash was recorded
+                (None, true) => vec![
+                    Target::Create2 {
+                        init_code: init_code.bytecode.clone(),
+                    },
+                    Target::Create3 {
+                        init_code: init_code.bytecode,
+                    },
+                ],
+            }
+        }
+    };
+
+    let init_data = args
+        .init_data
+        .map(|init_data| {
+            hex::decode(&init_data).map_err(|_| CrunchError::InvalidHex {
+                field: "init data",
+                value: init_data,
+            })
+        })
+        .transpose()?;
+    let constructor_value = args
+        .value
+        .map(|value| parse_amount("value", &value))
+        .transpose()?
+        .unwrap_or_default();
+    let init_value = args
+        .init_value
+        .map(|value| parse_amount("init value", &value))
+        .transpose()?
+        .unwrap_or_default();
+    let refund = args
+        .refund
+        .map(|refund| parse_address("refund address", &refund))
+        .transpose()?;
+
+    let mut resolved = Err(CrunchError::InvalidExport("no deployment".to_string()));
+    for target in targets {
+        let call = CreateXCall {
+            init_data: init_data.clone(),
+            constructor_value,
+            init_value,
+            refund,
+            ..CreateXCall::new(record.salt, target)
+        };
+        resolved = call
+            .resolve_salt_variant(&record.factory, &record.address, args.chain_id)
+            .map(|salt_variant| (call, salt_variant));
+        if resolved.is_ok() {
+            break;
+        }
+    }
+    let (call, salt_variant) = resolved?;
+
+    let script = args
+        .script
+        .unwrap_or_else(|| format!("Deploy_{}.s.sol", hex::encode(record.address)));
+    std::fs::write(
+        &script,
+        call.foundry_script(&record.factory, &record.address, &salt_variant),
+    )?;
+
+    println!("Function: {}", call.signature());
+    println!("Calldata: {}", hex::encode_prefixed(call.calldata()));
+    println!("Value:    {}", call.value());
+    println!("Cast:     {}", call.cast_command(&record.factory));
+    println!("Script:   {}", script);
+    Ok(())
+}
+
+/// Values addresses, given on the command line or found in a results directory.
+fn price(args: PriceArgs) -> Result<(), CrunchError> {
+    let job = match &args.job {
+        Some(path) => Job::load(path)?,
+        None => Job::default(),
+    };
+    let job = job.merge(Job {
+        thresholds: args
+            .base_difficulty
+            .as_deref()
+            .map(|difficulty| -> Result<_, CrunchError> {
+                Ok(ThresholdSpec {
+                    base_difficulty: Some(parse_difficulty(difficulty)?),
+                    ..Default::default()
+                })
+            })
+            .transpose()?,
+        ..Default::default()
+    });
+    let config = job.builder()?.build()?;
+    let pricing = Pricing {
+        hashrate: parse_hashrate(&args.hashrate)?,
+        base_price: args.base_price,
+    };
+    pricing.validate()?;
+
+    let mut addresses = args
+        .addresses
+        .iter()
+        .map(|address| parse_address("address", address))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(results) = &args.results {
+        addresses.extend(price::result_addresses(results)?);
+    }
+    if addresses.is_empty() {
+        return Err(CrunchError::InvalidPricing(
+            "no address to value, pass addresses or --results".to_string(),
+        ));
+    }
+
+    let valuations = pricing.valuations(&config.scoring, &config.thresholds, &addresses);
+    let report = match args.format {
+        ReportFormat::Csv => price::to_csv(&valuations),
+        ReportFormat::Json => price::to_json(&valuations) + "\n",
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, report)?,
+        None => print!("{report}"),
+    }
+    Ok(())
+}
+
+/// Scores addresses given on the command line and read from standard input.
+fn score(args: ScoreArgs) -> Result<(), CrunchError> {
+    let job = match &args.job {
+        Some(path) => Job::load(path)?,
+        None => Job::default(),
+    };
+    let config = job.builder()?.build()?;
+    let print = |score: &FancyScore| match args.json {
+        true => println!("{}", serde_json::to_string(score).unwrap()),
+        false => println!("{score}\n"),
+    };
+
+    let stdin = args
+        .stdin
+        .then(|| io::stdin().lines())
+        .into_iter()
+        .flatten();
+    let inputs = args.addresses.iter().cloned().map(Ok).chain(stdin);
+    let mut top = args.top.map(TopScores::new);
+    for line in inputs {
+        let line = line?;
+        let Some(address) = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .find(|field| !field.is_empty())
+        else {
+            continue;
+        };
+        let score = config.score(&parse_address("address", address)?);
+        match &mut top {
+            Some(top) => top.push(score),
+            None => print(&score),
+        }
+    }
+    for score in top.map(TopScores::into_sorted).unwrap_or_default() {
+        print(&score);
+    }
+    Ok(())
+}
+
+/// Lists, ranks and compacts the results of output directories and results files.
+fn manage_results(command: ResultsCommands) -> Result<(), CrunchError> {
+    match command {
+        ResultsCommands::List(args) => {
+            let store = load_results(&args)?;
+            print!("{}", results::to_csv(&store.ranked()));
+        }
+        ResultsCommands::Top(args) => {
+            let store = load_results(&args.results)?;
+            let top = match args.per_category {
+                true => store.top_per_category(args.count),
+                false => store.top(args.count),
             };
-            let output = args.output;
-
-            match Config::new(
-                gpu_device_id,
-                args.work_size,
-                args.result_buffer_size,
-                args.sleep_for,
-                &factory,
-                caller.as_deref(),
-                chain_id,
-                None,
-                reward,
-                &output,
-            ) {
-                Ok(config) => match gpu(config) {
-                    Ok(_) => (),
-                    Err(e) => panic!("{}", e),
-                },
-                Err(e) => panic!("{}", e),
+            print!("{}", results::to_csv(&top));
+        }
+        ResultsCommands::Prune(args) => {
+            let config = results_config(&args.job, Some(args.keep))?;
+            let mut store = ResultStore::new();
+            store.load(&config, &args.dir)?;
+            let pruned = store.prune(args.keep);
+            let removed = store.compact(&args.dir)?;
+            log::info!(
+                "Kept {} results, pruned {}, compacted {} address files",
+                store.len(),
+                pruned,
+                removed
+            );
+        }
+        ResultsCommands::Merge(args) => {
+            let config = results_config(&args.results.job, args.keep)?;
+            let into = Path::new(&args.into);
+            let mut store = ResultStore::new();
+            if into.is_dir() {
+                store.load(&config, into)?;
+            }
+            for source in &args.results.sources {
+                let added = store.load(&config, source)?;
+                log::info!("Merged {} new results from {}", added, source);
+            }
+            if let Some(keep) = config.keep_top {
+                store.prune(keep);
+            }
+            std::fs::create_dir_all(into)?;
+            store.compact(into)?;
+            log::info!(
+                "Kept {} results in {}",
+                store.len(),
+                into.join(STORE_FILE).display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The config results are ranked with, from a job file if given.
+fn results_config(job: &Option<String>, keep_top: Option<usize>) -> Result<Config, CrunchError> {
+    let job = match job {
+        Some(path) => Job::load(path)?,
+        None => Job::default(),
+    };
+    job.merge(Job {
+        keep_top,
+        ..Default::default()
+    })
+    .builder()?
+    .build()
+}
+
+fn load_results(args: &ResultsArgs) -> Result<ResultStore, CrunchError> {
+    let config = results_config(&args.job, None)?;
+    let mut store = ResultStore::new();
+    for source in &args.sources {
+        store.load(&config, source)?;
+    }
+    Ok(store)
+}
+
+/// Queries and maintains the results database.
+fn query_db(command: DbCommands) -> Result<(), CrunchError> {
+    match command {
+        DbCommands::Query(args) => {
+            let query = Query {
+                address: args
+                    .address
+                    .as_deref()
+                    .map(|address| parse_address("address", address))
+                    .transpose()?,
+                category: args.category,
+                min_difficulty: args
+                    .min_difficulty
+                    .as_deref()
+                    .map(parse_difficulty)
+                    .transpose()?,
+                job_id: args.job_id,
+                pending: args.pending,
+                limit: args.limit,
             };
+            let results = ResultsDb::open(&args.db.db)?.query(&query)?;
+            match args.json {
+                true => print!("{}", db::to_json(&results)),
+                false => print!("{}", db::to_csv(&results)),
+            }
+        }
+        DbCommands::Import(args) => {
+            let config = results_config(&args.job, None)?;
+            let database = ResultsDb::open(&args.db.db)?;
+            for source in &args.sources {
+                let mut added = 0;
+                for record in results::read_records(source)? {
+                    added += database.insert(&config, &record)? as usize;
+                }
+                log::info!("Imported {} new results from {}", added, source);
+            }
+        }
+        DbCommands::MarkUploaded(args) => {
+            let database = ResultsDb::open(&args.db.db)?;
+            for address in &args.addresses {
+                let marked = database.mark_uploaded(&parse_address("address", address)?)?;
+                log::info!("Marked {} results of {} as uploaded", marked, address);
+            }
         }
+        DbCommands::Job(args) => match ResultsDb::open(&args.db.db)?.job(&args.id)? {
+            Some(parameters) => print!("{parameters}"),
+            None => {
+                return Err(CrunchError::InvalidJob(format!(
+                    "job {} is not in the database",
+                    args.id
+                )))
+            }
+        },
     }
+    Ok(())
 }
@@ -0,0 +1,130 @@
+use crate::{Config, CreateXVariant};
+use std::{fmt::Write as _, ops::Range};
+use tiny_keccak::keccakf;
+
+/// Bytes absorbed per keccak256 permutation.
+const RATE: usize = 136;
+
+/// Offset of the salt in the CREATE2 preimage `0xff ++ factory ++ salt ++ init code hash`.
+pub const CREATE2_SALT_OFFSET: usize = 21;
+
+/// The padded keccak block of a preimage fitting in a single block, filled in ahead of a kernel
+/// launch with only the bytes that vary between work items left out.
+///
+/// No permutation is run ahead of time: the preimage fits in one block, so every hash still
+/// takes its single permutation, and the varying bytes sit in the same block as the constant
+/// ones. The kernel starts every hash from these lanes and writes the varying bytes over them,
+/// which only saves laying out the constant bytes of the preimage byte by byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Midstate {
+    lanes: [u64; 25],
+    /// Bytes of the preimage written by every work item.
+    pub varying: Range<usize>,
+}
+
+impl Midstate {
+    /// Fills in and pads the block of the preimage, whose bytes in the varying range are
+    /// ignored.
+    pub fn new(preimage: &[u8], varying: Range<usize>) -> Self {
+        assert!(preimage.len() < RATE, "preimage spans several blocks");
+        let mut block = [0u8; 200];
+        block[..preimage.len()].copy_from_slice(preimage);
+        block[varying.clone()].fill(0);
+        block[preimage.len()] = 0x01;
+        block[RATE - 1] |= 0x80;
+
+        let mut lanes = [0u64; 25];
+        for (lane, bytes) in lanes.iter_mut().zip(block.chunks(8)) {
+            *lane = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Self { lanes, varying }
+    }
+
+    /// Lanes of the state, in the little-endian byte order of the kernel.
+    pub fn lanes(&self) -> &[u64; 25] {
+        &self.lanes
+    }
+
+    /// Hashes the preimage with the varying bytes in place, the way a work item does.
+    pub fn hash(&self, varying: &[u8]) -> [u8; 32] {
+        assert_eq!(varying.len(), self.varying.len());
+        let mut lanes = self.lanes;
+        for (i, byte) in self.varying.clone().zip(varying) {
+            lanes[i / 8] ^= (*byte as u64) << (i % 8 * 8);
+        }
+        keccakf(&mut lanes);
+
+        let mut hash = [0u8; 32];
+        for (bytes, lane) in hash.chunks_mut(8).zip(lanes) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+        hash
+    }
+
+    /// Declares the lanes as a kernel constant.
+    pub fn opencl(&self, name: &str) -> String {
+        let lanes: Vec<String> = self.lanes.iter().map(|x| format!("0x{x:016x}ul")).collect();
+        let mut src = String::new();
+        writeln!(
+            src,
+            "__constant ulong {name}[25] = {{{}}};",
+            lanes.join(", ")
+        )
+        .unwrap();
+        src
+    }
+}
+
+/// Padded block of the CREATE2 preimage of a config, hashed by the proxy first for CREATE3, or
+/// `None` for CREATE. Only the bytes of the salt layout mined by the kernel vary, or the whole
+/// salt if the factory guards it.
+pub fn create2(config: &Config) -> Option<Midstate> {
+    let init_code_hash = match config.create_variant {
+        CreateXVariant::Create2 { init_code_hash } => init_code_hash,
+        CreateXVariant::Create3 => config.factory_kind.proxy_codehash(),
+        CreateXVariant::Create { .. } => return None,
+    };
+    let (salt, mined_offset) = config.salt_template();
+    let varying = match config.guard_preimage() {
+        Some(_) => 0..32,
+        None => {
+            let varying = config.salt_layout.varying();
+            mined_offset + varying.start..mined_offset + varying.end
+        }
+    };
+
+    let mut preimage = [0u8; 85];
+    preimage[0] = 0xff;
+    preimage[1..21].copy_from_slice(&config.factory_address);
+    preimage[21..53].copy_from_slice(&salt);
+    preimage[53..].copy_from_slice(&init_code_hash);
+    Some(Midstate::new(
+        &preimage,
+        varying.start + CREATE2_SALT_OFFSET..varying.end + CREATE2_SALT_OFFSET,
+    ))
+}
+
+/// Padded block of the RLP preimage `rlp([proxy, 1])` of a CREATE3 deployment, where the proxy
+/// varies.
+pub fn create3() -> Midstate {
+    let mut preimage = [0u8; 23];
+    preimage[0] = 0xd6;
+    preimage[1] = 0x94;
+    preimage[22] = 0x01;
+    Midstate::new(&preimage, 2..22)
+}
+
+/// Derives the address of a salt from the padded blocks the kernel starts from, for checking them
+/// against [`Config::derive_address`]. `None` for CREATE.
+pub fn derive_address(config: &Config, salt: &[u8; 32]) -> Option<[u8; 20]> {
+    let midstate = create2(config)?;
+    let salt = config.guard_salt(salt);
+    let salt_bytes =
+        midstate.varying.start - CREATE2_SALT_OFFSET..midstate.varying.end - CREATE2_SALT_OFFSET;
+    let hash = midstate.hash(&salt[salt_bytes]);
+    let hash = match config.create_variant {
+        CreateXVariant::Create3 => create3().hash(&hash[12..]),
+        _ => hash,
+    };
+    Some(hash[12..].try_into().unwrap())
+}
@@ -0,0 +1,245 @@
+use crate::{decode_fixed, parse_address, CrunchError};
+use alloy_primitives::hex;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use web3::{
+    ethabi::{self, Token},
+    signing::keccak256,
+    types::U256,
+};
+
+/// Longest preimage the kernel hashes the salt in.
+pub const MAX_PREIMAGE_LEN: usize = 1024;
+
+/// A preimage the factory hashes the user-controlled salt in before deploying with CREATE2, for
+/// factories whose salt depends on the deployment, like smart account factories.
+///
+/// The kernel hashes [`SaltPreimage::template`] with the salt written into it, while
+/// [`SaltPreimage::hash`] reimplements what the factory does to verify every result. Factories
+/// that deploy with the salt as is have no template, and only keep the salt a small uint256.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SaltPreimage {
+    /// Any preimage, with the salt written at `offset`.
+    Custom { template: Vec<u8>, offset: usize },
+    /// Safe's SafeProxyFactory: `keccak256(abi.encodePacked(keccak256(initializer), saltNonce))`.
+    SafeProxy { initializer: Vec<u8> },
+    /// Alchemy's LightAccountFactory v2: `keccak256(abi.encodePacked(owner, salt))`.
+    LightAccount { owner: [u8; 20] },
+    /// ZeroDev's Kernel factory: `keccak256(abi.encodePacked(data, salt))`.
+    Kernel { data: Vec<u8> },
+    /// Coinbase Smart Wallet factory: `keccak256(abi.encode(owners, nonce))`.
+    CoinbaseSmartWallet { owners: Vec<Vec<u8>> },
+    /// eth-infinitism's SimpleAccountFactory: the salt as is, the owner being part of the init
+    /// code of the account proxy.
+    SimpleAccount,
+}
+
+impl SaltPreimage {
+    /// Returns the preimage with a zero salt, and the offset of the salt within it, or `None` if
+    /// the factory deploys with the salt as is.
+    pub fn template(&self) -> Option<(Vec<u8>, usize)> {
+        let packed = |prefix: &[u8]| {
+            let mut preimage = prefix.to_vec();
+            preimage.extend([0u8; 32]);
+            (preimage, prefix.len())
+        };
+        let template = match self {
+            SaltPreimage::Custom { template, offset } => (template.clone(), *offset),
+            SaltPreimage::SafeProxy { initializer } => packed(&keccak256(initializer)),
+            SaltPreimage::LightAccount { owner } => packed(owner),
+            SaltPreimage::Kernel { data } => packed(data),
+            // the head holds the offset of the owners, then the nonce
+            SaltPreimage::CoinbaseSmartWallet { owners } => {
+                (coinbase_encode(owners, U256::zero()), 32)
+            }
+            SaltPreimage::SimpleAccount => return None,
+        };
+        Some(template)
+    }
+
+    /// Computes the salt the factory deploys with, independently of the template.
+    pub fn hash(&self, salt: &[u8; 32]) -> [u8; 32] {
+        let packed = |prefix: &[u8]| {
+            let preimage: Vec<u8> = prefix.iter().chain(salt).copied().collect();
+            keccak256(&preimage)
+        };
+        match self {
+            SaltPreimage::Custom { template, offset } => {
+                let mut preimage = template.clone();
+                preimage[*offset..offset + 32].copy_from_slice(salt);
+                keccak256(&preimage)
+            }
+            SaltPreimage::SafeProxy { initializer } => packed(&keccak256(initializer)),
+            SaltPreimage::LightAccount { owner } => packed(owner),
+            SaltPreimage::Kernel { data } => packed(data),
+            SaltPreimage::CoinbaseSmartWallet { owners } => {
+                keccak256(&coinbase_encode(owners, U256::from_big_endian(salt)))
+            }
+            SaltPreimage::SimpleAccount => *salt,
+        }
+    }
+
+    /// Checks that the kernel can hash the salt in the preimage.
+    pub fn validate(&self) -> Result<(), CrunchError> {
+        let Some((template, offset)) = self.template() else {
+            return Ok(());
+        };
+        if offset + 32 > template.len() {
+            return Err(CrunchError::InvalidSaltPreimage(format!(
+                "the salt at offset {offset} does not fit in {} bytes",
+                template.len()
+            )));
+        }
+        if template.len() > MAX_PREIMAGE_LEN {
+            return Err(CrunchError::InvalidSaltPreimage(format!(
+                "preimage of {} bytes is longer than {MAX_PREIMAGE_LEN} bytes",
+                template.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn coinbase_encode(owners: &[Vec<u8>], nonce: U256) -> Vec<u8> {
+    let owners = owners.iter().map(|owner| Token::Bytes(owner.clone()));
+    ethabi::encode(&[Token::Array(owners.collect()), Token::Uint(nonce)])
+}
+
+/// Smart account factories with a preset salt preimage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountFactory {
+    LightAccount,
+    Kernel,
+    CoinbaseSmartWallet,
+    SimpleAccount,
+}
+
+impl Display for AccountFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountFactory::LightAccount => write!(f, "light-account"),
+            AccountFactory::Kernel => write!(f, "kernel"),
+            AccountFactory::CoinbaseSmartWallet => write!(f, "coinbase-smart-wallet"),
+            AccountFactory::SimpleAccount => write!(f, "simple-account"),
+        }
+    }
+}
+
+/// A salt preimage as stored in a job file, either for a preset account factory or as a custom
+/// template.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreimageSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountFactory>,
+    /// Owners of the account. LightAccount takes a single owner address; Coinbase Smart Wallet
+    /// owners are addresses or 64-byte public keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// Initialization data of a Kernel account, in hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Custom preimage in hex, with 32 placeholder bytes for the salt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Offset of the salt within the custom preimage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+}
+
+impl PreimageSpec {
+    /// Resolves the spec into the preimage of the selected account factory, or the custom one.
+    pub fn resolve(&self) -> Result<SaltPreimage, CrunchError> {
+        let preimage = match (self.account, &self.template) {
+            (Some(_), Some(_)) => {
+                return Err(CrunchError::InvalidSaltPreimage(
+                    "either an account factory or a template may be given, not both".to_string(),
+                ))
+            }
+            (None, None) => {
+                return Err(CrunchError::InvalidSaltPreimage(
+                    "an account factory or a template is required".to_string(),
+                ))
+            }
+            (None, Some(template)) => SaltPreimage::Custom {
+                template: decode_hex("preimage template", template)?,
+                offset: self.offset.ok_or_else(|| {
+                    CrunchError::InvalidSaltPreimage(
+                        "the offset of the salt in the template is required".to_string(),
+                    )
+                })?,
+            },
+            (Some(AccountFactory::LightAccount), None) => match &self.owners[..] {
+                [owner] => SaltPreimage::LightAccount {
+                    owner: parse_address("owner address", owner)?,
+                },
+                _ => {
+                    return Err(CrunchError::InvalidSaltPreimage(
+                        "light-account takes exactly one owner".to_string(),
+                    ))
+                }
+            },
+            (Some(AccountFactory::Kernel), None) => SaltPreimage::Kernel {
+                data: decode_hex(
+                    "kernel data",
+                    self.data.as_deref().ok_or_else(|| {
+                        CrunchError::InvalidSaltPreimage(
+                            "kernel requires the initialization data".to_string(),
+                        )
+                    })?,
+                )?,
+            },
+            (Some(AccountFactory::CoinbaseSmartWallet), None) => {
+                if self.owners.is_empty() {
+                    return Err(CrunchError::InvalidSaltPreimage(
+                        "coinbase-smart-wallet requires at least one owner".to_string(),
+                    ));
+                }
+                let owners = self
+                    .owners
+                    .iter()
+                    .map(|owner| match owner.trim_start_matches("0x").len() {
+                        // addresses are owners as abi.encode(owner)
+                        40 => Ok(ethabi::encode(&[Token::Address(
+                            parse_address("owner address", owner)?.into(),
+                        )])),
+                        _ => decode_fixed::<64>("owner public key", owner).map(|key| key.to_vec()),
+                    })
+                    .collect::<Result<_, _>>()?;
+                SaltPreimage::CoinbaseSmartWallet { owners }
+            }
+            (Some(AccountFactory::SimpleAccount), None) => SaltPreimage::SimpleAccount,
+        };
+        let owners = ("owners", !self.owners.is_empty());
+        let data = ("data", self.data.is_some());
+        let offset = ("an offset", self.offset.is_some());
+        let unused = match preimage {
+            SaltPreimage::Custom { .. } => vec![owners, data],
+            SaltPreimage::Kernel { .. } => vec![owners, offset],
+            // the owner goes in the init code, with the constructor arguments of the proxy
+            SaltPreimage::SimpleAccount => vec![owners, data, offset],
+            _ => vec![data, offset],
+        };
+        if let Some((field, _)) = unused.iter().find(|(_, set)| *set) {
+            let name = self
+                .account
+                .map_or("a custom template".to_string(), |account| {
+                    account.to_string()
+                });
+            return Err(CrunchError::InvalidSaltPreimage(format!(
+                "{name} does not take {field}"
+            )));
+        }
+        preimage.validate()?;
+        Ok(preimage)
+    }
+}
+
+fn decode_hex(field: &'static str, value: &str) -> Result<Vec<u8>, CrunchError> {
+    hex::decode(value).map_err(|_| CrunchError::InvalidHex {
+        field,
+        value: value.to_string(),
+    })
+}
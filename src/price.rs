@@ -0,0 +1,163 @@
+use crate::{
+    record::ResultRecord,
+    score::{FancyScore, ScorerRegistry, Thresholds},
+    CrunchError,
+};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{fmt::Write as _, path::Path};
+use web3::types::Address;
+
+/// Hashrate difficulties are expressed in GPU-hours at by default, 1 GH/s.
+pub const DEFAULT_HASHRATE: f64 = 1e9;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// How found addresses are valued: by the work it takes to find an address as difficult, and
+/// relative to the price of an address at the base difficulty of the thresholds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pricing {
+    /// Addresses a GPU checks per second.
+    pub hashrate: f64,
+    /// Price of the addresses at or below the base difficulty.
+    pub base_price: f64,
+}
+
+impl Default for Pricing {
+    fn default() -> Self {
+        Self {
+            hashrate: DEFAULT_HASHRATE,
+            base_price: 1.0,
+        }
+    }
+}
+
+/// The value of an address, as listed in a valuation report.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Valuation {
+    /// Checksummed address.
+    pub address: String,
+    /// Category the address scores highest in.
+    pub category: String,
+    pub score: f64,
+    /// Weighted difficulty of the address in its category.
+    pub difficulty: f64,
+    /// GPU time it takes on average to find an address as difficult.
+    pub gpu_hours: f64,
+    pub price_multiplier: f64,
+    pub price: f64,
+}
+
+impl Pricing {
+    pub fn validate(&self) -> Result<(), CrunchError> {
+        if !self.hashrate.is_finite() || self.hashrate <= 0.0 {
+            return Err(CrunchError::InvalidPricing(format!(
+                "hashrate must be positive, got {}",
+                self.hashrate
+            )));
+        }
+        if !self.base_price.is_finite() || self.base_price < 0.0 {
+            return Err(CrunchError::InvalidPricing(format!(
+                "base price must not be negative, got {}",
+                self.base_price
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn value(&self, score: &FancyScore) -> Valuation {
+        Valuation {
+            address: score.address_mixed_case.clone(),
+            category: score.category.clone(),
+            score: score.scores[&score.category].score,
+            difficulty: score.total_score,
+            gpu_hours: score.total_score / self.hashrate / 3600.0,
+            price_multiplier: score.price_multiplier,
+            price: self.base_price * score.price_multiplier,
+        }
+    }
+
+    /// Scores and values every address, in order.
+    pub fn valuations(
+        &self,
+        registry: &ScorerRegistry,
+        thresholds: &Thresholds,
+        addresses: &[[u8; 20]],
+    ) -> Vec<Valuation> {
+        addresses
+            .iter()
+            .map(|address| self.value(&registry.score(Address::from_slice(address), thresholds)))
+            .collect()
+    }
+}
+
+/// Parses a hashrate in hashes per second, optionally with a `K`, `M`, `G` or `T` suffix, e.g.
+/// `2.5G`.
+pub fn parse_hashrate(value: &str) -> Result<f64, CrunchError> {
+    let trimmed = value.trim().trim_end_matches("H/s");
+    let (number, unit) = match trimmed.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&trimmed[..i], 1e3),
+        Some((i, 'M')) => (&trimmed[..i], 1e6),
+        Some((i, 'G')) => (&trimmed[..i], 1e9),
+        Some((i, 'T')) => (&trimmed[..i], 1e12),
+        _ => (trimmed, 1.0),
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|number| number * unit)
+        .filter(|hashrate| hashrate.is_finite() && *hashrate > 0.0)
+        .ok_or_else(|| CrunchError::InvalidPricing(format!("invalid hashrate {value}")))
+}
+
+/// Addresses of the records in the `addr_<address>.csv` files of a results directory, sorted and
+/// without duplicates.
+pub fn result_addresses(dir: impl AsRef<Path>) -> Result<Vec<[u8; 20]>, CrunchError> {
+    let mut addresses = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !(name.starts_with("addr_") && name.ends_with(".csv")) {
+            continue;
+        }
+        for line in std::fs::read_to_string(&path)?.lines() {
+            if !line.trim().is_empty() {
+                addresses.push(line.parse::<ResultRecord>()?.address);
+            }
+        }
+    }
+    addresses.sort();
+    addresses.dedup();
+    Ok(addresses)
+}
+
+/// Writes the valuations as CSV, with a header line.
+pub fn to_csv(valuations: &[Valuation]) -> String {
+    let mut csv =
+        "address,category,score,difficulty,gpu_hours,price_multiplier,price\n".to_string();
+    for valuation in valuations {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{}",
+            valuation.address,
+            valuation.category,
+            valuation.score,
+            valuation.difficulty,
+            valuation.gpu_hours,
+            valuation.price_multiplier,
+            valuation.price
+        )
+        .unwrap();
+    }
+    csv
+}
+
+pub fn to_json(valuations: &[Valuation]) -> String {
+    serde_json::to_string_pretty(valuations).expect("valuations are always serializable")
+}
@@ -0,0 +1,85 @@
+use crate::{decode_fixed, CrunchError};
+use alloy_primitives::hex;
+use std::{fmt::Display, path::Path, str::FromStr};
+
+/// A found address, as written to `addr_<address>.csv`:
+/// `salt,address,factory,miner_version,job_id,init_code_hash,init_code_source,acceptance`.
+///
+/// Records written before the job columns were introduced only have the first four fields, and
+/// those written before the acceptance rule was recorded the first seven.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResultRecord {
+    pub salt: [u8; 32],
+    pub address: [u8; 20],
+    pub factory: [u8; 20],
+    /// Miner version and the number of billion hashes processed, e.g. `0.3.0_12`.
+    pub miner: String,
+    pub job_id: String,
+    /// Init code hash of CREATE2 deployments, `None` for CREATE3.
+    pub init_code_hash: Option<[u8; 32]>,
+    pub init_code_source: String,
+    /// Category and minimum difficulty the address was accepted with, e.g.
+    /// `leading_zeroes>=4294967296`.
+    pub acceptance: String,
+}
+
+impl ResultRecord {
+    /// Reads the first record of a results file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CrunchError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        contents
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .ok_or_else(|| CrunchError::InvalidRecord(format!("{} is empty", path.display())))?
+            .parse()
+    }
+}
+
+impl Display for ResultRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{},{},{},{},{},{},{},{}",
+            hex::encode_prefixed(self.salt),
+            hex::encode_prefixed(self.address),
+            hex::encode_prefixed(self.factory),
+            self.miner,
+            self.job_id,
+            self.init_code_hash
+                .map(hex::encode_prefixed)
+                .unwrap_or_default(),
+            self.init_code_source,
+            self.acceptance
+        )
+    }
+}
+
+impl FromStr for ResultRecord {
+    type Err = CrunchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.trim().split(',').collect();
+        if ![4, 7, 8].contains(&fields.len()) {
+            return Err(CrunchError::InvalidRecord(format!(
+                "expected 4, 7 or 8 fields, got {}",
+                fields.len()
+            )));
+        }
+        let field = |i: usize| fields.get(i).copied().unwrap_or_default();
+
+        Ok(ResultRecord {
+            salt: decode_fixed("record salt", field(0))?,
+            address: decode_fixed("record address", field(1))?,
+            factory: decode_fixed("record factory", field(2))?,
+            miner: field(3).to_string(),
+            job_id: field(4).to_string(),
+            init_code_hash: match field(5) {
+                "" => None,
+                hash => Some(decode_fixed("record init code hash", hash)?),
+            },
+            init_code_source: field(6).to_string(),
+            acceptance: field(7).to_string(),
+        })
+    }
+}
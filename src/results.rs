@@ -0,0 +1,259 @@
+use crate::{record::ResultRecord, score::FancyScore, Config, CrunchError};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// File of an output directory that the top results are kept in, one record per line.
+pub const STORE_FILE: &str = "results.csv";
+
+/// Store files kept by the mining threads, loaded on their first accepted result and shared by
+/// the threads of several devices, by path.
+static STORES: Mutex<BTreeMap<PathBuf, ResultStore>> = Mutex::new(BTreeMap::new());
+
+/// A found address with its record and its score.
+#[derive(Clone, Debug)]
+pub struct StoredResult {
+    pub record: ResultRecord,
+    pub score: FancyScore,
+}
+
+impl StoredResult {
+    /// Category the address scores highest in, which it is ranked within.
+    pub fn category(&self) -> &str {
+        &self.score.category
+    }
+
+    /// Weighted difficulty of the address in its category.
+    pub fn difficulty(&self) -> f64 {
+        self.score.total_score
+    }
+}
+
+/// Found addresses ranked by difficulty, holding a single record per address.
+///
+/// Addresses are scored with the categories of a config, so that results of different jobs and
+/// runs compare. The first record of an address is kept, later ones found on restarts or in
+/// other output files are dropped.
+#[derive(Clone, Debug, Default)]
+pub struct ResultStore {
+    results: BTreeMap<[u8; 20], StoredResult>,
+    /// Addresses pruned from the store, whose records may be dropped from the output directory.
+    pruned: HashSet<[u8; 20]>,
+}
+
+impl ResultStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a record scored with the config. Returns whether its address was new to the store.
+    pub fn insert(&mut self, config: &Config, record: ResultRecord) -> bool {
+        if self.results.contains_key(&record.address) {
+            return false;
+        }
+        let score = config.score(&record.address);
+        self.results
+            .insert(record.address, StoredResult { record, score });
+        true
+    }
+
+    /// Adds the records of a results file, or of the store file and the `addr_<address>.csv`
+    /// files of an output directory. Returns the number of new addresses.
+    pub fn load(&mut self, config: &Config, path: impl AsRef<Path>) -> Result<usize, CrunchError> {
+        let mut added = 0;
+        for record in read_records(path)? {
+            added += self.insert(config, record) as usize;
+        }
+        Ok(added)
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    pub fn contains(&self, address: &[u8; 20]) -> bool {
+        self.results.contains_key(address)
+    }
+
+    /// Every result, from the most difficult down. Ties are ordered by address.
+    pub fn ranked(&self) -> Vec<&StoredResult> {
+        let mut ranked: Vec<&StoredResult> = self.results.values().collect();
+        ranked.sort_by(|a, b| b.difficulty().total_cmp(&a.difficulty()));
+        ranked
+    }
+
+    /// The `count` most difficult results.
+    pub fn top(&self, count: usize) -> Vec<&StoredResult> {
+        let mut ranked = self.ranked();
+        ranked.truncate(count);
+        ranked
+    }
+
+    /// The `count` most difficult results of every category, from the most difficult down.
+    pub fn top_per_category(&self, count: usize) -> Vec<&StoredResult> {
+        let mut kept: HashMap<&str, usize> = HashMap::new();
+        self.ranked()
+            .into_iter()
+            .filter(|result| {
+                let kept = kept.entry(result.category()).or_default();
+                *kept += 1;
+                *kept <= count
+            })
+            .collect()
+    }
+
+    /// Drops every result but the `count` most difficult of each category. Returns the number of
+    /// results dropped.
+    pub fn prune(&mut self, count: usize) -> usize {
+        let kept: HashSet<[u8; 20]> = self
+            .top_per_category(count)
+            .iter()
+            .map(|result| result.record.address)
+            .collect();
+        let before = self.results.len();
+        let pruned = &mut self.pruned;
+        self.results.retain(|address, _| {
+            if kept.contains(address) {
+                return true;
+            }
+            pruned.insert(*address);
+            false
+        });
+        before - self.results.len()
+    }
+
+    /// Writes the records to a results file, from the most difficult down. The file is replaced
+    /// at once, so that an interrupted write leaves the previous results in place.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CrunchError> {
+        let path = path.as_ref();
+        let mut contents = String::new();
+        for result in self.ranked() {
+            writeln!(contents, "{}", result.record).unwrap();
+        }
+        let partial = path.with_extension("csv.partial");
+        std::fs::write(&partial, contents)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// Saves the store to the store file of an output directory it was loaded from, and removes
+    /// the `addr_<address>.csv` files it holds, so that pruned results are not loaded again.
+    /// Files holding records the store has not seen, like ones written by a running search since
+    /// it was loaded, are kept. Returns the number of files removed.
+    pub fn compact(&self, dir: impl AsRef<Path>) -> Result<usize, CrunchError> {
+        let dir = dir.as_ref();
+        self.save(dir.join(STORE_FILE))?;
+        let mut removed = 0;
+        for file in result_files(dir)? {
+            if file.file_name() == Some(STORE_FILE.as_ref()) {
+                continue;
+            }
+            let seen = read_records(&file)?.iter().all(|record| {
+                self.contains(&record.address) || self.pruned.contains(&record.address)
+            });
+            if seen {
+                std::fs::remove_file(&file)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Reads every record of a results file, or of the store file and the `addr_<address>.csv` files
+/// of an output directory, in order.
+pub fn read_records(path: impl AsRef<Path>) -> Result<Vec<ResultRecord>, CrunchError> {
+    let path = path.as_ref();
+    let files = match path.is_dir() {
+        true => result_files(path)?,
+        false => vec![path.to_path_buf()],
+    };
+    let mut records = Vec::new();
+    for file in files {
+        for line in std::fs::read_to_string(&file)?.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = line
+                .parse::<ResultRecord>()
+                .map_err(|e| CrunchError::InvalidRecord(format!("{}: {e}", file.display())))?;
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// The store file of an output directory, followed by its `addr_<address>.csv` files sorted by
+/// name.
+fn result_files(dir: &Path) -> Result<Vec<PathBuf>, CrunchError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with("addr_") && name.ends_with(".csv") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    let store = dir.join(STORE_FILE);
+    if store.is_file() {
+        files.insert(0, store);
+    }
+    Ok(files)
+}
+
+/// Adds an accepted record to the store file of the output directory, keeping the `count` most
+/// difficult results of each category. The store is read once and kept in memory, and the file
+/// is only rewritten when the record makes it into the top results.
+pub(crate) fn retain(config: &Config, record: &ResultRecord, count: usize) {
+    let path = Path::new(&config.output_dir).join(STORE_FILE);
+    let mut stores = STORES.lock().unwrap_or_else(|e| e.into_inner());
+    let store = match stores.get_mut(&path) {
+        Some(store) => store,
+        None => {
+            let mut store = ResultStore::new();
+            if path.is_file() {
+                if let Err(e) = store.load(config, &path) {
+                    log::error!("Failed to load results from {}: {}", path.display(), e);
+                    return;
+                }
+            }
+            stores.entry(path.clone()).or_insert(store)
+        }
+    };
+    if !store.insert(config, record.clone()) {
+        return;
+    }
+    store.prune(count);
+    if store.contains(&record.address) {
+        if let Err(e) = store.save(&path) {
+            log::error!("Failed to keep solution in {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Writes results as CSV, with a header line. Every line is the category and the difficulty of
+/// the result followed by its record.
+pub fn to_csv(results: &[&StoredResult]) -> String {
+    let mut csv = "category,difficulty,salt,address,factory,miner,job_id,init_code_hash,\
+                   init_code_source,acceptance\n"
+        .to_string();
+    for result in results {
+        writeln!(
+            csv,
+            "{},{},{}",
+            result.category(),
+            result.difficulty(),
+            result.record
+        )
+        .unwrap();
+    }
+    csv
+}
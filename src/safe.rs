@@ -0,0 +1,137 @@
+use crate::{
+    init_code::{ConstructorArgs, InitCode},
+    parse_address, CrunchError,
+};
+use alloy_primitives::hex;
+use serde::{Deserialize, Serialize};
+use web3::{
+    ethabi::{self, Token},
+    signing::keccak256,
+    types::U256,
+};
+
+/// A Safe proxy deployed with `SafeProxyFactory.createProxyWithNonce(singleton, initializer,
+/// saltNonce)`, as stored in a job file.
+///
+/// The factory deploys `proxyCreationCode ‖ uint256(singleton)` with CREATE2 and the salt
+/// `keccak256(keccak256(initializer) ‖ saltNonce)`, so the salt nonce is what gets mined.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SafeSetup {
+    /// Safe singleton the proxy delegates to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub singleton: Option<String>,
+    /// Creation code of the proxy in hex, as returned by the factory's `proxyCreationCode()`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_code: Option<String>,
+    /// Calldata the proxy is initialized with. Built from the owners when not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initializer: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_handler: Option<String>,
+}
+
+impl SafeSetup {
+    /// Overrides the fields of this setup with every field that is set in `other`.
+    pub fn merge(mut self, other: SafeSetup) -> Self {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field;
+                })*
+            };
+        }
+        merge!(singleton, proxy_code, threshold, fallback_handler);
+        // an initializer replaces the owners it would be built from, and the other way around
+        if other.initializer.is_some() || !other.owners.is_empty() {
+            self.initializer = other.initializer;
+            self.owners = other.owners;
+        }
+        self
+    }
+
+    /// Resolves the initializer, building the `setup` call from the owners if it is not given.
+    pub fn initializer(&self) -> Result<Vec<u8>, CrunchError> {
+        match (&self.initializer, self.owners.is_empty()) {
+            (Some(_), false) => Err(CrunchError::InvalidSafeSetup(
+                "either the initializer or the owners may be given, not both".to_string(),
+            )),
+            (Some(initializer), true) => {
+                hex::decode(initializer).map_err(|_| CrunchError::InvalidHex {
+                    field: "initializer",
+                    value: initializer.clone(),
+                })
+            }
+            (None, true) => Err(CrunchError::InvalidSafeSetup(
+                "the initializer or the owners are required".to_string(),
+            )),
+            (None, false) => {
+                let owners = self
+                    .owners
+                    .iter()
+                    .map(|owner| parse_address("owner address", owner))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let fallback_handler = self
+                    .fallback_handler
+                    .as_deref()
+                    .map(|handler| parse_address("fallback handler address", handler))
+                    .transpose()?
+                    .unwrap_or_default();
+                setup_calldata(&owners, self.threshold.unwrap_or(1), &fallback_handler)
+            }
+        }
+    }
+
+    /// Init code the factory deploys, the proxy creation code followed by the singleton.
+    pub fn init_code(&self) -> Result<InitCode, CrunchError> {
+        let (Some(singleton), Some(proxy_code)) = (&self.singleton, &self.proxy_code) else {
+            return Err(CrunchError::InvalidSafeSetup(
+                "the singleton and the proxy creation code are required".to_string(),
+            ));
+        };
+        parse_address("singleton address", singleton)?;
+        InitCode::from_hex(proxy_code)?.with_constructor_args(&ConstructorArgs {
+            signature: "constructor(address)".to_string(),
+            values: vec![singleton.clone()],
+        })
+    }
+}
+
+/// Calldata of `Safe.setup` with the given owners and threshold, the fallback handler and no
+/// module setup or payment.
+pub fn setup_calldata(
+    owners: &[[u8; 20]],
+    threshold: u64,
+    fallback_handler: &[u8; 20],
+) -> Result<Vec<u8>, CrunchError> {
+    if threshold == 0 || threshold > owners.len() as u64 {
+        return Err(CrunchError::InvalidSafeSetup(format!(
+            "threshold must be between 1 and the number of owners ({}), got {threshold}",
+            owners.len()
+        )));
+    }
+
+    let tokens = [
+        Token::Array(
+            owners
+                .iter()
+                .map(|owner| Token::Address((*owner).into()))
+                .collect(),
+        ),
+        Token::Uint(threshold.into()),
+        Token::Address([0u8; 20].into()),
+        Token::Bytes(Vec::new()),
+        Token::Address((*fallback_handler).into()),
+        Token::Address([0u8; 20].into()),
+        Token::Uint(U256::zero()),
+        Token::Address([0u8; 20].into()),
+    ];
+    let signature = "setup(address[],uint256,address,bytes,address,address,uint256,address)";
+    let mut calldata = keccak256(signature.as_bytes())[..4].to_vec();
+    calldata.extend(ethabi::encode(&tokens));
+    Ok(calldata)
+}
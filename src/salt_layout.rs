@@ -0,0 +1,307 @@
+use crate::CrunchError;
+use alloy_primitives::hex;
+use std::{
+    fmt::{Display, Write as _},
+    ops::Range,
+    str::FromStr,
+};
+
+/// Low bytes of the counter taken from the index of the work item within a launch.
+pub const WORK_ITEM_BYTES: usize = 4;
+
+/// Most bytes of a counter: the work item index, followed by the 64-bit index of the launch.
+pub const MAX_COUNTER_BYTES: usize = WORK_ITEM_BYTES + 8;
+
+/// A run of bytes of the salt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SaltSegment {
+    /// Bytes kept as given, e.g. a tag or version identifying the deployer.
+    Fixed(Vec<u8>),
+    /// Bytes drawn at random for every kernel launch.
+    Random(usize),
+    /// Little-endian counter enumerating the work items of a launch in its first 4 bytes, and the
+    /// launches in the bytes after them.
+    Counter(usize),
+}
+
+impl SaltSegment {
+    pub fn len(&self) -> usize {
+        match self {
+            SaltSegment::Fixed(bytes) => bytes.len(),
+            SaltSegment::Random(len) | SaltSegment::Counter(len) => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Layout of the bytes of the salt left free by the factory, e.g. the 11 bytes after the caller
+/// and cross-chain flag of a CreateX salt, or the whole salt of a plain factory. Bytes after the
+/// layout are zero.
+///
+/// Written as comma-separated segments, e.g. `fixed:0xc0ffee,random:4,counter:8`. Defaults to
+/// `random:4,counter:7`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaltLayout {
+    segments: Vec<SaltSegment>,
+}
+
+impl Default for SaltLayout {
+    fn default() -> Self {
+        Self {
+            segments: vec![SaltSegment::Random(4), SaltSegment::Counter(7)],
+        }
+    }
+}
+
+impl SaltLayout {
+    /// Checks that the segments enumerate the work items with a single counter.
+    pub fn new(segments: Vec<SaltSegment>) -> Result<Self, CrunchError> {
+        if segments.iter().any(SaltSegment::is_empty) {
+            return Err(CrunchError::InvalidSaltLayout(
+                "segments cannot be empty".to_string(),
+            ));
+        }
+        let counters: Vec<usize> = segments
+            .iter()
+            .filter_map(|segment| match segment {
+                SaltSegment::Counter(len) => Some(*len),
+                _ => None,
+            })
+            .collect();
+        match counters[..] {
+            [len] if (WORK_ITEM_BYTES..=MAX_COUNTER_BYTES).contains(&len) => {}
+            [len] => {
+                return Err(CrunchError::InvalidSaltLayout(format!(
+                    "the counter takes {WORK_ITEM_BYTES} to {MAX_COUNTER_BYTES} bytes, not {len}"
+                )))
+            }
+            _ => {
+                return Err(CrunchError::InvalidSaltLayout(
+                    "exactly one counter is required".to_string(),
+                ))
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    pub fn segments(&self) -> &[SaltSegment] {
+        &self.segments
+    }
+
+    /// Bytes of the salt covered by the layout.
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(SaltSegment::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Bytes drawn at random for every launch.
+    pub fn random_len(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|segment| matches!(segment, SaltSegment::Random(_)))
+            .map(SaltSegment::len)
+            .sum()
+    }
+
+    /// Bytes of the counter.
+    pub fn counter_len(&self) -> usize {
+        self.segments
+            .iter()
+            .find_map(|segment| match segment {
+                SaltSegment::Counter(len) => Some(*len),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Bytes varied by the kernel: the random bytes and the counter.
+    pub fn mined_len(&self) -> usize {
+        self.random_len() + self.counter_len()
+    }
+
+    /// Span of the layout from its first varied byte to its last one.
+    pub fn varying(&self) -> Range<usize> {
+        let mut start = None;
+        let mut end = 0;
+        let mut position = 0;
+        for segment in &self.segments {
+            if !matches!(segment, SaltSegment::Fixed(_)) {
+                start.get_or_insert(position);
+                end = position + segment.len();
+            }
+            position += segment.len();
+        }
+        start.unwrap_or_default()..end
+    }
+
+    /// Checks that the layout fits in the free bytes of the salt.
+    pub fn validate(&self, free: usize) -> Result<(), CrunchError> {
+        if self.len() > free {
+            return Err(CrunchError::InvalidSaltLayout(format!(
+                "{self} takes {} bytes, but the factory leaves {free} bytes of the salt free",
+                self.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that a layout starting at the first byte of a CreateX salt keeps the salt unguarded
+    /// by the caller and chain id: CreateX reads the caller from bytes 0..20 and the cross-chain
+    /// flag from byte 20, and hashes the salt differently or reverts if they select a guard.
+    pub fn validate_createx_random(&self) -> Result<(), CrunchError> {
+        let bytes = self.fixed_bytes();
+        if bytes.len() >= 20 && bytes[..20].iter().all(Option::is_some) {
+            return Err(CrunchError::InvalidSaltLayout(
+                "the first 20 bytes of a CreateX salt select the caller guard and cannot all be \
+                 fixed, use --caller to guard the salt with the caller"
+                    .to_string(),
+            ));
+        }
+        if bytes.get(20).is_some_and(|byte| *byte != Some(0)) {
+            return Err(CrunchError::InvalidSaltLayout(
+                "byte 20 of a CreateX salt is the cross-chain flag and must be fixed to 0x00, \
+                 use --chain-id to guard the salt with the chain id"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The value of every byte of the layout that is fixed, and `None` for the varied ones.
+    pub fn fixed_bytes(&self) -> Vec<Option<u8>> {
+        self.segments
+            .iter()
+            .flat_map(|segment| match segment {
+                SaltSegment::Fixed(bytes) => bytes.iter().copied().map(Some).collect(),
+                _ => vec![None; segment.len()],
+            })
+            .collect()
+    }
+
+    /// The bytes varied by the kernel, in the order of the layout, for the random bytes of a
+    /// launch and the value of the counter.
+    pub fn mined(&self, random: &[u8], counter: u128) -> Vec<u8> {
+        let mut random = random.iter();
+        let mut mined = Vec::with_capacity(self.mined_len());
+        for segment in &self.segments {
+            match segment {
+                SaltSegment::Fixed(_) => {}
+                SaltSegment::Random(len) => mined.extend(random.by_ref().take(*len)),
+                SaltSegment::Counter(len) => {
+                    mined.extend(&counter.to_le_bytes()[..*len]);
+                }
+            }
+        }
+        mined
+    }
+
+    /// Writes the layout at the start of `salt`, taking the varied bytes from `mined`.
+    pub fn fill(&self, salt: &mut [u8], mined: &[u8]) {
+        let mut position = 0;
+        let mut mined = mined.iter();
+        for segment in &self.segments {
+            let bytes = &mut salt[position..position + segment.len()];
+            match segment {
+                SaltSegment::Fixed(fixed) => bytes.copy_from_slice(fixed),
+                _ => {
+                    for byte in bytes {
+                        *byte = *mined.next().expect("too few mined bytes for the layout");
+                    }
+                }
+            }
+            position += segment.len();
+        }
+    }
+
+    /// Defines `SALT_MINED()`, writing the random bytes of `d_message` and the counter of the
+    /// work item `gid` in launch `launch` into the salt, with the layout starting at `offset`.
+    pub fn opencl(&self, offset: usize) -> String {
+        let mut src = "#define SALT_MINED() { \\\n".to_string();
+        let mut position = offset;
+        let mut random = 0;
+        for segment in &self.segments {
+            match segment {
+                SaltSegment::Fixed(_) => {}
+                SaltSegment::Random(len) => {
+                    for i in 0..*len {
+                        writeln!(src, "  salt[{}] = d_message[{}]; \\", position + i, random)
+                            .unwrap();
+                        random += 1;
+                    }
+                }
+                SaltSegment::Counter(len) => {
+                    for i in 0..*len {
+                        let shift = 8 * i;
+                        let (word, shift) = match shift < 8 * WORK_ITEM_BYTES {
+                            true => ("gid", shift),
+                            false => ("launch", shift - 8 * WORK_ITEM_BYTES),
+                        };
+                        writeln!(
+                            src,
+                            "  salt[{}] = (uchar)({word} >> {shift}); \\",
+                            position + i
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+            position += segment.len();
+        }
+        src.push_str("}\n");
+        src
+    }
+}
+
+impl FromStr for SaltLayout {
+    type Err = CrunchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| CrunchError::InvalidSaltLayout(reason);
+        let segments =
+            s.split(',')
+                .map(|segment| {
+                    let (kind, value) = segment.trim().split_once(':').ok_or_else(|| {
+                        invalid(format!("{segment} is not of the form kind:value"))
+                    })?;
+                    let len = || {
+                        value
+                            .trim()
+                            .parse::<usize>()
+                            .map_err(|_| invalid(format!("invalid length in {segment}")))
+                    };
+                    match kind.trim() {
+                        "fixed" => hex::decode(value.trim())
+                            .map(SaltSegment::Fixed)
+                            .map_err(|_| invalid(format!("invalid hex in {segment}"))),
+                        "random" => len().map(SaltSegment::Random),
+                        "counter" => len().map(SaltSegment::Counter),
+                        kind => Err(invalid(format!(
+                            "unknown segment {kind}, expected fixed, random or counter"
+                        ))),
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+        Self::new(segments)
+    }
+}
+
+impl Display for SaltLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let segments: Vec<String> = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                SaltSegment::Fixed(bytes) => format!("fixed:{}", hex::encode_prefixed(bytes)),
+                SaltSegment::Random(len) => format!("random:{len}"),
+                SaltSegment::Counter(len) => format!("counter:{len}"),
+            })
+            .collect();
+        write!(f, "{}", segments.join(","))
+    }
+}
@@ -1,5 +1,9 @@
+use crate::fancy::{Definition, Difficulty, Metric, Run, Text};
+use crate::hexspeak::{Dictionary, DictionarySpec};
+use crate::CrunchError;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::env;
+use std::sync::Arc;
 use web3::signing::keccak256;
 
 use std::fmt::Display;
@@ -33,7 +37,7 @@ fn address_to_mixed_case(address: &H160) -> String {
     result
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
 pub enum FancyScoreCategory {
     LeadingZeroes,
     LeadingAny,
@@ -43,10 +47,30 @@ pub enum FancyScoreCategory {
     ShortLeadingAny,
     SnakeScore,
     LeadingLetters,
+    /// Hex-speak words of the built-in [`Dictionary`], only registered with a dictionary
+    /// configured.
+    HexSpeak,
     #[default]
     Random,
 }
 
+impl FancyScoreCategory {
+    /// The built-in categories, in the order ties are broken in. The default registry holds all
+    /// but [`FancyScoreCategory::HexSpeak`].
+    pub const ALL: [FancyScoreCategory; 10] = [
+        FancyScoreCategory::Random,
+        FancyScoreCategory::LeadingZeroes,
+        FancyScoreCategory::LeadingAny,
+        FancyScoreCategory::LettersCount,
+        FancyScoreCategory::NumbersOnly,
+        FancyScoreCategory::ShortLeadingZeroes,
+        FancyScoreCategory::ShortLeadingAny,
+        FancyScoreCategory::SnakeScore,
+        FancyScoreCategory::LeadingLetters,
+        FancyScoreCategory::HexSpeak,
+    ];
+}
+
 impl Display for FancyScoreCategory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -58,6 +82,7 @@ impl Display for FancyScoreCategory {
             FancyScoreCategory::ShortLeadingAny => write!(f, "short_leading_any"),
             FancyScoreCategory::SnakeScore => write!(f, "snake_score"),
             FancyScoreCategory::LeadingLetters => write!(f, "leading_letters"),
+            FancyScoreCategory::HexSpeak => write!(f, "hex_speak"),
             FancyScoreCategory::Random => write!(f, "random"),
         }
     }
@@ -76,6 +101,7 @@ impl FromStr for FancyScoreCategory {
             "short_leading_any" => Ok(FancyScoreCategory::ShortLeadingAny),
             "snake_score" => Ok(FancyScoreCategory::SnakeScore),
             "leading_letters" => Ok(FancyScoreCategory::LeadingLetters),
+            "hex_speak" => Ok(FancyScoreCategory::HexSpeak),
             "random" => Ok(FancyScoreCategory::Random),
             _ => Err(()),
         }
@@ -138,7 +164,7 @@ fn tx_test() {
     //39 letters probability
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct FancyScore {
     pub address_mixed_case: String,
     pub address_lower_case: String,
@@ -147,230 +173,651 @@ pub struct FancyScore {
     pub total_score: f64,
     pub price_multiplier: f64,
     pub category: String,
+    /// Category whose minimum difficulty the address reaches with the highest contribution, if
+    /// the address is accepted.
+    pub accepted_by: Option<String>,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct FancyScoreEntry {
-    pub category: FancyScoreCategory,
+    pub category: String,
     pub score: f64,
     pub difficulty: f64,
+    pub weight: f64,
+    /// Weighted difficulty the category contributes, the highest of which is the total score.
+    pub contribution: f64,
 }
 
-#[allow(dead_code)]
-fn get_env_int(key: &str, default: i64) -> i64 {
-    env::var(key)
-        .map(|s| i64::from_str(&s).unwrap())
-        .unwrap_or(default)
+impl Display for FancyScore {
+    /// Reports the score of the address in every category, over several lines.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} ({})",
+            self.address_mixed_case, self.address_short_etherscan
+        )?;
+        writeln!(
+            f,
+            "  category: {}, total score: {:.4e}, price multiplier: {}, accepted by: {}",
+            self.category,
+            self.total_score,
+            self.price_multiplier,
+            self.accepted_by.as_deref().unwrap_or("none")
+        )?;
+        write!(
+            f,
+            "  {:<22} {:>6} {:>12} {:>8} {:>12}",
+            "category", "score", "difficulty", "weight", "contribution"
+        )?;
+        for entry in self.scores.values() {
+            write!(
+                f,
+                "\n  {:<22} {:>6} {:>12.4e} {:>8} {:>12.4e}",
+                entry.category, entry.score, entry.difficulty, entry.weight, entry.contribution
+            )?;
+        }
+        Ok(())
+    }
 }
 
-fn get_env_float(key: &str, default: f64) -> f64 {
-    env::var(key)
-        .map(|s| f64::from_str(&s).unwrap())
-        .unwrap_or(default)
+/// The highest scoring addresses of a stream of scores, holding at most twice as many at once.
+#[derive(Debug, Clone)]
+pub struct TopScores {
+    count: usize,
+    scores: Vec<FancyScore>,
 }
-pub fn get_base_difficulty() -> f64 {
-    get_env_float("BASE_DIFFICULTY", 16.0f64.powf(9f64))
+
+impl TopScores {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            scores: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, score: FancyScore) {
+        self.scores.push(score);
+        if self.scores.len() >= 2 * self.count.max(1) {
+            self.truncate();
+        }
+    }
+
+    /// The highest scores, from the highest down. Ties keep the order they were pushed in.
+    pub fn into_sorted(mut self) -> Vec<FancyScore> {
+        self.truncate();
+        self.scores
+    }
+
+    fn truncate(&mut self) {
+        self.scores
+            .sort_by(|a, b| b.total_score.total_cmp(&a.total_score));
+        self.scores.truncate(self.count);
+    }
 }
-pub fn get_min_difficulty() -> f64 {
-    get_env_float("MIN_DIFFICULTY", 16.0f64.powf(8f64))
+
+/// The forms of an address scorers look at, without the `0x` prefix.
+#[derive(Debug, Clone)]
+pub struct AddressForms {
+    pub lower_case: String,
+    /// EIP-55 checksummed address.
+    pub mixed_case: String,
+    /// The first 8 and last 9 characters of the checksummed address, as shortened by etherscan.
+    pub short_etherscan: String,
 }
 
-#[allow(clippy::vec_init_then_push)]
-pub fn score_fancy(address: Address) -> FancyScore {
-    let mut score = FancyScore::default();
-
-    score.address_lower_case = format!("{:#x}", address).to_lowercase();
-    score.address_mixed_case = address_to_mixed_case(&address);
-    score.address_short_etherscan =
-        score.address_mixed_case[0..10].to_string() + "..." + &score.address_mixed_case[33..42];
-
-    let mixed_address_str = score.address_mixed_case.trim_start_matches("0x");
-    let address_str = format!("{:#x}", address);
-    let address_str = address_str.trim_start_matches("0x");
-    let short_address_str = score
-        .address_short_etherscan
-        .trim_start_matches("0x")
-        .replace("...", "");
-    let mut leading_zeroes = 0;
-    for c in address_str.chars() {
-        if c == '0' {
-            leading_zeroes += 1;
-        } else {
-            break;
+impl AddressForms {
+    pub fn new(address: &Address) -> Self {
+        let mixed_case = address_to_mixed_case(address);
+        let short_etherscan = mixed_case[2..10].to_string() + &mixed_case[33..42];
+        Self {
+            lower_case: format!("{:x}", address),
+            mixed_case: mixed_case[2..].to_string(),
+            short_etherscan,
         }
     }
+}
 
-    let char_start = address_str.chars().next().unwrap();
-    let mut leading_any = 0;
-    for c in address_str.chars() {
-        if c == char_start {
-            leading_any += 1;
-        } else {
-            break;
+/// A category of fancy addresses.
+pub trait Scorer: Send + Sync {
+    /// Name the category is reported, enabled and weighted under.
+    fn name(&self) -> String;
+
+    /// Returns the score of the address in this category, and its difficulty: how many addresses
+    /// have to be tried on average to find one scoring as high.
+    fn score(&self, address: &AddressForms) -> (f64, f64);
+
+    /// Declarative definition of the category, from which the kernel checks addresses for it.
+    /// Categories without one are only scored on the addresses reported for the others.
+    fn definition(&self) -> Option<Definition> {
+        None
+    }
+
+    /// Words the category scores by their position in the address, which the kernel looks for
+    /// at the start and the end of the address.
+    fn dictionary(&self) -> Option<&Dictionary> {
+        None
+    }
+}
+
+impl FancyScoreCategory {
+    /// The definition of the category, for all but [`FancyScoreCategory::HexSpeak`].
+    pub fn definition(&self) -> Option<Definition> {
+        let (metric, score_offset, difficulty) = match self {
+            FancyScoreCategory::HexSpeak => return None,
+            FancyScoreCategory::Random => (Metric::Constant, 0.0, Difficulty::Constant(1000.0)),
+            FancyScoreCategory::LeadingZeroes => (
+                Metric::LeadingRun {
+                    text: Text::Lowercase,
+                    of: Run::Zero,
+                },
+                0.0,
+                Difficulty::Exponential {
+                    base: 16.0,
+                    offset: 0.0,
+                },
+            ),
+            FancyScoreCategory::LeadingAny => (
+                Metric::LeadingRun {
+                    text: Text::Lowercase,
+                    of: Run::First,
+                },
+                1.0,
+                Difficulty::Exponential {
+                    base: 16.0,
+                    offset: 15. / 16.,
+                },
+            ),
+            FancyScoreCategory::LettersCount => (Metric::Letters, 0.0, Difficulty::Letters),
+            FancyScoreCategory::NumbersOnly => (Metric::Digits, 0.0, Difficulty::Decimal),
+            FancyScoreCategory::ShortLeadingZeroes => (
+                Metric::LeadingRun {
+                    text: Text::ShortChecksummed,
+                    of: Run::Zero,
+                },
+                0.0,
+                Difficulty::Exponential {
+                    base: 16.0,
+                    offset: 0.0,
+                },
+            ),
+            FancyScoreCategory::ShortLeadingAny => (
+                Metric::LeadingRun {
+                    text: Text::ShortChecksummed,
+                    of: Run::First,
+                },
+                0.0,
+                Difficulty::Exponential {
+                    base: 16.0,
+                    offset: 15. / 16.,
+                },
+            ),
+            FancyScoreCategory::SnakeScore => (
+                Metric::Repeats,
+                0.0,
+                Difficulty::Exponential {
+                    base: 16.0,
+                    offset: 9.0,
+                },
+            ),
+            FancyScoreCategory::LeadingLetters => (
+                Metric::LeadingRun {
+                    text: Text::Checksummed,
+                    of: Run::FirstLetter,
+                },
+                0.0,
+                Difficulty::Exponential {
+                    base: 32.0,
+                    offset: 15. / 16.,
+                },
+            ),
+        };
+        Some(Definition {
+            metric,
+            score_offset,
+            difficulty,
+        })
+    }
+}
+
+impl Scorer for FancyScoreCategory {
+    fn name(&self) -> String {
+        self.to_string()
+    }
+
+    fn score(&self, address: &AddressForms) -> (f64, f64) {
+        match FancyScoreCategory::definition(self) {
+            Some(definition) => definition.score(address),
+            None => Dictionary::builtin().score(address),
         }
     }
 
-    let mut leading_letters = 0;
-    let mixed_char_start = mixed_address_str.chars().next().unwrap();
-    if mixed_char_start.is_alphabetic() {
-        for c in mixed_address_str.chars() {
-            if c == mixed_char_start {
-                leading_letters += 1;
-            } else {
-                break;
+    fn definition(&self) -> Option<Definition> {
+        FancyScoreCategory::definition(self)
+    }
+
+    fn dictionary(&self) -> Option<&Dictionary> {
+        match self {
+            FancyScoreCategory::HexSpeak => Some(Dictionary::builtin()),
+            _ => None,
+        }
+    }
+}
+
+/// A category defined in a job file rather than in Rust.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CustomScorer {
+    /// Scores the longest of the words, in hex, found anywhere in the address.
+    Words { name: String, words: Vec<String> },
+    /// Scores the longest run of a repeated pair of characters, like `abababab`.
+    RepeatedPairs { name: String },
+    /// Scores how many characters the address mirrors from both ends, like `1234…4321`.
+    Palindrome { name: String },
+}
+
+impl CustomScorer {
+    /// Checks that the name is made of `[a-z0-9_]`, as it is written unquoted in the acceptance
+    /// column of the results, and that the words are hex.
+    pub fn validate(&self) -> Result<(), CrunchError> {
+        let name = self.name();
+        let valid_name = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+        if name.is_empty() || !name.chars().all(valid_name) {
+            return Err(CrunchError::InvalidScoring(format!(
+                "invalid category name {name:?}, only a-z, 0-9 and _ are allowed"
+            )));
+        }
+        if let CustomScorer::Words { name, words } = self {
+            let valid = |word: &String| {
+                !word.is_empty() && word.len() <= 40 && word.chars().all(|c| c.is_ascii_hexdigit())
+            };
+            if words.is_empty() || !words.iter().all(valid) {
+                return Err(CrunchError::InvalidScoring(format!(
+                    "{name} requires a list of words of 1 to 40 hex characters"
+                )));
             }
         }
+        Ok(())
     }
+}
 
-    let mut allowed_cipher = 'a';
-    let mut letters_only = 0;
-    for c in address_str.chars() {
-        if c.is_alphabetic() {
-            letters_only += 1;
-        } else if allowed_cipher == 'a' {
-            allowed_cipher = c;
-        } else {
-            //cipher have to be the same
-            if c != allowed_cipher {
-                letters_only = 0;
-                break;
+impl Scorer for CustomScorer {
+    fn name(&self) -> String {
+        match self {
+            CustomScorer::Words { name, .. }
+            | CustomScorer::RepeatedPairs { name }
+            | CustomScorer::Palindrome { name } => name.clone(),
+        }
+    }
+
+    fn score(&self, address: &AddressForms) -> (f64, f64) {
+        let address_str = address.lower_case.as_bytes();
+        match self {
+            CustomScorer::Words { words, .. } => {
+                let length = words
+                    .iter()
+                    .filter(|word| address.lower_case.contains(&word.to_lowercase()))
+                    .map(|word| word.len())
+                    .max()
+                    .unwrap_or(0);
+                (length as f64, Dictionary::difficulty(length, false))
+            }
+            CustomScorer::RepeatedPairs { .. } => {
+                let mut longest = 1;
+                for start in 0..39 {
+                    let pair = &address_str[start..start + 2];
+                    let repeats = address_str[start..]
+                        .chunks_exact(2)
+                        .take_while(|chunk| *chunk == pair)
+                        .count();
+                    longest = longest.max(repeats);
+                }
+                let repeats = longest as f64;
+                let difficulty = 16.0f64.powf(2.0 * (repeats - 1.0)) / (41.0 - 2.0 * repeats);
+                (repeats, difficulty.max(1.0))
+            }
+            CustomScorer::Palindrome { .. } => {
+                let mirrored = (0..20)
+                    .take_while(|i| address_str[*i] == address_str[39 - i])
+                    .count() as f64;
+                (mirrored, 16.0f64.powf(mirrored))
             }
         }
     }
+}
 
-    let mut numbers_only = 0;
-    for c in address_str.chars() {
-        if c.is_numeric() {
-            numbers_only += 1;
+/// The categories addresses are scored in, each with a weight its difficulty is multiplied by.
+///
+/// The default registry holds the built-in [`FancyScoreCategory`] scorers with a weight of 1.
+#[derive(Clone)]
+pub struct ScorerRegistry {
+    scorers: Vec<(Arc<dyn Scorer>, f64)>,
+}
+
+impl Default for ScorerRegistry {
+    fn default() -> Self {
+        Self {
+            scorers: FancyScoreCategory::ALL
+                .into_iter()
+                .filter(|category| *category != FancyScoreCategory::HexSpeak)
+                .map(|category| (Arc::new(category) as Arc<dyn Scorer>, 1.0))
+                .collect(),
         }
     }
+}
 
-    let mut short_leading_zeroes = 0;
-    for c in short_address_str.chars() {
-        if c == '0' {
-            short_leading_zeroes += 1;
-        } else {
-            break;
+impl ScorerRegistry {
+    /// Creates a registry without any category.
+    pub fn empty() -> Self {
+        Self {
+            scorers: Vec::new(),
         }
     }
 
-    let mut short_leading_any = 0;
-    let char_start = short_address_str.chars().next().unwrap();
-    for c in short_address_str.chars() {
-        if c == char_start {
-            short_leading_any += 1;
-        } else {
-            break;
+    /// Adds a category with a weight of 1.
+    pub fn register(&mut self, scorer: impl Scorer + 'static) -> Result<(), CrunchError> {
+        let name = scorer.name();
+        if self.names().any(|registered| registered == name) {
+            return Err(CrunchError::InvalidScoring(format!(
+                "{name} is registered twice"
+            )));
         }
+        self.scorers.push((Arc::new(scorer), 1.0));
+        Ok(())
+    }
+
+    /// Replaces the category registered under the same name, keeping its weight.
+    pub fn replace(&mut self, scorer: impl Scorer + 'static) -> Result<(), CrunchError> {
+        let index = self.position(&scorer.name())?;
+        self.scorers[index].0 = Arc::new(scorer);
+        Ok(())
     }
 
-    let mut snake_score = 0.0f64;
-    let mut prev_char = address_str.chars().next().unwrap();
-    for c in address_str.chars() {
-        if c == prev_char {
-            snake_score += 1.0;
+    /// Removes a category.
+    pub fn disable(&mut self, name: &str) -> Result<(), CrunchError> {
+        let index = self.position(name)?;
+        self.scorers.remove(index);
+        Ok(())
+    }
+
+    pub fn set_weight(&mut self, name: &str, weight: f64) -> Result<(), CrunchError> {
+        if !weight.is_finite() || weight <= 0.0 {
+            return Err(CrunchError::InvalidScoring(format!(
+                "weight of {name} must be positive, got {weight}"
+            )));
+        }
+        let index = self.position(name)?;
+        self.scorers[index].1 = weight;
+        Ok(())
+    }
+
+    /// The categories with their weights.
+    pub fn weighted(&self) -> impl Iterator<Item = (&dyn Scorer, f64)> {
+        self.scorers
+            .iter()
+            .map(|(scorer, weight)| (scorer.as_ref(), *weight))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = String> + '_ {
+        self.scorers.iter().map(|(scorer, _)| scorer.name())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scorers.is_empty()
+    }
+
+    fn position(&self, name: &str) -> Result<usize, CrunchError> {
+        self.names()
+            .position(|registered| registered == name)
+            .ok_or_else(|| CrunchError::InvalidScoring(format!("unknown category {name}")))
+    }
+
+    /// Scores the address in every category, the highest weighted difficulty being the total,
+    /// and checks it against the thresholds.
+    pub fn score(&self, address: Address, thresholds: &Thresholds) -> FancyScore {
+        let forms = AddressForms::new(&address);
+        let mut score = FancyScore {
+            address_lower_case: format!("{:#x}", address),
+            address_mixed_case: format!("0x{}", forms.mixed_case),
+            address_short_etherscan: format!(
+                "0x{}...{}",
+                &forms.short_etherscan[..8],
+                &forms.short_etherscan[8..]
+            ),
+            ..Default::default()
+        };
+
+        let score_entries: Vec<FancyScoreEntry> = self
+            .scorers
+            .iter()
+            .map(|(scorer, weight)| {
+                let (value, difficulty) = scorer.score(&forms);
+                FancyScoreEntry {
+                    category: scorer.name(),
+                    score: value,
+                    difficulty,
+                    weight: *weight,
+                    contribution: difficulty * weight,
+                }
+            })
+            .collect();
+
+        let neutral_price_point = thresholds.base_difficulty;
+
+        // This simple method is better than iterator, because of float NaN issues
+        let mut biggest_score = score_entries[0].clone();
+        for entry in score_entries.iter() {
+            if entry.contribution > biggest_score.contribution {
+                biggest_score = entry.clone();
+            }
+        }
+
+        let biggest_score_difficulty = biggest_score.contribution;
+
+        let mut accepted: Option<&FancyScoreEntry> = None;
+        for entry in score_entries.iter() {
+            if entry.contribution >= thresholds.minimum(&entry.category)
+                && !accepted.is_some_and(|accepted| entry.contribution <= accepted.contribution)
+            {
+                accepted = Some(entry);
+            }
+        }
+        score.accepted_by = accepted.map(|entry| entry.category.clone());
+
+        let price_multiplier = if biggest_score_difficulty <= neutral_price_point {
+            1.0
         } else {
-            prev_char = c;
+            biggest_score_difficulty / neutral_price_point
+        };
+
+        score.scores = score_entries
+            .into_iter()
+            .map(|entry| (entry.category.clone(), entry))
+            .collect();
+        score.total_score = biggest_score_difficulty;
+        score.price_multiplier = price_multiplier;
+        score.category = biggest_score.category;
+        score
+    }
+}
+
+/// Scoring of a job: which categories are disabled, their weights, and custom categories.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoringSpec {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub weights: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomScorer>,
+    /// Words scored by the `hex_speak` category, which is only registered with a dictionary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<DictionarySpec>,
+}
+
+impl ScoringSpec {
+    /// Builds the registry of the job on top of the built-in categories.
+    pub fn registry(&self) -> Result<ScorerRegistry, CrunchError> {
+        let mut registry = ScorerRegistry::default();
+        match &self.dictionary {
+            Some(dictionary) if dictionary.is_builtin() => {
+                registry.register(FancyScoreCategory::HexSpeak)?
+            }
+            Some(dictionary) => registry.register(dictionary.dictionary()?)?,
+            None => {}
+        }
+        for scorer in &self.custom {
+            scorer.validate()?;
+            registry.register(scorer.clone())?;
+        }
+        for name in &self.disabled {
+            registry.disable(name)?;
         }
+        for (name, weight) in &self.weights {
+            registry.set_weight(name, *weight)?;
+        }
+        if registry.is_empty() {
+            return Err(CrunchError::InvalidScoring(
+                "at least one category must be enabled".to_string(),
+            ));
+        }
+        Ok(registry)
     }
+}
 
-    let mut score_entries = Vec::new();
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::Random,
-        score: 1.0f64,
-        difficulty: 1000.0f64,
-    });
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::LeadingZeroes,
-        score: leading_zeroes as f64,
-        difficulty: 16.0f64.powf(leading_zeroes as f64),
-    });
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::LeadingAny,
-        score: leading_any as f64 - 1.0_f64,
-        difficulty: 16.0f64.powf(leading_any as f64 - (15. / 16.)),
-    });
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::LettersCount,
-        score: letters_only as f64,
-        difficulty: exactly_letters_combinations_difficulty(letters_only as f64, 40.0),
-    });
-
-    if numbers_only == 40 {
-        let number = address_str.parse::<f64>().unwrap();
-        let max_number = 9999999999999999999999999999999999999999f64;
-        let difficulty1 =
-            total_combinations(40.0) / 10.0f64.powf(numbers_only as f64) / (number / max_number);
-        let difficulty2 = total_combinations(40.0)
-            / 10.0f64.powf(numbers_only as f64)
-            / ((max_number - number) / max_number);
-        score_entries.push(FancyScoreEntry {
-            category: FancyScoreCategory::NumbersOnly,
-            score: numbers_only as f64,
-            difficulty: difficulty1.max(difficulty2),
-        });
-    } else {
-        score_entries.push(FancyScoreEntry {
-            category: FancyScoreCategory::NumbersOnly,
-            score: numbers_only as f64,
-            difficulty: 1.0f64,
-        });
-    }
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::ShortLeadingZeroes,
-        score: short_leading_zeroes as f64,
-        difficulty: 16.0f64.powf(short_leading_zeroes as f64),
-    });
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::ShortLeadingAny,
-        score: short_leading_any as f64,
-        difficulty: 16.0f64.powf(short_leading_any as f64 - (15. / 16.)),
-    });
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::SnakeScore,
-        score: snake_score,
-        difficulty: 16.0f64.powf(snake_score - 9.0),
-    });
-
-    score_entries.push(FancyScoreEntry {
-        category: FancyScoreCategory::LeadingLetters,
-        score: leading_letters as f64,
-        difficulty: 32.0f64.powf(leading_letters as f64 - (15. / 16.)),
-    });
-
-    score.scores = score_entries
-        .iter()
-        .map(|entry| (entry.category.to_string(), entry.clone()))
-        .collect();
-
-    let neutral_price_point = get_base_difficulty();
-
-    // This simple method is better than iterator, because of float NaN issues
-    let mut biggest_score = score_entries[0].clone();
-    for entry in score_entries.iter() {
-        if entry.difficulty > biggest_score.difficulty {
-            biggest_score = entry.clone();
+/// Difficulties addresses are recorded and priced at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thresholds {
+    /// Weighted difficulty an address has to reach in a category to be recorded.
+    pub min_difficulty: f64,
+    /// Difficulty of the addresses priced at the base price, more difficult ones having a price
+    /// multiplier above 1.
+    pub base_difficulty: f64,
+    /// Minimum difficulty of specific categories, in place of `min_difficulty`.
+    pub category_minimums: BTreeMap<String, f64>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            min_difficulty: 16.0f64.powi(8),
+            base_difficulty: 16.0f64.powi(9),
+            category_minimums: BTreeMap::new(),
+        }
+    }
+}
+
+impl Thresholds {
+    /// Weighted difficulty an address has to reach in the category to be recorded.
+    pub fn minimum(&self, category: &str) -> f64 {
+        self.category_minimums
+            .get(category)
+            .copied()
+            .unwrap_or(self.min_difficulty)
+    }
+
+    /// Checks that every difficulty is positive and that the categories are registered.
+    pub fn validate(&self, registry: &ScorerRegistry) -> Result<(), CrunchError> {
+        let difficulties = [
+            ("min difficulty", &self.min_difficulty),
+            ("base difficulty", &self.base_difficulty),
+        ];
+        for (name, difficulty) in difficulties.into_iter().chain(
+            self.category_minimums
+                .iter()
+                .map(|(name, difficulty)| (name.as_str(), difficulty)),
+        ) {
+            if !difficulty.is_finite() || *difficulty <= 0.0 {
+                return Err(CrunchError::InvalidScoring(format!(
+                    "{name} must be positive, got {difficulty}"
+                )));
+            }
+        }
+        for name in self.category_minimums.keys() {
+            registry.position(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Thresholds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min difficulty {}, base difficulty {}",
+            self.min_difficulty, self.base_difficulty
+        )?;
+        for (name, difficulty) in &self.category_minimums {
+            write!(f, ", {name} >= {difficulty}")?;
         }
+        Ok(())
     }
+}
+
+/// Thresholds of a job, each defaulting to its value in [`Thresholds::default`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_difficulty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_difficulty: Option<f64>,
+    /// Minimum difficulty of specific categories.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, f64>,
+}
 
-    let biggest_score_difficulty = biggest_score.difficulty;
+impl ThresholdSpec {
+    /// Overrides the thresholds with every one that is set in `other`.
+    pub fn merge(mut self, other: ThresholdSpec) -> Self {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field;
+                })*
+            };
+        }
+        merge!(min_difficulty, base_difficulty);
+        self.categories.extend(other.categories);
+        self
+    }
 
-    let price_multiplier = if biggest_score_difficulty <= neutral_price_point {
-        1.0
-    } else {
-        biggest_score_difficulty / neutral_price_point
+    pub fn thresholds(&self) -> Thresholds {
+        let defaults = Thresholds::default();
+        Thresholds {
+            min_difficulty: self.min_difficulty.unwrap_or(defaults.min_difficulty),
+            base_difficulty: self.base_difficulty.unwrap_or(defaults.base_difficulty),
+            category_minimums: self.categories.clone(),
+        }
+    }
+}
+
+/// Parses a difficulty given as a number, like `4294967296` or `4.3e9`, or as a power, like
+/// `16^8`.
+pub fn parse_difficulty(value: &str) -> Result<f64, CrunchError> {
+    let invalid = || CrunchError::InvalidScoring(format!("invalid difficulty {value}"));
+    let parse = |number: &str| number.trim().parse::<f64>().map_err(|_| invalid());
+    let difficulty = match value.split_once('^') {
+        Some((base, exponent)) => parse(base)?.powf(parse(exponent)?),
+        None => parse(value)?,
     };
+    match difficulty.is_finite() && difficulty > 0.0 {
+        true => Ok(difficulty),
+        false => Err(invalid()),
+    }
+}
 
-    score.total_score = biggest_score_difficulty;
-    score.price_multiplier = price_multiplier;
-    score.category = biggest_score.category.to_string();
-    score
+/// Parses the minimum difficulty of a category, given as `category=difficulty`.
+pub fn parse_category_minimum(value: &str) -> Result<(String, f64), CrunchError> {
+    let (category, difficulty) = value.split_once('=').ok_or_else(|| {
+        CrunchError::InvalidScoring(format!(
+            "expected a category minimum as category=difficulty, got {value}"
+        ))
+    })?;
+    Ok((category.trim().to_string(), parse_difficulty(difficulty)?))
+}
+
+/// Scores the address in the built-in categories, with the default thresholds.
+pub fn score_fancy(address: Address) -> FancyScore {
+    ScorerRegistry::default().score(address, &Thresholds::default())
 }
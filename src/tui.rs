@@ -0,0 +1,407 @@
+use crate::score::FancyScore;
+use console::{truncate_str, Key, Term};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Hashrate samples kept per device for its sparkline.
+const SPARKLINE_SAMPLES: usize = 32;
+/// Log lines kept for the log pane.
+const LOG_LINES: usize = 8;
+/// Change of the throttle per key press, in seconds.
+const THROTTLE_STEP: f64 = 0.01;
+const REFRESH: Duration = Duration::from_millis(250);
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The dashboard started by `--tui`, shared by the mining threads of every device.
+static DASHBOARD: OnceLock<Dashboard> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct DeviceStats {
+    name: String,
+    processed: u64,
+    /// Hashes per second of the last batches, the latest last.
+    samples: VecDeque<f64>,
+    /// Message and nonce of the last batch.
+    position: String,
+}
+
+/// The most difficult address accepted in a category.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BestResult {
+    /// Checksummed address.
+    pub address: String,
+    pub difficulty: f64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    devices: BTreeMap<u8, DeviceStats>,
+    best: BTreeMap<String, BestResult>,
+    accepted: u64,
+    logs: VecDeque<String>,
+    /// Seconds slept per batch, in place of `sleep_for`.
+    throttle: f64,
+}
+
+/// Live state of a mining run, drawn full-screen in place of the progress lines.
+///
+/// Mining threads report every batch and accepted address to it, while key presses pause and
+/// resume mining or change the time slept per batch.
+#[derive(Debug)]
+pub struct Dashboard {
+    job_id: String,
+    /// Expected number of addresses to check per accepted one, if known.
+    expected: Option<f64>,
+    started: Instant,
+    paused: AtomicBool,
+    stopped: AtomicBool,
+    state: Mutex<State>,
+    /// Held while drawing, so that the terminal is not restored in the middle of a frame.
+    drawing: Mutex<()>,
+}
+
+impl Dashboard {
+    pub fn new(job_id: impl Into<String>, expected: Option<f64>, throttle: f64) -> Self {
+        Self {
+            job_id: job_id.into(),
+            expected,
+            started: Instant::now(),
+            paused: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            state: Mutex::new(State {
+                throttle,
+                ..Default::default()
+            }),
+            drawing: Mutex::new(()),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn add_device(&self, device: u8, name: impl Into<String>) {
+        self.state().devices.entry(device).or_default().name = name.into();
+    }
+
+    /// Records a batch of `hashes` a device checked in `elapsed`, at `position`.
+    pub fn batch(&self, device: u8, hashes: u64, elapsed: Duration, position: impl Into<String>) {
+        let mut state = self.state();
+        let stats = state.devices.entry(device).or_default();
+        stats.processed += hashes;
+        stats.position = position.into();
+        if !elapsed.is_zero() {
+            if stats.samples.len() == SPARKLINE_SAMPLES {
+                stats.samples.pop_front();
+            }
+            stats
+                .samples
+                .push_back(hashes as f64 / elapsed.as_secs_f64());
+        }
+    }
+
+    /// Records an accepted address, keeping it if it is the most difficult of its category.
+    pub fn accepted(&self, score: &FancyScore) {
+        let mut state = self.state();
+        state.accepted += 1;
+        let best = BestResult {
+            address: score.address_mixed_case.clone(),
+            difficulty: score.total_score,
+        };
+        match state.best.get(&score.category) {
+            Some(current) if current.difficulty >= best.difficulty => {}
+            _ => {
+                state.best.insert(score.category.clone(), best);
+            }
+        }
+    }
+
+    pub fn log(&self, line: impl Into<String>) {
+        let mut state = self.state();
+        if state.logs.len() == LOG_LINES {
+            state.logs.pop_front();
+        }
+        state.logs.push_back(line.into());
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Seconds slept per batch.
+    pub fn throttle(&self) -> f64 {
+        self.state().throttle
+    }
+
+    /// Hashes checked per second by every device, as of their last batch.
+    pub fn hashrate(&self) -> f64 {
+        self.state()
+            .devices
+            .values()
+            .filter_map(|stats| stats.samples.back())
+            .fold(0.0, |total, hashrate| total + hashrate)
+    }
+
+    pub fn processed(&self) -> u64 {
+        self.state()
+            .devices
+            .values()
+            .map(|stats| stats.processed)
+            .sum()
+    }
+
+    pub fn best(&self) -> BTreeMap<String, BestResult> {
+        self.state().best.clone()
+    }
+
+    /// Applies a key press: `p` or space pauses and resumes, `+` and `-` change the throttle.
+    /// Returns `false` if the key quits.
+    pub fn handle_key(&self, key: &Key) -> bool {
+        match key {
+            Key::Char('q') | Key::Char('Q') | Key::Escape => return false,
+            Key::Char('p') | Key::Char('P') | Key::Char(' ') => {
+                self.paused.fetch_xor(true, Ordering::Relaxed);
+            }
+            Key::Char('+') | Key::Char('=') => self.state().throttle += THROTTLE_STEP,
+            Key::Char('-') | Key::Char('_') => {
+                let mut state = self.state();
+                state.throttle = (state.throttle - THROTTLE_STEP).max(0.0);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Lays out the dashboard in lines at most `width` characters wide.
+    pub fn render(&self, width: usize) -> Vec<String> {
+        let hashrate = self.hashrate();
+        let processed = self.processed();
+        let state = self.state();
+        let mut lines = vec![
+            format!(
+                "createXcrunch job {} | {} {} | throttle {:.2}s",
+                self.job_id,
+                match self.is_paused() {
+                    true => "PAUSED",
+                    false => "running",
+                },
+                format_elapsed(self.started.elapsed()),
+                state.throttle
+            ),
+            String::new(),
+            format!(
+                "{:<16} {:>12} {:>10}  {:<28} History",
+                "Device", "Hashrate", "Processed", "Position"
+            ),
+        ];
+        for (device, stats) in &state.devices {
+            let samples: Vec<f64> = stats.samples.iter().copied().collect();
+            lines.push(format!(
+                "{:<16} {:>12} {:>10}  {:<28} {}",
+                truncate_str(&format!("{device} {}", stats.name), 16, "…"),
+                format_hashrate(samples.last().copied().unwrap_or_default()),
+                format_hashes(stats.processed),
+                stats.position,
+                sparkline(&samples)
+            ));
+        }
+        lines.push(format!(
+            "{:<16} {:>12} {:>10}  {} accepted",
+            "Total",
+            format_hashrate(hashrate),
+            format_hashes(processed),
+            state.accepted
+        ));
+        lines.push(match self.expected {
+            Some(expected) if hashrate > 0.0 => format!(
+                "Next hit expected in {} (1 in {:.4e} addresses)",
+                format_eta(expected / hashrate),
+                expected
+            ),
+            Some(expected) => format!("Next hit: 1 in {expected:.4e} addresses"),
+            None => "Next hit: no difficulty estimate".to_string(),
+        });
+
+        lines.push(String::new());
+        lines.push(format!(
+            "{:<20} {:>12}  Address",
+            "Best by category", "Difficulty"
+        ));
+        let mut best: Vec<(&String, &BestResult)> = state.best.iter().collect();
+        best.sort_by(|a, b| b.1.difficulty.total_cmp(&a.1.difficulty));
+        for (category, result) in best {
+            lines.push(format!(
+                "{:<20} {:>12.4e}  {}",
+                category, result.difficulty, result.address
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push("Log".to_string());
+        lines.extend(state.logs.iter().cloned());
+        lines.push(String::new());
+        lines.push("[p] pause/resume  [+/-] throttle  [q] quit".to_string());
+
+        lines
+            .into_iter()
+            .map(|line| truncate_str(&line, width, "…").into_owned())
+            .collect()
+    }
+
+    fn draw(&self, term: &Term) -> io::Result<()> {
+        let _drawing = self.drawing.lock().unwrap_or_else(|e| e.into_inner());
+        if self.stopped.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let (rows, columns) = term.size();
+        term.move_cursor_to(0, 0)?;
+        for line in self
+            .render(columns as usize)
+            .iter()
+            .take((rows as usize).saturating_sub(1))
+        {
+            term.clear_line()?;
+            term.write_line(line)?;
+        }
+        term.clear_to_end_of_screen()
+    }
+}
+
+/// Renders samples as a line of bars scaled to the highest one.
+pub fn sparkline(samples: &[f64]) -> String {
+    let max = samples.iter().copied().fold(0.0, f64::max);
+    samples
+        .iter()
+        .map(|sample| match max > 0.0 {
+            true => SPARKS[((sample / max) * (SPARKS.len() - 1) as f64).round() as usize],
+            false => SPARKS[0],
+        })
+        .collect()
+}
+
+fn format_hashrate(hashrate: f64) -> String {
+    format!("{:.1}MH/s", hashrate / 1e6)
+}
+
+fn format_hashes(hashes: u64) -> String {
+    format!("{:.1}GH", hashes as f64 / 1e9)
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Formats a number of seconds at the precision it is meaningful at, e.g. `3.5s` or `2h 05m`.
+pub fn format_eta(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        return "never".to_string();
+    }
+    let whole = seconds as u64;
+    match whole {
+        0..=59 => format!("{seconds:.1}s"),
+        60..=3599 => format!("{}m {:02}s", whole / 60, whole % 60),
+        3600..=86399 => format!("{}h {:02}m", whole / 3600, whole / 60 % 60),
+        _ => format!("{}d {:02}h", whole / 86400, whole / 3600 % 24),
+    }
+}
+
+/// The running dashboard, if `--tui` started one.
+pub fn active() -> Option<&'static Dashboard> {
+    DASHBOARD
+        .get()
+        .filter(|dashboard| !dashboard.stopped.load(Ordering::Relaxed))
+}
+
+/// Takes over the terminal with the dashboard, redrawing it and reading keys in the background
+/// until it is stopped or quit.
+pub fn start(dashboard: Dashboard) -> &'static Dashboard {
+    let dashboard = DASHBOARD.get_or_init(|| dashboard);
+    let term = Term::stdout();
+    let _ = term.hide_cursor();
+    let _ = term.clear_screen();
+
+    thread::spawn(move || {
+        let term = Term::stdout();
+        while active().is_some() {
+            if dashboard.draw(&term).is_err() {
+                break;
+            }
+            thread::sleep(REFRESH);
+        }
+    });
+    thread::spawn(move || {
+        let term = Term::stdout();
+        while let Ok(key) = term.read_key() {
+            if !dashboard.handle_key(&key) {
+                stop();
+                std::process::exit(0);
+            }
+        }
+    });
+    dashboard
+}
+
+/// Restores the terminal and prints the last log lines of the dashboard, if one is running.
+pub fn stop() {
+    let Some(dashboard) = active() else {
+        return;
+    };
+    let _drawing = dashboard.drawing.lock().unwrap_or_else(|e| e.into_inner());
+    dashboard.stopped.store(true, Ordering::Relaxed);
+    let term = Term::stdout();
+    let _ = term.clear_screen();
+    let _ = term.show_cursor();
+    for line in &dashboard.state().logs {
+        eprintln!("{line}");
+    }
+}
+
+/// Blocks while mining is paused from the dashboard.
+pub(crate) fn wait_while_paused() {
+    while active().is_some_and(Dashboard::is_paused) {
+        thread::sleep(REFRESH);
+    }
+}
+
+/// Time to sleep per batch: the throttle of the dashboard, or `sleep_for` without one.
+pub(crate) fn sleep_for(sleep_for: f64) -> Duration {
+    let seconds = active().map_or(sleep_for, Dashboard::throttle);
+    Duration::from_secs_f64(seconds)
+}
+
+/// Log target showing log lines in the dashboard while it runs, and on standard error otherwise.
+pub struct LogPane;
+
+impl Write for LogPane {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match active() {
+            Some(dashboard) => {
+                for line in String::from_utf8_lossy(buf).lines() {
+                    if !line.trim().is_empty() {
+                        dashboard.log(line);
+                    }
+                }
+                Ok(buf.len())
+            }
+            None => io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
@@ -0,0 +1,106 @@
+use createxcrunch::{
+    best::{BestMetric, SharedBest},
+    job::Job,
+    mk_kernel_src, ConfigBuilder, CrunchError, RewardVariant,
+};
+use rstest::*;
+
+/// 9 leading zero nibbles and 5 zero bytes.
+const ZEROS: [u8; 20] = [
+    0, 0, 0, 0, 0x0c, 0x6c, 0x46, 0xeb, 0xf3, 0x53, 0xa5, 0x2b, 0xdb, 0, 0xb7, 0x08, 0xc2, 0x3d,
+    0x0c, 0x81,
+];
+
+#[rstest]
+#[case(BestMetric::LeadingZeros, 9.0)]
+#[case(BestMetric::TotalZeros, 5.0)]
+fn test_measure(#[case] metric: BestMetric, #[case] expected: f64) {
+    let config = ConfigBuilder::new().best(metric).build().unwrap();
+    assert_eq!(metric.measure(&config, &ZEROS), expected);
+}
+
+#[rstest]
+fn test_measure_fancy() {
+    let config = ConfigBuilder::new()
+        .best(BestMetric::Fancy)
+        .build()
+        .unwrap();
+    assert_eq!(
+        BestMetric::Fancy.measure(&config, &ZEROS),
+        config.score(&ZEROS).total_score
+    );
+}
+
+#[rstest]
+fn test_difficulty() {
+    assert_eq!(BestMetric::LeadingZeros.difficulty(9.0), 16f64.powi(9));
+    assert_eq!(BestMetric::Fancy.difficulty(1e12), 1e12);
+    // one in 256 bytes is zero, so a zero byte among 20 is found every ~13 addresses
+    let difficulty = BestMetric::TotalZeros.difficulty(1.0);
+    assert!((difficulty - 1.0 / (1.0 - (255f64 / 256.0).powi(20))).abs() < 1e-9);
+}
+
+#[rstest]
+#[case(BestMetric::LeadingZeros, Some("leadingZeroNibbles(d)"))]
+#[case(BestMetric::TotalZeros, Some("zeroBytes(d)"))]
+#[case(BestMetric::Fancy, None)]
+fn test_best_source(#[case] metric: BestMetric, #[case] expected: Option<&str>) {
+    let config = ConfigBuilder::new().best(metric).build().unwrap();
+    let src = mk_kernel_src(&config);
+    match expected {
+        Some(function) => {
+            assert!(src.contains(&format!("#define BEST_METRIC(d) {function}")));
+            assert!(src.contains("#define SUCCESS_CONDITION() improvesBest(digest, best)"));
+        }
+        // the fancy predicate is checked against a minimum raised by the host
+        None => {
+            assert!(!src.contains("#define BEST_METRIC"));
+            assert!(src.contains("#define SUCCESS_CONDITION() isFancy(digest)"));
+        }
+    }
+}
+
+#[rstest]
+fn test_best_without_reward() {
+    let err = ConfigBuilder::new()
+        .best(BestMetric::LeadingZeros)
+        .reward(RewardVariant::LeadingZeros { zeros_threshold: 4 })
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidJob(_)));
+
+    let err = ConfigBuilder::new()
+        .best(BestMetric::LeadingZeros)
+        .nonce_range(0..1000)
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidJob(_)));
+}
+
+#[rstest]
+fn test_best_job() {
+    let job = Job::from_toml("best = \"total_zeros\"").unwrap();
+    assert_eq!(job.best, Some(BestMetric::TotalZeros));
+    assert_eq!(job, Job::from_toml(&job.to_toml()).unwrap());
+
+    let job = Job::default().merge(job);
+    let configs = job.configs().unwrap();
+    assert_eq!(configs[0].best, Some(BestMetric::TotalZeros));
+}
+
+#[rstest]
+fn test_shared_best() {
+    let shared_best = SharedBest::default();
+    let devices = [shared_best.clone(), shared_best.clone()];
+    assert_eq!(shared_best.get(), None);
+
+    // the first measured address is the best, later ones have to beat the best of every device
+    assert!(devices[0].raise(4.0));
+    assert!(!devices[1].raise(4.0));
+    assert!(!devices[1].raise(3.0));
+    assert!(devices[1].raise(5.0));
+    assert!(!devices[0].raise(4.5));
+    assert_eq!(shared_best.get(), Some(5.0));
+}
@@ -0,0 +1,4 @@
+/// Left-pads a hex word to 32 bytes.
+pub fn word(value: &str) -> String {
+    format!("{value:0>64}")
+}
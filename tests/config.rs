@@ -0,0 +1,207 @@
+use createxcrunch::{parse_nibble, Config, CrunchError, RewardVariant};
+use rstest::*;
+
+const FACTORY: &str = "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+
+fn try_config(
+    factory: &str,
+    caller: Option<&str>,
+    init_code_hash: Option<&str>,
+    reward: RewardVariant,
+) -> Result<(), CrunchError> {
+    Config::new(
+        0,
+        1,
+        1,
+        0.0,
+        factory,
+        caller,
+        None,
+        init_code_hash,
+        reward,
+        "output.txt",
+    )
+    .map(|_| ())
+}
+
+#[rstest]
+fn test_valid_config() {
+    try_config(
+        FACTORY,
+        Some("0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5"),
+        Some("0x0000000000000000000000000000000000000000000000000000000000000000"),
+        RewardVariant::LeadingZeros {
+            zeros_threshold: 20,
+        },
+    )
+    .unwrap();
+}
+
+#[rstest]
+fn test_invalid_config() {
+    let reward = || RewardVariant::LeadingZeros { zeros_threshold: 1 };
+
+    let err = try_config("0xzz", None, None, reward()).unwrap_err();
+    assert!(matches!(
+        err,
+        CrunchError::InvalidHex {
+            field: "factory address",
+            ..
+        }
+    ));
+    assert_eq!(err.exit_code(), 3);
+
+    let err = try_config(FACTORY, None, Some("0x00"), reward()).unwrap_err();
+    assert!(matches!(
+        err,
+        CrunchError::InvalidLength {
+            field: "init code hash",
+            expected: 32,
+            actual: 1
+        }
+    ));
+
+    let err = try_config(
+        "0xBA5ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        None,
+        None,
+        reward(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        CrunchError::InvalidChecksum {
+            field: "factory address"
+        }
+    ));
+
+    let err = try_config(
+        FACTORY,
+        None,
+        None,
+        RewardVariant::TotalZeros {
+            zeros_threshold: 21,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        CrunchError::InvalidThreshold { value: 21, .. }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "threshold must be between 1 and 20, got 21"
+    );
+
+    let err = try_config(
+        FACTORY,
+        None,
+        None,
+        RewardVariant::TrailingNibbles {
+            count: 41,
+            nibble: Some(0),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "threshold must be between 1 and 40, got 41"
+    );
+
+    let err = try_config(
+        FACTORY,
+        None,
+        None,
+        RewardVariant::LeadingNibbles {
+            count: 8,
+            nibble: Some(16),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidPattern(_)));
+
+    let err = try_config(
+        FACTORY,
+        None,
+        None,
+        RewardVariant::Matching {
+            pattern: "ba5ed".to_owned().into_boxed_str(),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidPattern(_)));
+}
+
+#[rstest]
+#[case(RewardVariant::LeadingZeros { zeros_threshold: 2 }, "0000ff0000000000000000000000000000000011", true)]
+#[case(RewardVariant::LeadingZeros { zeros_threshold: 3 }, "0000ff0000000000000000000000000000000011", false)]
+#[case(RewardVariant::TotalZeros { zeros_threshold: 18 }, "0000ff0000000000000000000000000000000011", true)]
+#[case(RewardVariant::LeadingAndTotalZeros { leading_zeros_threshold: 3, total_zeros_threshold: 18 }, "0000ff0000000000000000000000000000000011", false)]
+#[case(RewardVariant::LeadingOrTotalZeros { leading_zeros_threshold: 3, total_zeros_threshold: 18 }, "0000ff0000000000000000000000000000000011", true)]
+#[case(RewardVariant::Matching { pattern: format!("ba5eXX{}", "X".repeat(34)).into() }, "ba5ed099633d3b313e4d5f7bdc1305d3c28ba5ed", true)]
+#[case(RewardVariant::Matching { pattern: format!("ba5eXX{}", "X".repeat(34)).into() }, "ba5fd099633d3b313e4d5f7bdc1305d3c28ba5ed", false)]
+#[case(RewardVariant::LeadingAny { group: 255, leading: 7, ones: 255, ints: 255 }, "1111111a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e", true)]
+#[case(RewardVariant::LeadingAny { group: 255, leading: 8, ones: 255, ints: 255 }, "1111111a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e", false)]
+#[case(RewardVariant::LeadingNibbles { count: 5, nibble: Some(0) }, "00000f0000000000000000000000000000000011", true)]
+#[case(RewardVariant::LeadingNibbles { count: 6, nibble: Some(0) }, "00000f0000000000000000000000000000000011", false)]
+#[case(RewardVariant::LeadingNibbles { count: 5, nibble: Some(0xf) }, "00000f0000000000000000000000000000000011", false)]
+#[case(RewardVariant::LeadingNibbles { count: 7, nibble: None }, "fffffff1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7", true)]
+#[case(RewardVariant::TrailingNibbles { count: 3, nibble: Some(1) }, "00000f0000000000000000000000000000000111", true)]
+#[case(RewardVariant::TrailingNibbles { count: 4, nibble: Some(1) }, "00000f0000000000000000000000000000000111", false)]
+#[case(RewardVariant::TrailingNibbles { count: 3, nibble: None }, "00000f0000000000000000000000000000000111", true)]
+#[case(RewardVariant::TrailingNibbles { count: 40, nibble: None }, "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee", true)]
+// the first eight nibbles repeat at the end
+#[case(RewardVariant::LeadingAny { group: 255, leading: 255, ones: 255, ints: 255 }, "12ab34cd2b3c4d5e6f7a8b9c0d1e2f3a12ab34cd", true)]
+fn test_reward_matches(
+    #[case] reward: RewardVariant,
+    #[case] address: &str,
+    #[case] expected: bool,
+) {
+    let address: [u8; 20] = alloy_primitives::hex::decode(address)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(reward.matches(&address), expected);
+}
+
+#[rstest]
+#[case(RewardVariant::LeadingZeros { zeros_threshold: 4 }, 16f64.powi(8))]
+#[case(RewardVariant::LeadingNibbles { count: 9, nibble: Some(0) }, 16f64.powi(9))]
+#[case(RewardVariant::TrailingNibbles { count: 9, nibble: None }, 16f64.powi(8))]
+#[case(RewardVariant::Matching { pattern: format!("ba5ed{}", "X".repeat(35)).into() }, 16f64.powi(5))]
+// 20 bytes, each zero with a probability of 1/256
+#[case(RewardVariant::TotalZeros { zeros_threshold: 1 }, 1.0 / (1.0 - (255f64 / 256.0).powi(20)))]
+#[case(RewardVariant::LeadingAndTotalZeros { leading_zeros_threshold: 2, total_zeros_threshold: 2 }, 16f64.powi(4))]
+#[case(RewardVariant::LeadingOrTotalZeros { leading_zeros_threshold: 1, total_zeros_threshold: 1 }, 1.0 / (1.0 - (255f64 / 256.0).powi(20)))]
+fn test_reward_difficulty(#[case] reward: RewardVariant, #[case] expected: f64) {
+    let difficulty = reward.difficulty().unwrap();
+    assert!(
+        (difficulty - expected).abs() < expected * 1e-9,
+        "{difficulty} != {expected}"
+    );
+}
+
+#[rstest]
+fn test_reward_difficulty_unknown() {
+    assert_eq!(RewardVariant::Fancy.difficulty(), None);
+}
+
+#[rstest]
+#[case("0", Some(0))]
+#[case("f", Some(15))]
+#[case("A", Some(10))]
+#[case("any", None)]
+fn test_parse_nibble(#[case] value: &str, #[case] expected: Option<u8>) {
+    assert_eq!(parse_nibble(value).unwrap(), expected);
+}
+
+#[rstest]
+#[case("")]
+#[case("g")]
+#[case("ff")]
+fn test_parse_nibble_invalid(#[case] value: &str) {
+    assert!(matches!(
+        parse_nibble(value),
+        Err(CrunchError::InvalidPattern(_))
+    ));
+}
@@ -0,0 +1,187 @@
+use createxcrunch::{
+    db::{to_csv, to_json, Query, ResultsDb},
+    job::Job,
+    record::ResultRecord,
+    Config, ConfigBuilder, CrunchError,
+};
+use rstest::*;
+use std::path::PathBuf;
+
+/// 10 leading zeros, a difficulty of 16^10.
+const ZEROS: [u8; 20] = [
+    0, 0, 0, 0, 0, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d,
+    0x0c, 0x81,
+];
+
+const PLAIN: [u8; 20] = [
+    0x88, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d, 0x0c, 0x81,
+    0x0a, 0x1b, 0x2c, 0x3d,
+];
+
+fn record(address: [u8; 20], salt: u8) -> ResultRecord {
+    ResultRecord {
+        salt: [salt; 32],
+        address,
+        factory: [0x22; 20],
+        miner: "0.3.0_0".to_string(),
+        job_id: "abcd".to_string(),
+        init_code_hash: Some([0x33; 32]),
+        init_code_source: "hash".to_string(),
+        acceptance: "leading_zeroes>=4294967296".to_string(),
+    }
+}
+
+fn config() -> Config {
+    ConfigBuilder::new().build().unwrap()
+}
+
+fn database(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-test-db-{name}"));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.join("results.db")
+}
+
+#[rstest]
+fn test_insert() {
+    let config = config();
+    let db = ResultsDb::open(database("insert")).unwrap();
+    assert!(db.insert(&config, &record(ZEROS, 0x11)).unwrap());
+    // rediscovered results are stored once
+    assert!(!db.insert(&config, &record(ZEROS, 0x11)).unwrap());
+    assert!(db.insert(&config, &record(ZEROS, 0x12)).unwrap());
+    assert!(db.insert(&config, &record(PLAIN, 0x11)).unwrap());
+
+    let results = db.query(&Query::default()).unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].record, record(ZEROS, 0x11));
+    assert_eq!(results[0].category, "leading_zeroes");
+    assert_eq!(results[0].difficulty, 16f64.powi(10));
+    assert!(results[0].scores.contains("\"leading_zeroes\""));
+    assert_eq!(results[0].uploaded_at, None);
+    assert_eq!(results[2].record.address, PLAIN);
+}
+
+#[rstest]
+fn test_query() {
+    let config = config();
+    let db = ResultsDb::open(database("query")).unwrap();
+    for result in [
+        record(ZEROS, 0x11),
+        record(ZEROS, 0x12),
+        record(PLAIN, 0x11),
+    ] {
+        db.insert(&config, &result).unwrap();
+    }
+    let count = |query: Query| db.query(&query).unwrap().len();
+
+    let address = |address| Query {
+        address: Some(address),
+        ..Default::default()
+    };
+    assert_eq!(count(address(ZEROS)), 2);
+    assert_eq!(count(address([0x99; 20])), 0);
+    assert_eq!(
+        count(Query {
+            category: Some("leading_zeroes".to_string()),
+            ..Default::default()
+        }),
+        2
+    );
+    assert_eq!(
+        count(Query {
+            min_difficulty: Some(16f64.powi(9)),
+            ..Default::default()
+        }),
+        2
+    );
+    assert_eq!(
+        count(Query {
+            job_id: Some("ef01".to_string()),
+            ..Default::default()
+        }),
+        0
+    );
+    assert_eq!(
+        count(Query {
+            limit: Some(1),
+            ..Default::default()
+        }),
+        1
+    );
+
+    assert_eq!(db.mark_uploaded(&ZEROS).unwrap(), 2);
+    assert_eq!(db.mark_uploaded(&ZEROS).unwrap(), 0);
+    let pending = db
+        .query(&Query {
+            pending: true,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].record.address, PLAIN);
+    assert!(db.query(&address(ZEROS)).unwrap()[0].uploaded_at.is_some());
+}
+
+#[rstest]
+fn test_jobs() {
+    let path = database("jobs");
+    let job = Job::from_toml("chain_id = 1\n").unwrap();
+    let db = ResultsDb::open(&path).unwrap();
+    db.insert_job(&job).unwrap();
+    db.insert_job(&job).unwrap();
+    drop(db);
+
+    // the database persists across runs
+    let db = ResultsDb::open(&path).unwrap();
+    assert_eq!(db.job(&job.id()).unwrap(), Some(job.to_toml()));
+    assert_eq!(db.job("abcd").unwrap(), None);
+}
+
+#[rstest]
+fn test_db_formats() {
+    let config = config();
+    let db = ResultsDb::open(database("formats")).unwrap();
+    db.insert(&config, &record(ZEROS, 0x11)).unwrap();
+    let results = db.query(&Query::default()).unwrap();
+
+    let csv = to_csv(&results);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "salt,address,factory,miner,job_id,init_code_hash,init_code_source,acceptance,category,difficulty,found_at,uploaded_at"
+    );
+    assert!(lines[1].starts_with(&format!(
+        "{},leading_zeroes,{},",
+        record(ZEROS, 0x11),
+        16f64.powi(10)
+    )));
+    assert!(lines[1].ends_with(','));
+
+    let json: serde_json::Value = serde_json::from_str(to_json(&results).trim()).unwrap();
+    assert_eq!(
+        json["address"],
+        "0x0000000000c6c46ebf353a52bdbab708c23d0c81"
+    );
+    assert_eq!(json["scores"]["leading_zeroes"]["score"], 10.0);
+    assert_eq!(json["uploaded_at"], serde_json::Value::Null);
+}
+
+#[rstest]
+fn test_database_job() {
+    let job = Job::from_toml("database = \"output/results.db\"").unwrap();
+    assert_eq!(job, Job::from_toml(&job.to_toml()).unwrap());
+    assert_eq!(
+        job.configs().unwrap()[0].database.as_deref(),
+        Some("output/results.db")
+    );
+}
+
+#[rstest]
+fn test_database_error() {
+    let dir = std::env::temp_dir().join("createxcrunch-test-db-error");
+    std::fs::create_dir_all(&dir).unwrap();
+    // a directory cannot be opened as a database
+    let err = ResultsDb::open(&dir).map(|_| ()).unwrap_err();
+    assert!(matches!(err, CrunchError::Database(_)));
+    assert_eq!(err.exit_code(), 25);
+}
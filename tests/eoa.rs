@@ -0,0 +1,82 @@
+use alloy_primitives::hex;
+use createxcrunch::{eoa, CrunchError, RewardVariant};
+use rstest::*;
+use secp256k1::{Secp256k1, SecretKey};
+
+/// Private key 1, whose public key is the generator.
+const ONE: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+const GENERATOR: &str = "0x0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+fn offset(n: u8) -> [u8; 32] {
+    let mut offset = [0u8; 32];
+    offset[31] = n;
+    offset
+}
+
+#[rstest]
+fn test_address() {
+    let public_key = eoa::parse_public_key(GENERATOR).unwrap();
+    assert_eq!(
+        hex::encode(eoa::address(&public_key)),
+        "7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+    );
+    assert_eq!(
+        eoa::parse_secret_key(ONE)
+            .unwrap()
+            .public_key(&Secp256k1::signing_only()),
+        public_key
+    );
+}
+
+#[rstest]
+fn test_search_and_combine() {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+    let public_key = secret_key.public_key(&secp);
+
+    // the address of pub + 5·G, with everything but the first two bytes masked out
+    let target = eoa::address(&eoa::offset_public_key(&public_key, &offset(5)).unwrap());
+    let pattern = format!("{}{}", hex::encode(&target[..2]), "X".repeat(36));
+    let reward = RewardVariant::Matching {
+        pattern: pattern.into(),
+    };
+
+    let solutions = eoa::search(
+        &public_key,
+        |address| reward.matches(address),
+        &offset(1),
+        16,
+    )
+    .unwrap();
+    let solution = solutions
+        .iter()
+        .find(|solution| solution.offset == offset(5))
+        .unwrap();
+    assert_eq!(solution.address, target);
+
+    // the miner only learns the offsets, the owner of the key combines them
+    for solution in solutions {
+        let combined = eoa::combine(&secret_key, &solution.offset).unwrap();
+        assert_eq!(eoa::address(&combined.public_key(&secp)), solution.address);
+    }
+}
+
+#[rstest]
+#[case(GENERATOR.replace("0x02", "0x05"))]
+#[case("0x1234".to_string())]
+fn test_invalid_public_key(#[case] value: String) {
+    let err = eoa::parse_public_key(&value).unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidKey(_)));
+    assert_eq!(err.exit_code(), 21);
+}
+
+#[rstest]
+fn test_invalid_secret_key() {
+    // zero is not a valid private key
+    let err = eoa::parse_secret_key(&format!("0x{}", "00".repeat(32))).unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidKey(_)));
+    assert!(matches!(
+        eoa::parse_secret_key("0x01"),
+        Err(CrunchError::InvalidLength { .. })
+    ));
+}
@@ -0,0 +1,120 @@
+use createxcrunch::{
+    events::{Event, EVENTS_VERSION},
+    record::ResultRecord,
+    score::{ScorerRegistry, Thresholds},
+    CrunchError,
+};
+use rstest::*;
+use serde_json::{json, Value};
+use std::time::Duration;
+use web3::types::Address;
+
+/// 10 leading zeros, a difficulty of 16^10.
+const ZEROS: [u8; 20] = [
+    0, 0, 0, 0, 0, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d,
+    0x0c, 0x81,
+];
+
+fn parse(event: &Event) -> Value {
+    let line = event.to_json(1_700_000_000_000);
+    assert!(!line.contains('\n'));
+    serde_json::from_str(&line).unwrap()
+}
+
+#[rstest]
+fn test_started() {
+    let event = Event::Started {
+        job_id: "abcd".to_string(),
+        devices: vec![0, 1],
+        output_dir: "output".to_string(),
+        expected_difficulty: None,
+    };
+    assert_eq!(
+        parse(&event),
+        json!({
+            "version": EVENTS_VERSION,
+            "timestamp_ms": 1_700_000_000_000u64,
+            "event": "started",
+            "job_id": "abcd",
+            "devices": [0, 1],
+            "output_dir": "output",
+            "expected_difficulty": null,
+        })
+    );
+}
+
+#[rstest]
+fn test_progress() {
+    let event = Event::progress(
+        Some(1),
+        3_000_000_000,
+        1_000_000_000,
+        Duration::from_millis(500),
+        "message 0a1b2c3d nonce 7",
+    );
+    let json = parse(&event);
+    assert_eq!(json["event"], "progress");
+    assert_eq!(json["device"], 1);
+    assert_eq!(json["processed"], 3_000_000_000u64);
+    assert_eq!(json["hashes"], 1_000_000_000u64);
+    assert_eq!(json["elapsed_ms"], 500);
+    assert_eq!(json["hashrate"], 2e9);
+    assert_eq!(json["position"], "message 0a1b2c3d nonce 7");
+
+    // the CPU search has no device, and a batch can take no measurable time
+    let json = parse(&Event::progress(None, 0, 0, Duration::ZERO, ""));
+    assert_eq!(json["device"], Value::Null);
+    assert_eq!(json["hashrate"], 0.0);
+}
+
+#[rstest]
+fn test_solution() {
+    let record = ResultRecord {
+        salt: [0x11; 32],
+        address: ZEROS,
+        factory: [0x22; 20],
+        miner: "0.3.0_0".to_string(),
+        job_id: "abcd".to_string(),
+        init_code_hash: None,
+        init_code_source: "hash".to_string(),
+        acceptance: "leading_zeroes>=4294967296".to_string(),
+    };
+    let score =
+        ScorerRegistry::default().score(Address::from_slice(&ZEROS), &Thresholds::default());
+    let json = parse(&Event::solution(Some(0), &record, &score));
+    assert_eq!(json["event"], "solution");
+    assert_eq!(json["device"], 0);
+    assert_eq!(
+        json["address"],
+        "0x0000000000c6c46EBF353a52bdBAb708C23D0c81"
+    );
+    assert_eq!(json["salt"], format!("0x{}", "11".repeat(32)));
+    assert_eq!(json["factory"], format!("0x{}", "22".repeat(20)));
+    assert_eq!(json["job_id"], "abcd");
+    assert_eq!(json["acceptance"], "leading_zeroes>=4294967296");
+    assert_eq!(json["category"], "leading_zeroes");
+    assert_eq!(json["difficulty"], 16f64.powi(10));
+}
+
+#[rstest]
+fn test_errors() {
+    let error = CrunchError::DeviceNotFound {
+        index: 2,
+        available: 1,
+    };
+    let json = parse(&Event::device_error(2, &error));
+    assert_eq!(json["event"], "device_error");
+    assert_eq!(json["device"], 2);
+    assert_eq!(json["error"], error.to_string());
+    assert_eq!(json["exit_code"], error.exit_code());
+
+    let json = parse(&Event::stopped(&Err(error)));
+    assert_eq!(json["event"], "stopped");
+    assert_eq!(json["reason"], "error");
+    assert_eq!(json["exit_code"], 11);
+
+    let json = parse(&Event::stopped(&Ok(())));
+    assert_eq!(json["reason"], "completed");
+    assert_eq!(json["error"], Value::Null);
+    assert_eq!(json["exit_code"], 0);
+}
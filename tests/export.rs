@@ -0,0 +1,216 @@
+mod common;
+
+use alloy_primitives::hex;
+use common::word;
+use createxcrunch::{
+    export::{clone_init_code, parse_amount, CreateXCall, Target},
+    factory::FactoryKind,
+    record::ResultRecord,
+    ConfigBuilder, CrunchError, SaltVariant,
+};
+use rstest::*;
+use web3::types::U256;
+
+const CREATEX: &str = "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+const CALLER: &str = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5";
+
+fn salt() -> [u8; 32] {
+    [0x11; 32]
+}
+
+#[rstest]
+fn test_deploy_create2_calldata() {
+    let call = CreateXCall::new(
+        salt(),
+        Target::Create2 {
+            init_code: vec![0x60, 0x00],
+        },
+    );
+
+    let expected = [
+        "26307668".to_string(),
+        "11".repeat(32),
+        word("40"),
+        word("2"),
+        format!("{:0<64}", "6000"),
+    ]
+    .concat();
+    assert_eq!(call.signature(), "deployCreate2(bytes32,bytes)");
+    assert_eq!(hex::encode(call.calldata()), expected);
+    assert_eq!(call.value(), U256::zero());
+}
+
+#[rstest]
+fn test_deploy_create3_and_init_calldata() {
+    let call = CreateXCall {
+        init_data: Some(vec![0xab, 0xcd]),
+        constructor_value: U256::from(1),
+        init_value: U256::from(2),
+        ..CreateXCall::new(
+            salt(),
+            Target::Create3 {
+                init_code: vec![0x60, 0x00],
+            },
+        )
+    };
+
+    let expected = [
+        "00d84acb".to_string(),
+        "11".repeat(32),
+        word("a0"),
+        word("e0"),
+        word("1"),
+        word("2"),
+        word("2"),
+        format!("{:0<64}", "6000"),
+        word("2"),
+        format!("{:0<64}", "abcd"),
+    ]
+    .concat();
+    assert_eq!(hex::encode(call.calldata()), expected);
+    assert_eq!(call.value(), U256::from(3));
+}
+
+#[rstest]
+fn test_deploy_create2_and_init_refund_calldata() {
+    let call = CreateXCall {
+        init_data: Some(vec![]),
+        refund: Some([0x22; 20]),
+        ..CreateXCall::new(
+            salt(),
+            Target::Create2 {
+                init_code: vec![0x60, 0x00],
+            },
+        )
+    };
+
+    let expected = [
+        "a7db93f2".to_string(),
+        "11".repeat(32),
+        word("c0"),
+        word("100"),
+        word("0"),
+        word("0"),
+        word(&"22".repeat(20)),
+        word("2"),
+        format!("{:0<64}", "6000"),
+        word("0"),
+    ]
+    .concat();
+    assert_eq!(hex::encode(call.calldata()), expected);
+}
+
+#[rstest]
+fn test_deploy_create2_clone_calldata() {
+    let call = CreateXCall {
+        init_data: Some(vec![0x81, 0x29, 0xfc, 0x1c]),
+        ..CreateXCall::new(
+            salt(),
+            Target::Create2Clone {
+                implementation: [0x22; 20],
+            },
+        )
+    };
+
+    let expected = [
+        "2852527a".to_string(),
+        "11".repeat(32),
+        word(&"22".repeat(20)),
+        word("60"),
+        word("4"),
+        format!("{:0<64}", "8129fc1c"),
+    ]
+    .concat();
+    assert_eq!(hex::encode(call.calldata()), expected);
+    assert_eq!(
+        hex::encode(clone_init_code(&[0x22; 20])),
+        format!(
+            "3d602d80600a3d3981f3363d3d373d3d3d363d73{}5af43d82803e903d91602b57fd5bf3",
+            "22".repeat(20)
+        )
+    );
+}
+
+#[rstest]
+fn test_resolve_mined_salt() {
+    let init_code = vec![0x60, 0x00];
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::CreateX)
+        .calling_address(CALLER)
+        .chain_id(10)
+        .init_code_hash(hex::encode(web3::signing::keccak256(&init_code)))
+        .build()
+        .unwrap();
+    let salt = config.salt(&[0xab; 11]);
+    let address = config.derive_address(&salt);
+
+    let call = CreateXCall::new(salt, Target::Create2 { init_code });
+    let salt_variant = call
+        .resolve_salt_variant(&config.factory_address, &address, Some(10))
+        .unwrap();
+    assert!(matches!(salt_variant, SaltVariant::CrosschainSender { .. }));
+
+    let script = call.foundry_script(&config.factory_address, &address, &salt_variant);
+    assert!(script.contains(&format!("vm.startBroadcast({CALLER});")));
+    assert!(script.contains("require(block.chainid == 10"));
+
+    // the chain id is needed to recompute the address
+    let err = call
+        .resolve_salt_variant(&config.factory_address, &address, None)
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidExport(_)));
+}
+
+#[rstest]
+fn test_cast_command() {
+    let call = CreateXCall {
+        constructor_value: parse_amount("value", "0x10").unwrap(),
+        ..CreateXCall::new(salt(), Target::Create3 { init_code: vec![] })
+    };
+    let factory = hex::decode(CREATEX).unwrap().try_into().unwrap();
+
+    assert_eq!(
+        call.cast_command(&factory),
+        format!(
+            "cast send {CREATEX} 0x{} --value 16",
+            hex::encode(call.calldata())
+        )
+    );
+    assert!(parse_amount("value", "1e18").is_err());
+}
+
+#[rstest]
+fn test_record_round_trip() {
+    let line = format!(
+        "0x{},0x{},{},0.3.0_12,abcd,,hash,leading_zeroes>=4294967296",
+        "11".repeat(32),
+        "22".repeat(20),
+        CREATEX.to_lowercase()
+    );
+    let record: ResultRecord = line.parse().unwrap();
+
+    assert_eq!(record.init_code_hash, None);
+    assert_eq!(record.job_id, "abcd");
+    assert_eq!(record.acceptance, "leading_zeroes>=4294967296");
+    assert_eq!(record.to_string(), line);
+
+    // records from before the acceptance rule was added
+    let unscored: ResultRecord = line.rsplit_once(',').unwrap().0.parse().unwrap();
+    assert_eq!(unscored.init_code_source, "hash");
+    assert_eq!(unscored.acceptance, "");
+
+    // records from before the job columns were added
+    let legacy: ResultRecord = line
+        .splitn(5, ',')
+        .take(4)
+        .collect::<Vec<_>>()
+        .join(",")
+        .parse()
+        .unwrap();
+    assert_eq!(legacy.init_code_source, "");
+
+    assert!(matches!(
+        "0x11,0x22".parse::<ResultRecord>(),
+        Err(CrunchError::InvalidRecord(_))
+    ));
+}
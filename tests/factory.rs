@@ -0,0 +1,229 @@
+use alloy_primitives::hex;
+use createxcrunch::{
+    factory::{
+        create2_address, create3_address, create_address, FactoryKind, PROXY_CHILD_CODEHASH,
+    },
+    parse_address, parse_nonce_range, Config, ConfigBuilder, CreateXVariant, CrunchError,
+    SaltVariant, DEFAULT_FACTORY,
+};
+use rstest::*;
+use web3::signing::keccak256;
+
+const CALLER: &str = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5";
+
+#[rstest]
+fn test_proxy_codehash() {
+    let proxy = hex::decode("0x67363d3d37363d34f03d5260086018f3").unwrap();
+    assert_eq!(keccak256(&proxy), PROXY_CHILD_CODEHASH);
+}
+
+#[rstest]
+fn test_create2_address() {
+    // example 1 from EIP-1014
+    let address = create2_address(&[0u8; 20], &[0u8; 32], &keccak256(&[0]));
+    assert_eq!(
+        hex::encode(address),
+        "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"
+    );
+}
+
+#[rstest]
+fn test_create3_address() {
+    let deployer = [0x11u8; 20];
+    let salt = [0x22u8; 32];
+    let proxy = create2_address(&deployer, &salt, &PROXY_CHILD_CODEHASH);
+
+    let mut rlp = vec![0xd6, 0x94];
+    rlp.extend(proxy);
+    rlp.push(0x01);
+    assert_eq!(
+        create3_address(&deployer, &salt, &PROXY_CHILD_CODEHASH)[..],
+        keccak256(&rlp)[12..]
+    );
+}
+
+#[rstest]
+#[case(0, "cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d")]
+#[case(1, "343c43a37d37dff08ae8c4a11544c718abb4fcf8")]
+#[case(2, "f778b86fa74e846c4f0a1fbd1335fe81c00a0c91")]
+#[case(3, "fffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c")]
+fn test_create_address(#[case] nonce: u64, #[case] expected: &str) {
+    let deployer = hex::decode("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+    let address = create_address(&deployer.try_into().unwrap(), nonce);
+    assert_eq!(hex::encode(address), expected);
+}
+
+#[rstest]
+#[case(0x7f, vec![0x7f])]
+#[case(0x80, vec![0x81, 0x80])]
+#[case(0x0100, vec![0x82, 0x01, 0x00])]
+#[case(u64::MAX, vec![0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])]
+fn test_create_address_rlp(#[case] nonce: u64, #[case] encoded: Vec<u8>) {
+    let deployer = [0x11u8; 20];
+    let mut rlp = vec![0xc0 + 21 + encoded.len() as u8, 0x94];
+    rlp.extend(deployer);
+    rlp.extend(encoded);
+    assert_eq!(create_address(&deployer, nonce)[..], keccak256(&rlp)[12..]);
+}
+
+#[rstest]
+fn test_create_config() {
+    let config = ConfigBuilder::new()
+        .deployer(CALLER)
+        .nonce_range(parse_nonce_range("5..=9").unwrap())
+        .build()
+        .unwrap();
+    let CreateXVariant::Create {
+        deployer,
+        ref nonce_range,
+    } = config.create_variant
+    else {
+        panic!("expected a CREATE deployment");
+    };
+    assert_eq!(*nonce_range, 5..10);
+    assert_eq!(
+        config.derive_address(&Config::nonce_salt(7)),
+        create_address(&deployer, 7)
+    );
+
+    // CreateX deploys from its own nonce by default
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::CreateX)
+        .nonce_range(0..1)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        config.create_variant,
+        CreateXVariant::Create { deployer, .. } if deployer == config.factory_address
+    ));
+
+    assert_eq!(parse_nonce_range("42").unwrap(), 42..43);
+    assert!(parse_nonce_range("0..=18446744073709551615").is_err());
+    let err = build_err(ConfigBuilder::new().nonce_range(3..3));
+    assert_eq!(err.exit_code(), 18);
+    let err = build_err(
+        ConfigBuilder::new()
+            .nonce_range(0..1)
+            .init_code_hash(format!("0x{}", "00".repeat(32))),
+    );
+    assert!(matches!(err, CrunchError::InvalidNonceRange(_)));
+    let err = build_err(
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::CreateX)
+            .calling_address(CALLER)
+            .nonce_range(0..1),
+    );
+    assert!(matches!(err, CrunchError::UnsupportedFactory(_)));
+}
+
+#[rstest]
+fn test_factory_kind_from_address() {
+    let config = ConfigBuilder::new()
+        .factory_address("0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed")
+        .calling_address(CALLER)
+        .build()
+        .unwrap();
+    assert_eq!(config.factory_kind, FactoryKind::CreateX);
+
+    // the default factory and unknown addresses guard the salt like CreateX
+    let config = ConfigBuilder::new()
+        .calling_address(CALLER)
+        .chain_id(1)
+        .build()
+        .unwrap();
+    assert_eq!(config.factory_kind, FactoryKind::CreateX);
+    assert_eq!(
+        config.factory_address,
+        parse_address("factory", DEFAULT_FACTORY).unwrap()
+    );
+    let config = ConfigBuilder::new()
+        .factory_address(CALLER)
+        .build()
+        .unwrap();
+    assert_eq!(config.factory_kind, FactoryKind::CreateX);
+
+    // other kinds are opt-in
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::Plain)
+        .build()
+        .unwrap();
+    assert_eq!(config.factory_kind, FactoryKind::Plain);
+}
+
+#[rstest]
+fn test_guard_salt() {
+    let mined = [0xabu8; 11];
+
+    // CreateX hashes a random salt on its own
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::CreateX)
+        .build()
+        .unwrap();
+    let salt = config.salt(&mined);
+    assert_eq!(salt[..11], mined);
+    assert_eq!(
+        FactoryKind::CreateX.guard_salt(&config.salt_variant, &salt),
+        keccak256(&salt)
+    );
+
+    // ZeframLou's factory hashes the caller and the salt packed together
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::ZeframLou)
+        .calling_address(CALLER)
+        .build()
+        .unwrap();
+    let SaltVariant::Sender { calling_address } = config.salt_variant else {
+        panic!("expected a sender salt");
+    };
+    let salt = config.salt(&mined);
+    let preimage: Vec<u8> = calling_address.iter().chain(&salt).copied().collect();
+    assert_eq!(
+        FactoryKind::ZeframLou.guard_salt(&config.salt_variant, &salt),
+        keccak256(&preimage)
+    );
+
+    // the immutable factory only accepts salts starting with the caller or zeros
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::Immutable)
+        .init_code_hash(format!("0x{}", "00".repeat(32)))
+        .build()
+        .unwrap();
+    let salt = config.salt(&mined);
+    assert_eq!(salt[..21], [0u8; 21]);
+    assert_eq!(salt[21..], mined);
+}
+
+#[rstest]
+fn test_unsupported_factory() {
+    let hash = format!("0x{}", "00".repeat(32));
+
+    // Solady has no canonical deployment
+    let err = build_err(ConfigBuilder::new().factory_kind(FactoryKind::Solady));
+    assert_eq!(err.exit_code(), 15);
+
+    let err = build_err(
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::ZeframLou)
+            .calling_address(CALLER)
+            .init_code_hash(&hash),
+    );
+    assert!(matches!(err, CrunchError::UnsupportedFactory(_)));
+
+    let err = build_err(
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Arachnid)
+            .init_code_hash(&hash)
+            .chain_id(1),
+    );
+    assert!(matches!(err, CrunchError::UnsupportedFactory(_)));
+
+    let err = build_err(ConfigBuilder::new().factory_kind(FactoryKind::Safe));
+    assert!(matches!(err, CrunchError::UnsupportedFactory(_)));
+}
+
+fn build_err(builder: ConfigBuilder) -> CrunchError {
+    match builder.build() {
+        Ok(_) => panic!("expected the factory to reject the config"),
+        Err(e) => e,
+    }
+}
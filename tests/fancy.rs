@@ -0,0 +1,208 @@
+use createxcrunch::{
+    fancy::FancyPredicate,
+    hexspeak::DictionarySpec,
+    mk_kernel_src,
+    score::{AddressForms, FancyScoreCategory, Scorer, ScorerRegistry, ScoringSpec, Thresholds},
+    ConfigBuilder, RewardVariant,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rstest::*;
+use web3::types::Address;
+
+/// Random addresses, most of them shaped to score high in one of the categories.
+fn addresses(count: usize) -> impl Iterator<Item = [u8; 20]> {
+    let mut rng = StdRng::seed_from_u64(36);
+    (0..count).map(move |i| {
+        let mut address: [u8; 20] = rng.gen();
+        match i % 10 {
+            0 => address[..rng.gen_range(0..12)].fill(0),
+            1 => address
+                .iter_mut()
+                .for_each(|b| *b = *b % 10 * 16 + *b / 16 % 10),
+            2 => {
+                let letter = rng.gen_range(10..16u8) * 17;
+                address[..rng.gen_range(0..8)].fill(letter);
+            }
+            3 => {
+                let digit = rng.gen_range(0..10u8);
+                for b in address.iter_mut() {
+                    if *b >> 4 < 10 {
+                        *b = digit << 4 | *b & 0xf;
+                    }
+                    if *b & 0xf < 10 {
+                        *b = *b & 0xf0 | digit;
+                    }
+                }
+            }
+            4 => address
+                .iter_mut()
+                .filter(|_| rng.gen_bool(0.6))
+                .for_each(|b| *b = (*b >> 4) * 17),
+            5 => {
+                address[..4].fill(0);
+                address[16..].fill(0);
+            }
+            6 => address
+                .iter_mut()
+                .for_each(|b| *b = rng.gen_range(0..2) * 0x99),
+            7 | 8 => {
+                // a word at the start, the end or anywhere in the address
+                let word = ["deadbeef", "c0ffee", "ba5ed", "0ddba11", "b055"][rng.gen_range(0..5)];
+                let at = match rng.gen_range(0..3) {
+                    0 => 0,
+                    1 => 40 - word.len(),
+                    _ => rng.gen_range(0..=40 - word.len()),
+                };
+                let mut nibbles: Vec<u8> = address.iter().flat_map(|b| [b >> 4, b & 15]).collect();
+                for (j, c) in word.chars().enumerate() {
+                    nibbles[at + j] = c.to_digit(16).unwrap() as u8;
+                }
+                for (j, b) in address.iter_mut().enumerate() {
+                    *b = nibbles[2 * j] << 4 | nibbles[2 * j + 1];
+                }
+            }
+            _ => {}
+        }
+        address
+    })
+}
+
+fn registries() -> Vec<(f64, ScorerRegistry)> {
+    let spec = |disabled: &[&str], weights: &[(&str, f64)]| {
+        ScoringSpec {
+            disabled: disabled.iter().map(|name| name.to_string()).collect(),
+            weights: weights
+                .iter()
+                .map(|(name, weight)| (name.to_string(), *weight))
+                .collect(),
+            ..Default::default()
+        }
+        .registry()
+        .unwrap()
+    };
+    vec![
+        (16.0f64.powi(8), ScorerRegistry::default()),
+        (16.0f64.powi(4), spec(&["random"], &[])),
+        (
+            16.0f64.powi(3),
+            spec(
+                &["random", "leading_any"],
+                &[("leading_letters", 0.5), ("numbers_only", 20.0)],
+            ),
+        ),
+        (1e6, spec(&["random"], &[("numbers_only", 10.0)])),
+        (
+            16.0f64.powi(4),
+            ScoringSpec {
+                disabled: vec!["random".to_string()],
+                dictionary: Some(DictionarySpec {
+                    words: ["coffee", "based", "oddball", "boss", "a"]
+                        .map(String::from)
+                        .to_vec(),
+                    file: None,
+                }),
+                ..Default::default()
+            }
+            .registry()
+            .unwrap(),
+        ),
+    ]
+}
+
+#[rstest]
+fn test_definitions_score_like_categories() {
+    let forms = AddressForms::new(
+        &"0x000000000000000000000000000000000000dEaD"
+            .parse()
+            .unwrap(),
+    );
+    let (score, difficulty) = FancyScoreCategory::LeadingZeroes.score(&forms);
+    assert_eq!(score, 36.0);
+    assert_eq!(difficulty, 16.0f64.powi(36));
+    // every category is checked by the kernel
+    assert!(FancyScoreCategory::ALL.iter().all(|category| {
+        Scorer::definition(category).is_some() != category.dictionary().is_some()
+    }));
+}
+
+/// Checks that the predicate accepts exactly the addresses the scorer does, for `count`
+/// addresses in every registry.
+fn check_predicate(count: usize) {
+    for (min_difficulty, registry) in registries() {
+        let thresholds = Thresholds {
+            min_difficulty,
+            ..Default::default()
+        };
+        let predicate = FancyPredicate::new(&registry, &thresholds);
+        for address in addresses(count) {
+            let score = registry.score(Address::from_slice(&address), &thresholds);
+            assert_eq!(
+                predicate.accepts(&address),
+                score.accepted_by.is_some(),
+                "0x{} scores {} in {}",
+                alloy_primitives::hex::encode(address),
+                score.total_score,
+                score.category
+            );
+        }
+    }
+}
+
+#[rstest]
+fn test_predicate_matches_scorer() {
+    check_predicate(1 << 15);
+}
+
+/// Too slow for debug builds, run with `cargo test --release --test fancy -- --ignored`.
+#[rstest]
+#[ignore]
+fn test_predicate_matches_scorer_exhaustive() {
+    check_predicate(1 << 22);
+}
+
+#[rstest]
+fn test_predicate_source() {
+    let mut registry = ScorerRegistry::default();
+    registry.disable("random").unwrap();
+    let config = ConfigBuilder::new()
+        .reward(RewardVariant::Fancy)
+        .scoring(registry)
+        .build()
+        .unwrap();
+    let src = mk_kernel_src(&config);
+    assert!(src.contains("#define SUCCESS_CONDITION() isFancy(digest)"));
+    let predicate = FancyPredicate::new(&config.scoring, &config.thresholds);
+    assert_eq!(
+        predicate.checksummed(),
+        ["short_leading_any", "leading_letters"]
+    );
+    assert!(src.contains("checksum(n, c)"));
+
+    // the checksum is only computed for categories depending on it, or able to accept addresses
+    let mut registry = ScorerRegistry::default();
+    for name in ["random", "short_leading_any", "leading_letters"] {
+        registry.disable(name).unwrap();
+    }
+    let unreachable = Thresholds {
+        category_minimums: [
+            ("short_leading_any".to_string(), 1e100),
+            ("leading_letters".to_string(), 1e100),
+        ]
+        .into(),
+        ..Default::default()
+    };
+    for (registry, thresholds) in [
+        (registry, Thresholds::default()),
+        (ScorerRegistry::default(), unreachable),
+    ] {
+        let predicate = FancyPredicate::new(&registry, &thresholds);
+        assert!(predicate.checksummed().is_empty());
+        let config = ConfigBuilder::new()
+            .reward(RewardVariant::Fancy)
+            .scoring(registry)
+            .thresholds(thresholds)
+            .build()
+            .unwrap();
+        assert!(!mk_kernel_src(&config).contains("checksum(n, c)"));
+    }
+}
@@ -0,0 +1,133 @@
+use createxcrunch::{
+    gas::{difficulty, min_gas_per_reference, GasScore, MAX_GAS},
+    job::Job,
+    ConfigBuilder, CrunchError, RewardVariant,
+};
+use rstest::*;
+
+fn address(hex: &str) -> [u8; 20] {
+    alloy_primitives::hex::decode(hex)
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+#[rstest]
+// no zero byte
+#[case("88c6c46ebf353a52bdbab708c23d0c810a1b2c3d", 0, 0, 0)]
+// 2 zero bytes in the middle, 12 gas each
+#[case("88c6c46ebf353a52bd0000708c23d0c810a1b2c3", 2, 0, 24)]
+// 3 leading zero bytes dropped altogether, and another one saving 12 gas
+#[case("00000088c6c46ebf353a52bdbab700c23d0c810a", 4, 3, 60)]
+#[case("0000000000000000000000000000000000000000", 20, 20, MAX_GAS)]
+fn test_gas_score(
+    #[case] hex: &str,
+    #[case] zero_bytes: u8,
+    #[case] leading_zero_bytes: u8,
+    #[case] gas: u64,
+) {
+    let score = GasScore::new(&address(hex));
+    assert_eq!(score.zero_bytes, zero_bytes);
+    assert_eq!(score.leading_zero_bytes, leading_zero_bytes);
+    assert_eq!(score.gas, gas);
+    assert_eq!(score.saved(1000), gas * 1000);
+}
+
+#[rstest]
+fn test_gas_report() {
+    let score = GasScore::new(&address("00000088c6c46ebf353a52bdbab700c23d0c810a"));
+    assert_eq!(
+        score.to_string(),
+        "60 gas per reference (4 zero bytes, 3 leading)"
+    );
+}
+
+#[rstest]
+#[case(60, 1, 60)]
+#[case(100000, 1000, 100)]
+#[case(1001, 1000, 2)]
+fn test_min_gas_per_reference(
+    #[case] min_gas: u64,
+    #[case] references: u64,
+    #[case] expected: u64,
+) {
+    assert_eq!(
+        min_gas_per_reference(min_gas, references).unwrap(),
+        expected
+    );
+}
+
+#[rstest]
+#[case(0, 1)]
+#[case(60, 0)]
+#[case(MAX_GAS + 1, 1)]
+fn test_invalid_gas(#[case] min_gas: u64, #[case] references: u64) {
+    let err = ConfigBuilder::new()
+        .reward(RewardVariant::Gas {
+            min_gas,
+            references,
+        })
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidGas(_)));
+    assert_eq!(err.exit_code(), 24);
+}
+
+#[rstest]
+fn test_gas_difficulty() {
+    // any zero byte saves 12 gas
+    let total_zeros = RewardVariant::TotalZeros { zeros_threshold: 1 };
+    assert!((difficulty(12) / total_zeros.difficulty().unwrap() - 1.0).abs() < 1e-9);
+    // only an address of zero bytes saves the most
+    assert!((difficulty(MAX_GAS) / 256f64.powi(20) - 1.0).abs() < 1e-9);
+    // a leading zero byte saves 16 gas, as does any pair of zero bytes
+    assert!(difficulty(16) < 256.0);
+    assert!(difficulty(16) > difficulty(12));
+}
+
+#[rstest]
+#[case("00000088c6c46ebf353a52bdbab700c23d0c810a", 1, 60, true)]
+#[case("00000088c6c46ebf353a52bdbab700c23d0c810a", 1, 61, false)]
+#[case("00000088c6c46ebf353a52bdbab700c23d0c810a", 1000, 60000, true)]
+fn test_gas_reward(
+    #[case] hex: &str,
+    #[case] references: u64,
+    #[case] min_gas: u64,
+    #[case] expected: bool,
+) {
+    let address = address(hex);
+    let config = ConfigBuilder::new()
+        .reward(RewardVariant::Gas {
+            min_gas,
+            references,
+        })
+        .build()
+        .unwrap();
+    assert_eq!(config.reward.matches(&address), expected);
+    // gas criteria are recorded regardless of the scoring categories
+    let score = config.score(&address);
+    // and carry the gas the address saves
+    assert_eq!(
+        config.acceptance(&address, &score),
+        expected.then(|| {
+            format!(
+                "gas>={min_gas};saved={};per_reference=60;zero_bytes=4;leading_zero_bytes=3",
+                60 * references
+            )
+        })
+    );
+}
+
+#[rstest]
+fn test_gas_job() {
+    let job = Job::from_toml("[reward]\nkind = \"gas\"\nmin_gas = 60\n").unwrap();
+    assert_eq!(
+        job.reward,
+        Some(RewardVariant::Gas {
+            min_gas: 60,
+            references: 1
+        })
+    );
+    assert_eq!(job, Job::from_toml(&job.to_toml()).unwrap());
+}
@@ -0,0 +1,98 @@
+use alloy_primitives::hex;
+use createxcrunch::{
+    init_code::{ConstructorArgs, InitCode, InitCodeSource},
+    job::Job,
+    CreateXVariant, CrunchError,
+};
+use rstest::*;
+use std::path::PathBuf;
+
+fn write_temp(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("createxcrunch_{}_{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[rstest]
+fn test_constructor_args() {
+    let args = ConstructorArgs {
+        signature: "constructor(address,uint256)".to_string(),
+        values: vec![
+            "0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A".to_string(),
+            "42".to_string(),
+        ],
+    };
+
+    assert_eq!(
+        hex::encode(args.encode().unwrap()),
+        "00000000000000000000000088c6c46ebf353a52bdbab708c23d0c81daa8134a\
+         000000000000000000000000000000000000000000000000000000000000002a"
+    );
+
+    let init_code = InitCode::from_hex("0x6000")
+        .unwrap()
+        .with_constructor_args(&args)
+        .unwrap();
+    assert_eq!(init_code.bytecode.len(), 2 + 64);
+    assert_eq!(init_code.describe(), "bytecode+constructor_args");
+
+    let args = ConstructorArgs {
+        signature: "constructor(address)".to_string(),
+        values: vec!["42".to_string(), "43".to_string()],
+    };
+    assert!(matches!(
+        args.encode(),
+        Err(CrunchError::InvalidConstructorArgs(_))
+    ));
+}
+
+#[rstest]
+fn test_artifacts() {
+    let foundry = write_temp(
+        "foundry.json",
+        r#"{"abi": [], "bytecode": {"object": "0x00", "linkReferences": {}}}"#,
+    );
+    let hardhat = write_temp(
+        "hardhat.json",
+        r#"{"_format": "hh-sol-artifact-1", "abi": [], "bytecode": "0x00"}"#,
+    );
+    let unlinked = write_temp(
+        "unlinked.json",
+        r#"{"bytecode": "0x73__$2a8f3ea2d1cd2e7d8a0c8d4f5e8b9c1a2b$__63"}"#,
+    );
+
+    let init_code = InitCode::from_artifact(&foundry).unwrap();
+    assert_eq!(init_code.bytecode, vec![0u8]);
+    assert_eq!(init_code.source, InitCodeSource::Artifact(foundry.clone()));
+    // keccak256(0x00)
+    assert_eq!(
+        hex::encode(init_code.hash()),
+        "bc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a"
+    );
+    assert_eq!(
+        InitCode::from_artifact(&hardhat).unwrap().bytecode,
+        vec![0u8]
+    );
+    assert!(matches!(
+        InitCode::from_artifact(&unlinked),
+        Err(CrunchError::InvalidArtifact(_))
+    ));
+
+    let job = Job {
+        artifact: Some(hardhat.display().to_string()),
+        ..Default::default()
+    };
+    let config = job.configs().unwrap().remove(0);
+    assert!(matches!(
+        config.create_variant,
+        CreateXVariant::Create2 { init_code_hash } if init_code_hash == init_code.hash()
+    ));
+    assert_eq!(
+        config.init_code_source,
+        Some(format!("artifact:{}", hardhat.display()))
+    );
+
+    for path in [foundry, hardhat, unlinked] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
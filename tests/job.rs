@@ -0,0 +1,113 @@
+use createxcrunch::{
+    job::{CreateKind, Job},
+    CreateXVariant, CrunchError, RewardVariant, SaltVariant,
+};
+use rstest::*;
+
+const JOB: &str = r#"
+factory = "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed"
+caller = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5"
+chain_id = 1
+create = "create2"
+init_code = "0x00"
+devices = [0, 1]
+
+[reward]
+kind = "leading_zeros"
+zeros_threshold = 4
+"#;
+
+#[rstest]
+fn test_job_round_trip() {
+    let job = Job::from_toml(JOB).unwrap();
+
+    assert_eq!(job, Job::from_toml(&job.to_toml()).unwrap());
+    assert_eq!(job, Job::from_json(&job.to_json()).unwrap());
+    assert_eq!(job.id(), Job::from_toml(&job.to_toml()).unwrap().id());
+}
+
+#[rstest]
+#[case("kind = \"leading_nibbles\"\ncount = 9\nnibble = 0", RewardVariant::LeadingNibbles { count: 9, nibble: Some(0) })]
+#[case("kind = \"trailing_nibbles\"\ncount = 6", RewardVariant::TrailingNibbles { count: 6, nibble: None })]
+fn test_job_nibble_reward(#[case] reward: &str, #[case] expected: RewardVariant) {
+    let job = Job::from_toml(&JOB.replace("kind = \"leading_zeros\"\nzeros_threshold = 4", reward))
+        .unwrap();
+
+    assert_eq!(job.reward, Some(expected));
+    assert_eq!(job, Job::from_toml(&job.to_toml()).unwrap());
+    assert_eq!(job, Job::from_json(&job.to_json()).unwrap());
+}
+
+#[rstest]
+fn test_job_configs() {
+    let job = Job::from_toml(JOB).unwrap();
+    let configs = job.configs().unwrap();
+
+    assert_eq!(configs.len(), 2);
+    assert_eq!(configs[1].gpu_device, 1);
+    assert_eq!(configs[0].job_id, Some(job.id()));
+    assert!(matches!(
+        configs[0].salt_variant,
+        SaltVariant::CrosschainSender { .. }
+    ));
+    assert_eq!(
+        configs[0].reward,
+        RewardVariant::LeadingZeros { zeros_threshold: 4 }
+    );
+    // keccak256(0x00)
+    let CreateXVariant::Create2 { init_code_hash } = configs[0].create_variant else {
+        panic!("expected a create2 job");
+    };
+    assert_eq!(init_code_hash[..4], [0xbc, 0x36, 0x78, 0x9e],);
+}
+
+#[rstest]
+fn test_job_merge() {
+    let job = Job::from_toml(JOB).unwrap().merge(Job {
+        chain_id: Some(10),
+        init_code_hash: Some(format!("0x{}", "11".repeat(32))),
+        devices: vec![3],
+        ..Default::default()
+    });
+
+    assert_eq!(job.chain_id, Some(10));
+    assert_eq!(job.init_code, None);
+    assert_eq!(job.devices, vec![3]);
+    assert_eq!(job.create, Some(CreateKind::Create2));
+    assert_ne!(job.id(), Job::from_toml(JOB).unwrap().id());
+}
+
+#[rstest]
+fn test_invalid_job() {
+    let err = Job::from_toml("factory = 1").unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidJob(_)));
+
+    let job = Job {
+        create: Some(CreateKind::Create2),
+        ..Default::default()
+    };
+    assert!(matches!(job.configs(), Err(CrunchError::InvalidJob(_))));
+
+    let job = Job {
+        create: Some(CreateKind::Create),
+        ..Default::default()
+    };
+    assert!(matches!(job.configs(), Err(CrunchError::InvalidJob(_))));
+
+    let job = Job {
+        create: Some(CreateKind::Create),
+        nonces: Some("0..10".to_string()),
+        init_code: Some("0x6000".to_string()),
+        ..Default::default()
+    };
+    assert!(matches!(job.configs(), Err(CrunchError::InvalidJob(_))));
+
+    let job = Job {
+        reward: Some(RewardVariant::TotalZeros { zeros_threshold: 0 }),
+        ..Default::default()
+    };
+    assert!(matches!(
+        job.configs(),
+        Err(CrunchError::InvalidThreshold { value: 0, .. })
+    ));
+}
@@ -0,0 +1,188 @@
+use createxcrunch::{
+    factory::FactoryKind,
+    job::{CreateKind, Job},
+    keyspace::{DeviceShare, Keyspace, MAX_WORK_SIZE},
+    Config, ConfigBuilder, CrunchError,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use rstest::*;
+use std::collections::HashSet;
+
+fn plain(layout: &str, work_size: usize) -> (Keyspace, Config) {
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::Plain)
+        .salt_layout(layout.parse().unwrap())
+        .work_size(work_size)
+        .build()
+        .unwrap();
+    (Keyspace::new(&config), config)
+}
+
+#[rstest]
+fn test_keyspace_rollover() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let (mut keyspace, _) = plain("random:4,counter:5", 10);
+    assert_eq!(keyspace.launches_per_message(), 256);
+    assert_eq!(keyspace.size(), None);
+
+    let first = keyspace.next(&mut rng).unwrap();
+    assert_eq!(first.index, 0);
+    assert_eq!(first.message.len(), 4);
+    for index in 1..256 {
+        let launch = keyspace.next(&mut rng).unwrap();
+        assert_eq!(launch.index, index);
+        assert_eq!(launch.message, first.message);
+    }
+    assert_eq!(
+        keyspace.to_string(),
+        format!(
+            "message {} launches 0..=255 of 256",
+            alloy_primitives::hex::encode(&first.message)
+        )
+    );
+
+    // the launch bytes of the counter are used up, the next launch starts a new message
+    let launch = keyspace.next(&mut rng).unwrap();
+    assert_eq!(launch.index, 0);
+    assert_ne!(launch.message, first.message);
+    assert_eq!(keyspace.covered(), 257 * 10);
+    assert_eq!(
+        keyspace.to_string(),
+        format!(
+            "message {} launches 0..=0 of 256, 1 messages covered",
+            alloy_primitives::hex::encode(&launch.message)
+        )
+    );
+}
+
+#[rstest]
+fn test_keyspace_exhausted() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let (mut keyspace, config) = plain("fixed:0xc0ffee,counter:5", 4);
+    assert_eq!(keyspace.size(), Some(256 * 4));
+
+    // every salt of the keyspace is handed out exactly once
+    let mut salts = HashSet::new();
+    while let Some(launch) = keyspace.next(&mut rng) {
+        assert_eq!(launch.kernel_message(), [0]);
+        for work_item in 0..4 {
+            assert!(salts.insert(config.launch_salt(&launch.message, launch.index, work_item)));
+        }
+    }
+    assert_eq!(salts.len(), 256 * 4);
+    assert_eq!(keyspace.covered(), 256 * 4);
+    assert_eq!(keyspace.to_string(), "launches 0..=255 of 256");
+    assert!(keyspace.next(&mut rng).is_none());
+}
+
+#[rstest]
+fn test_keyspace_single_launch() {
+    let mut rng = StdRng::seed_from_u64(1);
+    // a counter of 4 bytes only enumerates the work items, every launch takes a new message
+    let (mut keyspace, _) = plain("random:7,counter:4", 10);
+    let first = keyspace.next(&mut rng).unwrap();
+    let second = keyspace.next(&mut rng).unwrap();
+    assert_eq!((first.index, second.index), (0, 0));
+    assert_ne!(first.message, second.message);
+    assert!(keyspace
+        .to_string()
+        .ends_with("launches 0..=0 of 1, 1 messages covered"));
+
+    let (mut keyspace, _) = plain("counter:4", 10);
+    assert!(keyspace.next(&mut rng).is_some());
+    assert!(keyspace.next(&mut rng).is_none());
+}
+
+#[rstest]
+fn test_keyspace_device_share() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let job = Job {
+        factory_kind: Some(FactoryKind::Plain),
+        salt_layout: Some("fixed:0xc0ffee,counter:5".to_string()),
+        work_size: Some(4),
+        devices: vec![0, 1, 2],
+        ..Default::default()
+    };
+    let configs = job.configs().unwrap();
+
+    // the devices split the launches of the keyspace between them, no salt is hashed twice
+    let mut salts = HashSet::new();
+    for (config, expected) in configs.iter().zip([86, 85, 85]) {
+        let mut keyspace = Keyspace::new(config);
+        assert_eq!(keyspace.size(), Some(expected * 4));
+        while let Some(launch) = keyspace.next(&mut rng) {
+            for work_item in 0..4 {
+                assert!(salts.insert(config.launch_salt(&launch.message, launch.index, work_item)));
+            }
+        }
+        assert_eq!(keyspace.covered(), expected * 4);
+    }
+    assert_eq!(salts.len(), 256 * 4);
+    let mut keyspace = Keyspace::new(&configs[1]);
+    keyspace.next(&mut rng);
+    keyspace.next(&mut rng);
+    assert_eq!(keyspace.to_string(), "launches 1..=4 step 3 of 256");
+
+    // random bytes keep the messages of the devices apart, each takes every launch of its own
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::Plain)
+        .salt_layout("random:4,counter:5".parse().unwrap())
+        .device_share(1, 2)
+        .build()
+        .unwrap();
+    let mut keyspace = Keyspace::new(&config);
+    assert_eq!(keyspace.size(), None);
+    assert_eq!(keyspace.next(&mut rng).unwrap().index, 0);
+    assert_eq!(keyspace.next(&mut rng).unwrap().index, 1);
+
+    let err = ConfigBuilder::new()
+        .device_share(2, 2)
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidJob(_)));
+}
+
+#[rstest]
+fn test_nonce_batches_device_share() {
+    let job = Job {
+        create: Some(CreateKind::Create),
+        nonces: Some("0..10".to_string()),
+        work_size: Some(3),
+        devices: vec![0, 1],
+        ..Default::default()
+    };
+    let configs = job.configs().unwrap();
+
+    // the 4 batches of 3 nonces are split between the devices, each scanned once
+    let shares: Vec<Vec<u64>> = configs
+        .iter()
+        .map(|config| config.device_share.batches(4).collect())
+        .collect();
+    assert_eq!(shares, [[0, 2], [1, 3]]);
+    assert_eq!(DeviceShare::default().batches(4).count(), 4);
+}
+
+#[rstest]
+fn test_work_size_limit() {
+    let build = |work_size: u64| {
+        ConfigBuilder::new()
+            .work_size(work_size as usize)
+            .build()
+            .map(|_| ())
+    };
+    assert!(build(MAX_WORK_SIZE).is_ok());
+    for work_size in [0, MAX_WORK_SIZE + 1] {
+        let err = build(work_size).unwrap_err();
+        assert!(matches!(err, CrunchError::InvalidWorkSize(_)));
+        assert_eq!(err.exit_code(), 27);
+    }
+
+    // CREATE nonces are not enumerated by the salt counter
+    let config = ConfigBuilder::new()
+        .nonce_range(0..u64::MAX)
+        .work_size(MAX_WORK_SIZE as usize * 2)
+        .build()
+        .map(|_| ());
+    assert!(config.is_ok());
+}
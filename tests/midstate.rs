@@ -0,0 +1,122 @@
+use createxcrunch::{
+    factory::FactoryKind,
+    midstate::{self, Midstate},
+    mk_kernel_src,
+    preimage::SaltPreimage,
+    ConfigBuilder,
+};
+use rand::{thread_rng, Rng};
+use rstest::*;
+use web3::signing::keccak256;
+
+const CALLER: &str = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5";
+const INIT_CODE_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+#[rstest]
+fn test_midstate_hash() {
+    let preimage: Vec<u8> = (0..100).collect();
+    let midstate = Midstate::new(&preimage, 30..50);
+    assert_eq!(midstate.hash(&preimage[30..50]), keccak256(&preimage));
+    // the bytes of the preimage in the varying range are left out
+    let mut zeroed = preimage.clone();
+    zeroed[30..50].fill(0);
+    assert_eq!(midstate, Midstate::new(&zeroed, 30..50));
+}
+
+#[rstest]
+fn test_midstate_padding() {
+    let midstate = midstate::create3();
+    let lanes = midstate.lanes();
+    // rlp([proxy, 1]) is 0xd6 0x94 <proxy> 0x01, padded with 0x01 and a final 0x80
+    assert_eq!(lanes[0], 0x94d6);
+    assert_eq!(lanes[2], 0x0101 << 48);
+    assert_eq!(lanes[16], 0x80 << 56);
+    assert!(lanes[3..16]
+        .iter()
+        .chain(&lanes[17..])
+        .all(|&lane| lane == 0));
+}
+
+#[rstest]
+fn test_midstates_match_cpu() {
+    let builders = [
+        ConfigBuilder::new().factory_kind(FactoryKind::Plain),
+        ConfigBuilder::new().factory_kind(FactoryKind::CreateX),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::CreateX)
+            .calling_address(CALLER)
+            .chain_id(1),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Arachnid)
+            .init_code_hash(INIT_CODE_HASH),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Immutable)
+            .calling_address(CALLER)
+            .init_code_hash(INIT_CODE_HASH),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Solady)
+            .factory_address(CALLER),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::ZeframLou)
+            .calling_address(CALLER),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Plain)
+            .init_code_hash(INIT_CODE_HASH)
+            .salt_preimage(SaltPreimage::Kernel {
+                data: vec![0xab; 200],
+            }),
+    ];
+
+    let mut rng = thread_rng();
+    for builder in builders {
+        for init_code_hash in [None, Some(INIT_CODE_HASH)] {
+            let mut builder = builder.clone();
+            if let Some(init_code_hash) = init_code_hash {
+                builder = builder.init_code_hash(init_code_hash);
+            }
+            let Ok(config) = builder.build() else {
+                continue;
+            };
+            for _ in 0..8 {
+                let mined: [u8; 11] = rng.gen();
+                let salt = config.salt(&mined);
+                assert_eq!(
+                    midstate::derive_address(&config, &salt),
+                    Some(config.derive_address(&salt)),
+                    "{}",
+                    config.factory_kind
+                );
+            }
+        }
+    }
+}
+
+#[rstest]
+#[case(ConfigBuilder::new().factory_kind(FactoryKind::Plain), 0..11)]
+#[case(ConfigBuilder::new().factory_kind(FactoryKind::CreateX).calling_address(CALLER), 0..32)]
+fn test_varying_salt_bytes(
+    #[case] builder: ConfigBuilder,
+    #[case] expected: std::ops::Range<usize>,
+) {
+    let config = builder.build().unwrap();
+    let midstate = midstate::create2(&config).unwrap();
+    assert_eq!(midstate.varying, expected.start + 21..expected.end + 21);
+
+    let src = mk_kernel_src(&config);
+    assert!(src.contains("__constant ulong MIDSTATE[25] = {"));
+    assert!(src.contains(&format!("#define SALT_VARYING_START {}", expected.start)));
+    assert!(src.contains(&format!("#define SALT_VARYING_END {}", expected.end)));
+    assert!(!src.contains("#define S2_1 "));
+}
+
+#[rstest]
+fn test_create_has_no_midstate() {
+    let config = ConfigBuilder::new()
+        .deployer(CALLER)
+        .nonce_range(0..10)
+        .build()
+        .unwrap();
+    assert_eq!(midstate::create2(&config), None);
+    assert_eq!(midstate::derive_address(&config, &[0u8; 32]), None);
+    assert!(!mk_kernel_src(&config).contains("__constant ulong MIDSTATE"));
+}
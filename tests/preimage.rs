@@ -0,0 +1,167 @@
+mod common;
+
+use alloy_primitives::hex;
+use common::word;
+use createxcrunch::{
+    factory::{create2_address, FactoryKind},
+    job::Job,
+    preimage::{AccountFactory, PreimageSpec, SaltPreimage},
+    ConfigBuilder, CreateXVariant, CrunchError,
+};
+use rstest::*;
+use web3::signing::keccak256;
+
+const OWNER: &str = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5";
+
+fn spec(account: AccountFactory) -> PreimageSpec {
+    PreimageSpec {
+        account: Some(account),
+        owners: vec![OWNER.to_string()],
+        ..Default::default()
+    }
+}
+
+#[rstest]
+#[case(SaltPreimage::SafeProxy { initializer: vec![0xb6, 0x3e, 0x80, 0x0d] })]
+#[case(SaltPreimage::LightAccount { owner: [0x34; 20] })]
+#[case(SaltPreimage::Kernel { data: vec![0xab; 200] })]
+#[case(SaltPreimage::CoinbaseSmartWallet { owners: vec![vec![0x11; 32], vec![0x22; 64]] })]
+fn test_template_matches_reference(#[case] salt_preimage: SaltPreimage) {
+    let salt = keccak256(b"salt");
+    let (mut template, offset) = salt_preimage.template().unwrap();
+    template[offset..offset + 32].copy_from_slice(&salt);
+    assert_eq!(keccak256(&template), salt_preimage.hash(&salt));
+}
+
+#[rstest]
+fn test_custom_preimage() {
+    let salt = keccak256(b"salt");
+    let salt_preimage = SaltPreimage::Custom {
+        template: vec![0x33; 64],
+        offset: 16,
+    };
+    let expected = keccak256(&[&[0x33; 16], &salt[..], &[0x33; 16]].concat());
+    assert_eq!(salt_preimage.hash(&salt), expected);
+
+    let (mut template, offset) = salt_preimage.template().unwrap();
+    template[offset..offset + 32].copy_from_slice(&salt);
+    assert_eq!(keccak256(&template), expected);
+}
+
+#[rstest]
+fn test_simple_account_preimage() {
+    // SimpleAccountFactory deploys with the salt as is
+    let salt_preimage = PreimageSpec {
+        account: Some(AccountFactory::SimpleAccount),
+        ..Default::default()
+    }
+    .resolve()
+    .unwrap();
+    assert_eq!(salt_preimage, SaltPreimage::SimpleAccount);
+    assert_eq!(salt_preimage.template(), None);
+    let salt = keccak256(b"salt");
+    assert_eq!(salt_preimage.hash(&salt), salt);
+
+    // the owner is part of the init code instead
+    let err = spec(AccountFactory::SimpleAccount).resolve().unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidSaltPreimage(_)));
+
+    // the kernel hashes no preimage, and the salt is kept a small uint256
+    let init_code_hash = [0x01; 32];
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::Plain)
+        .init_code_hash(hex::encode(init_code_hash))
+        .salt_preimage(salt_preimage)
+        .build()
+        .unwrap();
+    assert_eq!(config.guard_preimage(), None);
+    let salt = config.salt(&[0xab; 11]);
+    assert_eq!(salt[..21], [0u8; 21]);
+    assert_eq!(
+        config.derive_address(&salt),
+        create2_address(&config.factory_address, &salt, &init_code_hash)
+    );
+}
+
+#[rstest]
+fn test_coinbase_smart_wallet_preimage() {
+    let salt_preimage = spec(AccountFactory::CoinbaseSmartWallet).resolve().unwrap();
+    let (template, offset) = salt_preimage.template().unwrap();
+
+    // abi.encode(owners, nonce) with a single address owner
+    let expected = [
+        word("40"),
+        word("0"),
+        word("1"),
+        word("20"),
+        word("20"),
+        word(&OWNER[2..].to_lowercase()),
+    ]
+    .concat();
+    assert_eq!(hex::encode(template), expected);
+    assert_eq!(offset, 32);
+}
+
+#[rstest]
+fn test_account_job() {
+    let job = Job::from_toml(
+        r#"
+        factory_kind = "plain"
+        init_code_hash = "0x0000000000000000000000000000000000000000000000000000000000000001"
+
+        [salt_preimage]
+        account = "light_account"
+        owners = ["0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5"]
+        "#,
+    )
+    .unwrap();
+    let config = job.configs().unwrap().remove(0);
+    let CreateXVariant::Create2 { init_code_hash } = config.create_variant else {
+        panic!("expected a CREATE2 deployment");
+    };
+
+    let salt = config.salt(&[0xab; 11]);
+    assert_eq!(salt[..21], [0u8; 21]);
+    let preimage: Vec<u8> = hex::decode(OWNER)
+        .unwrap()
+        .into_iter()
+        .chain(salt)
+        .collect();
+    assert_eq!(
+        config.derive_address(&salt),
+        create2_address(
+            &config.factory_address,
+            &keccak256(&preimage),
+            &init_code_hash
+        )
+    );
+}
+
+#[rstest]
+fn test_invalid_preimage() {
+    let err = PreimageSpec {
+        owners: vec![],
+        ..spec(AccountFactory::CoinbaseSmartWallet)
+    }
+    .resolve()
+    .unwrap_err();
+    assert_eq!(err.exit_code(), 20);
+
+    let err = PreimageSpec {
+        data: Some("0x00".to_string()),
+        ..spec(AccountFactory::LightAccount)
+    }
+    .resolve()
+    .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidSaltPreimage(_)));
+
+    // the salt must fit in the template
+    let err = PreimageSpec {
+        template: Some(format!("0x{}", "00".repeat(40))),
+        offset: Some(10),
+        ..Default::default()
+    }
+    .resolve()
+    .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidSaltPreimage(_)));
+}
@@ -0,0 +1,117 @@
+use createxcrunch::{
+    price::{parse_hashrate, result_addresses, to_csv, to_json, Pricing},
+    score::{ScorerRegistry, Thresholds},
+    CrunchError,
+};
+use rstest::*;
+
+/// 10 leading zeros, a difficulty of 16^10.
+const ZEROS: [u8; 20] = [
+    0, 0, 0, 0, 0, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d,
+    0x0c, 0x81,
+];
+
+const PLAIN: [u8; 20] = [
+    0x88, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d, 0x0c, 0x81,
+    0x0a, 0x1b, 0x2c, 0x3d,
+];
+
+#[rstest]
+fn test_valuation() {
+    let pricing = Pricing {
+        hashrate: 2e9,
+        base_price: 50.0,
+    };
+    let valuations = pricing.valuations(
+        &ScorerRegistry::default(),
+        &Thresholds::default(),
+        &[ZEROS, PLAIN],
+    );
+
+    let zeros = &valuations[0];
+    assert_eq!(zeros.address, "0x0000000000c6c46EBF353a52bdBAb708C23D0c81");
+    assert_eq!(zeros.category, "leading_zeroes");
+    assert_eq!(zeros.score, 10.0);
+    assert_eq!(zeros.difficulty, 16.0f64.powi(10));
+    assert_eq!(zeros.gpu_hours, 16.0f64.powi(10) / 2e9 / 3600.0);
+    // 16 times the base difficulty
+    assert_eq!(zeros.price_multiplier, 16.0);
+    assert_eq!(zeros.price, 800.0);
+
+    // addresses below the base difficulty are priced at the base price
+    assert_eq!(valuations[1].price, 50.0);
+
+    let thresholds = Thresholds {
+        base_difficulty: 16.0f64.powi(8),
+        ..Default::default()
+    };
+    let valuation = &pricing.valuations(&ScorerRegistry::default(), &thresholds, &[ZEROS])[0];
+    assert_eq!(valuation.price, 50.0 * 256.0);
+}
+
+#[rstest]
+fn test_report_formats() {
+    let valuations =
+        Pricing::default().valuations(&ScorerRegistry::default(), &Thresholds::default(), &[ZEROS]);
+    let csv = to_csv(&valuations);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "address,category,score,difficulty,gpu_hours,price_multiplier,price"
+    );
+    assert!(lines[1].starts_with("0x0000000000c6c46EBF353a52bdBAb708C23D0c81,leading_zeroes,10,"));
+
+    let json: serde_json::Value = serde_json::from_str(&to_json(&valuations)).unwrap();
+    assert_eq!(json[0]["category"], "leading_zeroes");
+    assert_eq!(json[0]["price_multiplier"], 16.0);
+}
+
+#[rstest]
+fn test_result_addresses() {
+    let dir = std::env::temp_dir().join("createxcrunch-test-price");
+    std::fs::create_dir_all(&dir).unwrap();
+    let record = |address: &str| {
+        format!(
+            "0x{},0x{address},0x{},0.3.0_0,abcd,,hash,leading_zeroes>=4294967296\n",
+            "11".repeat(32),
+            "22".repeat(20)
+        )
+    };
+    let zeros = hex(&ZEROS);
+    std::fs::write(
+        dir.join(format!("addr_{zeros}.csv")),
+        record(&zeros).repeat(2),
+    )
+    .unwrap();
+    std::fs::write(dir.join("addr_8888.csv"), record(&"88".repeat(20))).unwrap();
+    std::fs::write(dir.join("job_abcd.toml"), "chain_id = 1\n").unwrap();
+
+    assert_eq!(result_addresses(&dir).unwrap(), vec![ZEROS, [0x88; 20]]);
+}
+
+fn hex(address: &[u8; 20]) -> String {
+    address.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[rstest]
+#[case("1000000000", Some(1e9))]
+#[case("2.5G", Some(2.5e9))]
+#[case("800MH/s", Some(8e8))]
+#[case("1T", Some(1e12))]
+#[case("0", None)]
+#[case("fast", None)]
+fn test_parse_hashrate(#[case] value: &str, #[case] expected: Option<f64>) {
+    assert_eq!(parse_hashrate(value).ok(), expected);
+}
+
+#[rstest]
+fn test_invalid_pricing() {
+    let err = Pricing {
+        base_price: -1.0,
+        ..Default::default()
+    }
+    .validate()
+    .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidPricing(_)));
+    assert_eq!(err.exit_code(), 23);
+}
@@ -0,0 +1,202 @@
+use createxcrunch::{
+    job::Job,
+    record::ResultRecord,
+    results::{to_csv, ResultStore, StoredResult, STORE_FILE},
+    Config, ConfigBuilder, CrunchError,
+};
+use rstest::*;
+use std::path::{Path, PathBuf};
+
+const PLAIN: [u8; 20] = [
+    0x88, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d, 0x0c, 0x81,
+    0x0a, 0x1b, 0x2c, 0x3d,
+];
+
+/// An address of `zeros` leading zero nibbles, ranked in the leading zeroes category.
+fn zeros(zeros: usize) -> [u8; 20] {
+    let mut address = PLAIN;
+    for nibble in 0..zeros {
+        address[nibble / 2] &= if nibble % 2 == 0 { 0x0f } else { 0xf0 };
+    }
+    address
+}
+
+fn record(address: [u8; 20], job_id: &str) -> ResultRecord {
+    ResultRecord {
+        salt: [0x11; 32],
+        address,
+        factory: [0x22; 20],
+        miner: "0.3.0_0".to_string(),
+        job_id: job_id.to_string(),
+        init_code_hash: None,
+        init_code_source: "hash".to_string(),
+        acceptance: "leading_zeroes>=4294967296".to_string(),
+    }
+}
+
+fn config() -> Config {
+    ConfigBuilder::new().build().unwrap()
+}
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-test-results-{name}"));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_records(dir: &Path, file: &str, records: &[ResultRecord]) {
+    let contents: String = records.iter().map(|record| format!("{record}\n")).collect();
+    std::fs::write(dir.join(file), contents).unwrap();
+}
+
+fn addresses(results: &[&StoredResult]) -> Vec<[u8; 20]> {
+    results.iter().map(|result| result.record.address).collect()
+}
+
+#[rstest]
+fn test_deduplication() {
+    let config = config();
+    let first = dir("first");
+    let second = dir("second");
+    // rediscovered addresses are appended to their file again
+    write_records(
+        &first,
+        &format!("addr_{}.csv", alloy_primitives::hex::encode(zeros(8))),
+        &[record(zeros(8), "first"), record(zeros(8), "again")],
+    );
+    write_records(&first, "job_abcd.toml", &[]);
+    write_records(&second, STORE_FILE, &[record(zeros(8), "second")]);
+    write_records(
+        &second,
+        &format!("addr_{}.csv", alloy_primitives::hex::encode(PLAIN)),
+        &[record(PLAIN, "second")],
+    );
+
+    let mut store = ResultStore::new();
+    assert_eq!(store.load(&config, &first).unwrap(), 1);
+    assert_eq!(store.load(&config, &second).unwrap(), 1);
+    assert_eq!(store.len(), 2);
+    // the first record of an address is kept
+    assert_eq!(store.ranked()[0].record.job_id, "first");
+}
+
+#[rstest]
+fn test_ranking() {
+    let config = config();
+    let mut store = ResultStore::new();
+    for address in [zeros(6), PLAIN, zeros(10), zeros(8)] {
+        assert!(store.insert(&config, record(address, "abcd")));
+    }
+    assert!(!store.insert(&config, record(zeros(8), "abcd")));
+
+    let ranked = store.ranked();
+    assert_eq!(ranked[0].category(), "leading_zeroes");
+    assert_eq!(ranked[0].difficulty(), 16f64.powi(10));
+    assert_ne!(ranked[3].category(), "leading_zeroes");
+    assert_eq!(
+        addresses(&ranked),
+        vec![zeros(10), zeros(8), zeros(6), PLAIN]
+    );
+    assert_eq!(addresses(&store.top(2)), vec![zeros(10), zeros(8)]);
+    // the plain address is the best of its own category
+    assert_eq!(
+        addresses(&store.top_per_category(1)),
+        vec![zeros(10), PLAIN]
+    );
+
+    assert_eq!(store.prune(2), 1);
+    assert!(!store.contains(&zeros(6)));
+    assert_eq!(addresses(&store.ranked()), vec![zeros(10), zeros(8), PLAIN]);
+}
+
+#[rstest]
+fn test_save() {
+    let config = config();
+    let dir = dir("save");
+    let mut store = ResultStore::new();
+    for address in [PLAIN, zeros(10)] {
+        store.insert(&config, record(address, "abcd"));
+    }
+    let path = dir.join(STORE_FILE);
+    store.save(&path).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        contents.lines().next().unwrap(),
+        record(zeros(10), "abcd").to_string()
+    );
+    let mut loaded = ResultStore::new();
+    assert_eq!(loaded.load(&config, &dir).unwrap(), 2);
+    assert_eq!(addresses(&loaded.ranked()), addresses(&store.ranked()));
+}
+
+#[rstest]
+fn test_compact() {
+    let config = config();
+    let dir = dir("compact");
+    for address in [PLAIN, zeros(8), zeros(10)] {
+        let name = format!("addr_{}.csv", alloy_primitives::hex::encode(address));
+        write_records(&dir, &name, &[record(address, "abcd")]);
+    }
+    let mut store = ResultStore::new();
+    assert_eq!(store.load(&config, &dir).unwrap(), 3);
+    assert_eq!(store.prune(1), 1);
+
+    // a search still running writes another address after the store was loaded
+    let unseen = format!("addr_{}.csv", alloy_primitives::hex::encode(zeros(9)));
+    write_records(&dir, &unseen, &[record(zeros(9), "abcd")]);
+    assert_eq!(store.compact(&dir).unwrap(), 3);
+    assert!(dir.join(&unseen).is_file());
+
+    // the pruned address does not come back from its file
+    let mut loaded = ResultStore::new();
+    loaded.load(&config, &dir).unwrap();
+    assert_eq!(
+        addresses(&loaded.ranked()),
+        vec![zeros(10), zeros(9), PLAIN]
+    );
+}
+
+#[rstest]
+fn test_invalid_results_file() {
+    let dir = dir("invalid");
+    std::fs::write(dir.join(STORE_FILE), "0x11,0x22\n").unwrap();
+    let err = ResultStore::new().load(&config(), &dir).unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidRecord(_)));
+}
+
+#[rstest]
+fn test_results_csv() {
+    let config = config();
+    let mut store = ResultStore::new();
+    store.insert(&config, record(zeros(10), "abcd"));
+    let csv = to_csv(&store.ranked());
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "category,difficulty,salt,address,factory,miner,job_id,init_code_hash,init_code_source,acceptance"
+    );
+    assert_eq!(
+        lines[1],
+        format!(
+            "leading_zeroes,{},{}",
+            16f64.powi(10),
+            record(zeros(10), "abcd")
+        )
+    );
+}
+
+#[rstest]
+fn test_keep_top() {
+    let job = Job::from_toml("keep_top = 5").unwrap();
+    assert_eq!(job, Job::from_toml(&job.to_toml()).unwrap());
+    assert_eq!(job.configs().unwrap()[0].keep_top, Some(5));
+
+    let err = ConfigBuilder::new()
+        .keep_top(0)
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidJob(_)));
+}
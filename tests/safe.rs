@@ -0,0 +1,113 @@
+mod common;
+
+use alloy_primitives::hex;
+use common::word;
+use createxcrunch::{
+    factory::{create2_address, FactoryKind},
+    job::{CreateKind, Job},
+    safe::{setup_calldata, SafeSetup},
+    CreateXVariant, CrunchError,
+};
+use rstest::*;
+use web3::signing::keccak256;
+
+const OWNER: &str = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5";
+const SINGLETON: &str = "0x41675C099F32341bf84BFc5382aF534df5C7461a";
+
+fn setup() -> SafeSetup {
+    SafeSetup {
+        singleton: Some(SINGLETON.to_string()),
+        proxy_code: Some("0x6080".to_string()),
+        owners: vec![OWNER.to_string()],
+        ..Default::default()
+    }
+}
+
+#[rstest]
+fn test_setup_calldata() {
+    let owner = hex::decode(OWNER).unwrap().try_into().unwrap();
+    let calldata = setup_calldata(&[owner], 1, &[0x22; 20]).unwrap();
+
+    let expected = [
+        "b63e800d".to_string(),
+        word("100"),
+        word("1"),
+        word("0"),
+        word("140"),
+        word(&"22".repeat(20)),
+        word("0"),
+        word("0"),
+        word("0"),
+        word("1"),
+        word(&OWNER[2..].to_lowercase()),
+        word("0"),
+    ]
+    .concat();
+    assert_eq!(hex::encode(calldata), expected);
+
+    let err = setup_calldata(&[owner], 2, &[0u8; 20]).unwrap_err();
+    assert_eq!(err.exit_code(), 19);
+}
+
+#[rstest]
+fn test_safe_proxy_address() {
+    let job = Job {
+        create: Some(CreateKind::Create2),
+        safe: Some(setup()),
+        ..Default::default()
+    };
+    let config = job.configs().unwrap().remove(0);
+    assert_eq!(config.factory_kind, FactoryKind::SafeProxy);
+
+    // createProxyWithNonce deploys the creation code followed by the singleton
+    let init_code = hex::decode(format!("6080{}", word(&SINGLETON[2..]))).unwrap();
+    let CreateXVariant::Create2 { init_code_hash } = config.create_variant else {
+        panic!("expected a CREATE2 deployment");
+    };
+    assert_eq!(init_code_hash, keccak256(&init_code));
+
+    let salt_nonce = config.salt(&[0xab; 11]);
+    assert_eq!(salt_nonce[..21], [0u8; 21]);
+    let initializer = setup().initializer().unwrap();
+    let preimage: Vec<u8> = keccak256(&initializer)
+        .into_iter()
+        .chain(salt_nonce)
+        .collect();
+    assert_eq!(
+        config.derive_address(&salt_nonce),
+        create2_address(
+            &config.factory_address,
+            &keccak256(&preimage),
+            &init_code_hash
+        )
+    );
+}
+
+#[rstest]
+fn test_invalid_safe_setup() {
+    let safe = SafeSetup {
+        initializer: Some("0x".to_string()),
+        ..setup()
+    };
+    assert!(matches!(
+        safe.initializer(),
+        Err(CrunchError::InvalidSafeSetup(_))
+    ));
+
+    let safe = SafeSetup {
+        proxy_code: None,
+        ..setup()
+    };
+    assert!(matches!(
+        safe.init_code(),
+        Err(CrunchError::InvalidSafeSetup(_))
+    ));
+
+    let job = Job {
+        create: Some(CreateKind::Create2),
+        init_code: Some("0x6000".to_string()),
+        safe: Some(setup()),
+        ..Default::default()
+    };
+    assert!(matches!(job.configs(), Err(CrunchError::InvalidJob(_))));
+}
@@ -0,0 +1,184 @@
+use createxcrunch::{
+    factory::FactoryKind,
+    job::Job,
+    midstate, mk_kernel_src,
+    salt_layout::{SaltLayout, SaltSegment},
+    ConfigBuilder, CrunchError,
+};
+use rstest::*;
+
+const CALLER: &str = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5";
+
+#[rstest]
+fn test_salt_layout_parse() {
+    let layout: SaltLayout = "fixed:0xc0ffee, random:2,counter:8".parse().unwrap();
+    assert_eq!(
+        layout.segments(),
+        [
+            SaltSegment::Fixed(vec![0xc0, 0xff, 0xee]),
+            SaltSegment::Random(2),
+            SaltSegment::Counter(8),
+        ]
+    );
+    assert_eq!(layout.len(), 13);
+    assert_eq!(layout.mined_len(), 10);
+    assert_eq!(layout.varying(), 3..13);
+    assert_eq!(layout.to_string(), "fixed:0xc0ffee,random:2,counter:8");
+    assert_eq!(layout, layout.to_string().parse().unwrap());
+
+    assert_eq!(SaltLayout::default().to_string(), "random:4,counter:7");
+}
+
+#[rstest]
+#[case("random:4")]
+#[case("counter:4,counter:4")]
+#[case("counter:3")]
+#[case("counter:13")]
+#[case("random:0,counter:7")]
+#[case("fixed:0xzz,counter:7")]
+#[case("nonce:4,counter:7")]
+#[case("counter")]
+fn test_salt_layout_invalid(#[case] spec: &str) {
+    let err = spec.parse::<SaltLayout>().unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidSaltLayout(_)), "{spec}");
+    assert_eq!(err.exit_code(), 26);
+}
+
+#[rstest]
+fn test_salt_layout_fill() {
+    let layout: SaltLayout = "random:2,fixed:0xaa,counter:6,fixed:0xbb".parse().unwrap();
+    // the counter is little-endian, the work item in its first 4 bytes and the launch after them
+    let mined = layout.mined(&[1, 2], 0x0304 << 32 | 0x05060708);
+    assert_eq!(mined, [1, 2, 8, 7, 6, 5, 4, 3]);
+
+    let mut salt = [0u8; 11];
+    layout.fill(&mut salt, &mined);
+    assert_eq!(salt, [1, 2, 0xaa, 8, 7, 6, 5, 4, 3, 0xbb, 0]);
+    assert_eq!(layout.varying(), 0..9);
+}
+
+#[rstest]
+fn test_launch_salt() {
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::Plain)
+        .salt_layout("fixed:0xc0ffee,random:1,counter:12".parse().unwrap())
+        .build()
+        .unwrap();
+    let salt = config.launch_salt(&[0x99], u64::MAX, 7);
+    let mut expected = [0u8; 32];
+    expected[..4].copy_from_slice(&[0xc0, 0xff, 0xee, 0x99]);
+    expected[4] = 7;
+    expected[8..16].fill(0xff);
+    assert_eq!(salt, expected);
+
+    // the fixed bytes are part of the template the kernel starts from
+    let (template, offset) = config.salt_template();
+    assert_eq!(offset, 0);
+    assert_eq!(template[..4], [0xc0, 0xff, 0xee, 0]);
+}
+
+#[rstest]
+fn test_salt_layout_fits_factory() {
+    // CreateX leaves 11 bytes after the caller and the cross-chain flag
+    let builder = ConfigBuilder::new()
+        .factory_kind(FactoryKind::CreateX)
+        .calling_address(CALLER);
+    let config = builder
+        .clone()
+        .salt_layout("fixed:0x01,random:2,counter:8".parse().unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(config.salt_template().1, 21);
+
+    let err = builder
+        .salt_layout("random:4,counter:8".parse().unwrap())
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidSaltLayout(_)));
+
+    let err = ConfigBuilder::new()
+        .nonce_range(0..10)
+        .salt_layout(SaltLayout::default())
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidSaltLayout(_)));
+}
+
+#[rstest]
+#[case("random:4,counter:7", true)]
+#[case("fixed:0x0000,random:4,counter:7", true)]
+#[case("random:16,counter:4,fixed:0x00", true)]
+// the first 20 bytes would always be the zero address, or an address that may be the caller
+#[case(
+    "fixed:0x0000000000000000000000000000000000000000,random:4,counter:7",
+    false
+)]
+#[case("fixed:0x34a50a7a272e86ee30b7a74e36f3f02af18b1eb5,counter:4", false)]
+// byte 20 would select the cross-chain guard, or make CreateX revert
+#[case("random:16,counter:5", false)]
+#[case("random:16,counter:4,fixed:0x01", false)]
+fn test_salt_layout_createx_random(#[case] layout: &str, #[case] valid: bool) {
+    let build = |factory_kind| {
+        ConfigBuilder::new()
+            .factory_kind(factory_kind)
+            .salt_layout(layout.parse().unwrap())
+            .build()
+            .map(|_| ())
+    };
+    match build(FactoryKind::CreateX) {
+        Ok(()) => assert!(valid, "{layout}"),
+        Err(err) => {
+            assert!(!valid, "{layout}");
+            assert!(matches!(err, CrunchError::InvalidSaltLayout(_)));
+        }
+    }
+    // the salt of a plain factory is used as is
+    assert!(build(FactoryKind::Plain).is_ok());
+}
+
+#[rstest]
+fn test_salt_layout_kernel() {
+    let config = ConfigBuilder::new()
+        .factory_kind(FactoryKind::Plain)
+        .salt_layout("fixed:0xc0ffee,counter:5".parse().unwrap())
+        .build()
+        .unwrap();
+    let src = mk_kernel_src(&config);
+    assert!(src.contains("#define SALT_MINED() {"));
+    assert!(src.contains("salt[3] = (uchar)(gid >> 0);"));
+    assert!(src.contains("salt[6] = (uchar)(gid >> 24);"));
+    assert!(src.contains("salt[7] = (uchar)(launch >> 0);"));
+    assert!(!src.contains("d_message[0]"));
+
+    // only the enumerated bytes vary between work items
+    let midstate = midstate::create2(&config).unwrap();
+    assert_eq!(midstate.varying, 3 + 21..8 + 21);
+}
+
+#[rstest]
+fn test_salt_layout_job() {
+    let job = Job::from_toml(
+        r#"
+factory_kind = "plain"
+salt_layout = "fixed:0xc0ffee,random:2,counter:8"
+"#,
+    )
+    .unwrap();
+    assert_eq!(job, Job::from_toml(&job.to_toml()).unwrap());
+    let config = job.builder().unwrap().build().unwrap();
+    assert_eq!(
+        config.salt_layout.to_string(),
+        "fixed:0xc0ffee,random:2,counter:8"
+    );
+
+    let job = Job {
+        salt_layout: Some("counter:2".to_string()),
+        ..job
+    };
+    assert!(matches!(
+        job.builder().map(|_| ()).unwrap_err(),
+        CrunchError::InvalidSaltLayout(_)
+    ));
+}
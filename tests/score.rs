@@ -0,0 +1,352 @@
+use createxcrunch::{
+    hexspeak::{spell, Dictionary, DictionarySpec},
+    job::Job,
+    score::{
+        parse_category_minimum, parse_difficulty, score_fancy, AddressForms, CustomScorer, Scorer,
+        ScorerRegistry, ScoringSpec, ThresholdSpec, Thresholds, TopScores,
+    },
+    ConfigBuilder, CrunchError,
+};
+use rstest::*;
+use web3::types::Address;
+
+fn address(value: &str) -> Address {
+    value.parse().unwrap()
+}
+
+/// Scores addresses ending in `beef`.
+struct Beef;
+
+impl Scorer for Beef {
+    fn name(&self) -> String {
+        "beef".to_string()
+    }
+
+    fn score(&self, address: &AddressForms) -> (f64, f64) {
+        match address.lower_case.ends_with("beef") {
+            true => (1.0, 16.0f64.powi(4)),
+            false => (0.0, 1.0),
+        }
+    }
+}
+
+#[rstest]
+fn test_builtin_categories() {
+    let score = score_fancy(address("0x0000000088c6c46ebf353a52bdbab708c23d0c81"));
+    assert_eq!(score.category, "leading_zeroes");
+    assert_eq!(score.total_score, 16.0f64.powi(8));
+    assert_eq!(score.scores.len(), 9);
+    assert_eq!(score.scores["leading_zeroes"].score, 8.0);
+    assert_eq!(score.address_short_etherscan, "0x00000000...8C23d0C81");
+}
+
+#[rstest]
+fn test_registry() {
+    let beef = address("0x88c6c46ebf353a52bdbab708c23d0c810000beef");
+    let thresholds = Thresholds::default();
+
+    let mut registry = ScorerRegistry::default();
+    registry.register(Beef).unwrap();
+    let score = registry.score(beef, &thresholds);
+    assert_eq!(score.category, "beef");
+    assert_eq!(score.scores["beef"].contribution, 65536.0);
+
+    // weights scale the difficulty each category contributes
+    registry.set_weight("random", 100.0).unwrap();
+    let score = registry.score(beef, &thresholds);
+    assert_eq!(score.category, "random");
+    assert_eq!(score.total_score, 100000.0);
+    assert_eq!(score.scores["beef"].weight, 1.0);
+
+    registry.disable("random").unwrap();
+    assert_eq!(registry.score(beef, &thresholds).category, "beef");
+    assert!(!registry
+        .score(beef, &thresholds)
+        .scores
+        .contains_key("random"));
+
+    assert!(matches!(
+        registry.register(Beef),
+        Err(CrunchError::InvalidScoring(_))
+    ));
+    assert!(matches!(
+        registry.disable("random"),
+        Err(CrunchError::InvalidScoring(_))
+    ));
+    assert_eq!(
+        registry.set_weight("beef", 0.0).unwrap_err().exit_code(),
+        22
+    );
+}
+
+#[rstest]
+fn test_hex_speak_registration() {
+    // hex-speak words are only scored with a dictionary configured
+    let names = |spec: ScoringSpec| -> Vec<String> { spec.registry().unwrap().names().collect() };
+    assert!(!names(ScoringSpec::default()).contains(&"hex_speak".to_string()));
+    assert!(ScorerRegistry::default().disable("hex_speak").is_err());
+
+    // an empty dictionary table selects the built-in words
+    let builtin = ScoringSpec {
+        dictionary: Some(DictionarySpec::default()),
+        ..Default::default()
+    };
+    assert_eq!(names(builtin.clone()).last().unwrap(), "hex_speak");
+    let score = builtin.registry().unwrap().score(
+        address("0x88c6c46ebf353a52bdbab708c23d0c810000beef"),
+        &Thresholds::default(),
+    );
+    assert_eq!(score.scores["hex_speak"].score, 4.0);
+}
+
+#[rstest]
+#[case(CustomScorer::Words { name: "brand".to_string(), words: vec!["ba5ed".to_string(), "c0ffee".to_string()] }, "0x88c6c46ebf353a52bdbc0ffeec23d0c810000bef", 6.0)]
+#[case(CustomScorer::Words { name: "brand".to_string(), words: vec!["ba5ed".to_string()] }, "0x88c6c46ebf353a52bdbc0ffeec23d0c810000bef", 0.0)]
+#[case(CustomScorer::RepeatedPairs { name: "pairs".to_string() }, "0x88c6c46ebf353a52bdbc0ffeec23d0c8abababab", 4.0)]
+#[case(CustomScorer::Palindrome { name: "palindrome".to_string() }, "0x1234c46ebf353a52bdbc0ffeec23d0c810004321", 4.0)]
+fn test_custom_scorers(#[case] scorer: CustomScorer, #[case] value: &str, #[case] expected: f64) {
+    let (score, difficulty) = scorer.score(&AddressForms::new(&address(value)));
+    assert_eq!(score, expected);
+    assert!(difficulty >= 1.0);
+}
+
+#[rstest]
+fn test_job_scoring() {
+    let job = Job::from_toml(
+        r#"
+        [scoring]
+        disabled = ["random", "snake_score"]
+        weights = { brand = 2.0 }
+
+        [[scoring.custom]]
+        kind = "words"
+        name = "brand"
+        words = ["ba5ed"]
+        "#,
+    )
+    .unwrap();
+    let config = job.configs().unwrap().remove(0);
+    let names: Vec<_> = config.scoring.names().collect();
+    assert_eq!(names.len(), 8);
+    assert!(names.contains(&"brand".to_string()));
+    assert!(!names.contains(&"random".to_string()));
+
+    let score = config.scoring.score(
+        address("0xba5ed6c46ebf353a52bdbab708c23d0c81000be1"),
+        &config.thresholds,
+    );
+    assert_eq!(score.category, "brand");
+    assert_eq!(
+        score.scores["brand"].contribution,
+        2.0 * 16.0f64.powi(5) / 36.0
+    );
+}
+
+#[rstest]
+#[case(ScoringSpec { disabled: vec!["unknown".to_string()], ..Default::default() })]
+#[case(ScoringSpec { custom: vec![CustomScorer::Words { name: "brand".to_string(), words: vec!["coffee".to_string()] }], ..Default::default() })]
+#[case(ScoringSpec { custom: vec![CustomScorer::Palindrome { name: "random".to_string() }], ..Default::default() })]
+#[case(ScoringSpec { custom: vec![CustomScorer::Palindrome { name: "mirror,1".to_string() }], ..Default::default() })]
+#[case(ScoringSpec { custom: vec![CustomScorer::RepeatedPairs { name: "Pairs".to_string() }], ..Default::default() })]
+#[case(ScoringSpec { custom: vec![CustomScorer::RepeatedPairs { name: String::new() }], ..Default::default() })]
+fn test_invalid_scoring(#[case] spec: ScoringSpec) {
+    assert!(matches!(
+        spec.registry(),
+        Err(CrunchError::InvalidScoring(_))
+    ));
+}
+
+#[rstest]
+#[case("coffee", Some("c0ffee"))]
+#[case("Based", Some("ba5ed"))]
+#[case("toilets", Some("7011e75"))]
+#[case("deadbeef", Some("deadbeef"))]
+#[case("gm", None)]
+fn test_spell(#[case] word: &str, #[case] expected: Option<&str>) {
+    assert_eq!(spell(word).as_deref(), expected);
+}
+
+#[rstest]
+#[case("0xc0ffee6ebf353a52bdbab708c23d0c810000bef0", 6.0, 16.0f64.powi(6))]
+#[case("0x88c6c46ebf353a52bdbab708c23d0c810c0ffee0", 6.0, 16.0f64.powi(6) / 35.0)]
+#[case("0x88c6c46ebf353a52bdbab708c23d0c8100ba5ed0", 5.0, 16.0f64.powi(5) / 36.0)]
+#[case("0x88c6c46ebf353a52bdbab708c23d0c81000ba5ed", 5.0, 16.0f64.powi(5))]
+#[case("0x88c6c46ebf353a52bdbab708c23d0c810000bef0", 0.0, 1.0)]
+fn test_hex_speak(#[case] value: &str, #[case] expected_score: f64, #[case] difficulty: f64) {
+    let dictionary = Dictionary::new(["coffee", "based"]).unwrap();
+    assert_eq!(dictionary.words(), ["ba5ed", "c0ffee"]);
+    let (score, expected_difficulty) = dictionary.score(&AddressForms::new(&address(value)));
+    assert_eq!(score, expected_score);
+    assert_eq!(expected_difficulty, difficulty);
+}
+
+#[rstest]
+fn test_dictionary_job() {
+    let path = std::env::temp_dir().join("createxcrunch-test-words.txt");
+    std::fs::write(&path, "facade\nhello\n\noddball\n").unwrap();
+    let job = Job::from_toml(&format!(
+        r#"
+        [scoring.dictionary]
+        words = ["coffee"]
+        file = "{}"
+
+        [scoring.weights]
+        hex_speak = 2.0
+        "#,
+        path.display()
+    ))
+    .unwrap();
+    let spec = job.scoring.unwrap();
+    let dictionary = spec.dictionary.as_ref().unwrap().dictionary().unwrap();
+    assert_eq!(dictionary.words(), ["0ddba11", "c0ffee", "facade"]);
+
+    // the job dictionary registers the hex_speak category, weighted as configured
+    let score = spec.registry().unwrap().score(
+        address("0xfacade6ebf353a52bdbab708c23d0c810000bef0"),
+        &Thresholds::default(),
+    );
+    assert_eq!(score.scores["hex_speak"].score, 6.0);
+    assert_eq!(
+        score.scores["hex_speak"].contribution,
+        2.0 * 16.0f64.powi(6)
+    );
+
+    let err = DictionarySpec {
+        words: vec!["hello".to_string()],
+        file: None,
+    }
+    .dictionary()
+    .unwrap_err();
+    assert!(matches!(err, CrunchError::InvalidScoring(_)));
+}
+
+#[rstest]
+#[case("4294967296", Some(4294967296.0))]
+#[case("16^8", Some(4294967296.0))]
+#[case("1e12", Some(1e12))]
+#[case("0", None)]
+#[case("-16^3", None)]
+#[case("sixteen", None)]
+fn test_parse_difficulty(#[case] value: &str, #[case] expected: Option<f64>) {
+    assert_eq!(parse_difficulty(value).ok(), expected);
+}
+
+#[rstest]
+fn test_category_minimums() {
+    // scores 16^8 in leading_zeroes and 16^4 in hex_speak
+    let address = address("0x0000000088c6c46ebf353a52bdbab708c23dbeef");
+    let registry = ScoringSpec {
+        dictionary: Some(DictionarySpec::default()),
+        ..Default::default()
+    }
+    .registry()
+    .unwrap();
+    let mut thresholds = Thresholds::default();
+    let score = registry.score(address, &thresholds);
+    assert_eq!(score.accepted_by.as_deref(), Some("leading_zeroes"));
+
+    thresholds.min_difficulty = 16.0f64.powi(9);
+    thresholds.category_minimums = [("hex_speak".to_string(), 16.0f64.powi(4))].into();
+    let score = registry.score(address, &thresholds);
+    assert_eq!(score.category, "leading_zeroes");
+    assert_eq!(score.accepted_by.as_deref(), Some("hex_speak"));
+
+    thresholds
+        .category_minimums
+        .insert("hex_speak".to_string(), 1e9);
+    let score = registry.score(address, &thresholds);
+    assert_eq!(score.accepted_by, None);
+    assert_eq!(
+        parse_category_minimum("hex_speak=16^6").unwrap(),
+        ("hex_speak".to_string(), 16.0f64.powi(6))
+    );
+}
+
+#[rstest]
+fn test_job_thresholds() {
+    let job = Job::from_toml(
+        r#"
+        [scoring.dictionary]
+
+        [thresholds]
+        min_difficulty = 1e10
+        categories = { hex_speak = 65536 }
+        "#,
+    )
+    .unwrap()
+    .merge(Job {
+        thresholds: Some(ThresholdSpec {
+            base_difficulty: Some(1e12),
+            categories: [("random".to_string(), 1e20)].into(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    let config = job.configs().unwrap().remove(0);
+    assert_eq!(config.thresholds.min_difficulty, 1e10);
+    assert_eq!(config.thresholds.base_difficulty, 1e12);
+    assert_eq!(config.thresholds.minimum("hex_speak"), 65536.0);
+    assert_eq!(config.thresholds.minimum("random"), 1e20);
+    assert_eq!(config.thresholds.minimum("leading_zeroes"), 1e10);
+
+    let beef = address("0x88c6c46ebf353a52bdbab708c23d0c810000beef");
+    let score = config.score(&beef.0);
+    assert_eq!(
+        config.acceptance(&beef.0, &score).unwrap(),
+        "hex_speak>=65536"
+    );
+}
+
+#[rstest]
+#[case(Thresholds { min_difficulty: 0.0, ..Default::default() })]
+#[case(Thresholds { base_difficulty: f64::NAN, ..Default::default() })]
+#[case(Thresholds { category_minimums: [("unknown".to_string(), 1e9)].into(), ..Default::default() })]
+#[case(Thresholds { category_minimums: [("random".to_string(), -1.0)].into(), ..Default::default() })]
+fn test_invalid_thresholds(#[case] thresholds: Thresholds) {
+    let err = ConfigBuilder::new()
+        .thresholds(thresholds)
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(err.exit_code(), 22);
+}
+
+#[rstest]
+fn test_top_scores() {
+    let addresses = [
+        "0x88c6c46ebf353a52bdbab708c23d0c810a1b2c3d",
+        "0x0000000088c6c46ebf353a52bdbab708c23d0c81",
+        "0x000000000000c46ebf353a52bdbab708c23d0c81",
+        "0x88c6c46ebf353a52bdbab708c23d0c810000beef",
+        "0x00000000000000000000c708c23d0c810000beef",
+    ];
+    let mut top = TopScores::new(2);
+    for value in addresses {
+        top.push(score_fancy(address(value)));
+    }
+    let top: Vec<_> = top
+        .into_sorted()
+        .into_iter()
+        .map(|score| score.address_lower_case)
+        .collect();
+    assert_eq!(top, [addresses[4], addresses[2]]);
+}
+
+#[rstest]
+fn test_score_report() {
+    let score = score_fancy(address("0x0000000088c6c46ebf353a52bdbab708c23d0c81"));
+    let report = score.to_string();
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(
+        lines[0],
+        "0x0000000088c6C46EbF353A52bDBAB708C23d0C81 (0x00000000...8C23d0C81)"
+    );
+    assert!(lines[1].contains("category: leading_zeroes"));
+    assert!(lines[1].contains("accepted by: leading_zeroes"));
+    // a header and a line per category
+    assert_eq!(lines.len(), 3 + score.scores.len());
+
+    let json: serde_json::Value = serde_json::to_value(&score).unwrap();
+    assert_eq!(json["scores"]["leading_zeroes"]["score"], 8.0);
+    assert_eq!(json["price_multiplier"], 1.0);
+}
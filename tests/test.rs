@@ -1,9 +1,14 @@
 use alloy_primitives::hex::{decode, encode};
 use alloy_primitives::FixedBytes;
-use byteorder::{ByteOrder, LittleEndian};
-use createxcrunch::{mk_kernel_src, Config, CreateXVariant, RewardVariant, SaltVariant};
-use itertools::chain;
+use createxcrunch::{
+    factory::FactoryKind,
+    fancy::FancyPredicate,
+    mk_kernel_src,
+    score::{ScoringSpec, Thresholds},
+    Config, ConfigBuilder, CreateXVariant, RewardVariant, SaltVariant,
+};
 use ocl::{Buffer, Context, Device, MemFlags, Platform, ProQue, Program, Queue};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use rstest::*;
 
 #[fixture]
@@ -11,20 +16,43 @@ fn try_nonce(
     #[default(SaltVariant::Random)] salt_variant: SaltVariant,
     #[default(CreateXVariant::Create3)] create_variant: CreateXVariant,
     #[default(RewardVariant::LeadingZeros { zeros_threshold: 1 })] reward: RewardVariant,
-    #[default([0; 1])] nonce: [u32; 1],
+    #[default([0; 1])] nonce: [u64; 1],
 ) -> ocl::Result<String> {
     let config = Config {
         gpu_device: 0,
+        device_share: Default::default(),
+        // These fields will be ignored for tests
+        work_size: 1,
+        result_buffer_size: 1,
+        sleep_for: 0.0,
         // 0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed
         factory_address: [
             186, 94, 208, 153, 99, 61, 59, 49, 62, 77, 95, 123, 220, 19, 5, 211, 194, 139, 165, 237,
         ],
+        factory_kind: FactoryKind::CreateX,
         salt_variant,
         create_variant,
         reward,
-        // This field will be ignored for tests
-        output: "output.txt",
+        // These fields will be ignored for tests
+        output: "output.txt".to_string(),
+        output_dir: "output".to_string(),
+        job_id: None,
+        init_code_source: None,
+        salt_preimage: None,
+        salt_layout: Default::default(),
+        scoring: Default::default(),
+        thresholds: Default::default(),
+        best: None,
+        shared_best: Default::default(),
+        keep_top: None,
+        database: None,
     };
+
+    run_kernel(&config, nonce).map(|(_, address)| address)
+}
+
+/// Runs the kernel for a single work item and returns the salt and address it found.
+fn run_kernel(config: &Config, nonce: [u64; 1]) -> ocl::Result<([u8; 32], String)> {
     // set up a platform to use
     let platform = Platform::new(ocl::core::default_platform()?);
 
@@ -38,7 +66,7 @@ fn try_nonce(
 
     let program = Program::builder()
         .devices(device)
-        .src(mk_kernel_src(&config))
+        .src(mk_kernel_src(config))
         .build(&context)?;
 
     // set up the queue to use
@@ -48,7 +76,7 @@ fn try_nonce(
     let ocl_pq = ProQue::new(context, queue, program, Some(1));
 
     // construct the 4-byte message to hash, leaving last 8 of salt empty
-    let salt = FixedBytes::<4>::try_from(&[0u8; 4]).unwrap();
+    let salt = FixedBytes::<4>::from(&[0u8; 4]);
 
     // build a corresponding buffer for passing the message to the kernel
     let message_buffer = Buffer::builder()
@@ -58,12 +86,6 @@ fn try_nonce(
         .copy_host_slice(&salt[..])
         .build()?;
 
-    // reset nonce & create a buffer to view it in little-endian
-    // for more uniformly distributed nonces, we shall initialize it to a random value
-    let mut view_buf = [0; 8];
-
-    LittleEndian::write_u64(&mut view_buf, (nonce[0] as u64) << 32);
-
     // build a corresponding buffer for passing the nonce to the kernel
     let nonce_buffer = Buffer::builder()
         .queue(ocl_pq.queue().clone())
@@ -85,7 +107,7 @@ fn try_nonce(
     let kern = ocl_pq
         .kernel_builder("hashMessage")
         .arg_named("message", None::<&Buffer<u8>>)
-        .arg_named("nonce", None::<&Buffer<u32>>)
+        .arg_named("nonce", None::<&Buffer<u64>>)
         .arg_named("solutions", None::<&Buffer<u64>>)
         .build()?;
 
@@ -109,19 +131,7 @@ fn try_nonce(
 
     println!("Solution: {:?}", solution);
 
-    let mined_salt = chain!(salt, solution[..7].iter().copied());
-
-    let salt: Vec<u8> = match config.salt_variant {
-        SaltVariant::CrosschainSender {
-            chain_id: _,
-            calling_address,
-        } => chain!(calling_address, [1u8], mined_salt).collect(),
-        SaltVariant::Crosschain { chain_id: _ } => chain!([0u8; 20], [1u8], mined_salt).collect(),
-        SaltVariant::Sender { calling_address } => {
-            chain!(calling_address, [0u8], mined_salt).collect()
-        }
-        SaltVariant::Random => chain!(mined_salt, [0u8; 21]).collect(),
-    };
+    let salt = config.launch_salt(&salt[..], nonce[0], solutions[0] as u32);
 
     println!("Salt: {:?}", salt);
 
@@ -137,7 +147,7 @@ fn try_nonce(
 
     address.insert_str(0, "0x");
 
-    Ok(address)
+    Ok((salt, address))
 }
 
 #[rstest]
@@ -146,7 +156,7 @@ fn test_create3_random() {
         SaltVariant::Random,
         CreateXVariant::Create3,
         RewardVariant::LeadingZeros { zeros_threshold: 1 },
-        [61u32; 1],
+        [61u64; 1],
     )
     .unwrap();
 
@@ -156,7 +166,7 @@ fn test_create3_random() {
         SaltVariant::Random,
         CreateXVariant::Create3,
         RewardVariant::TotalZeros { zeros_threshold: 2 },
-        [357u32; 1],
+        [357u64; 1],
     )
     .unwrap();
 
@@ -169,7 +179,7 @@ fn test_create3_random() {
             leading_zeros_threshold: 1,
             total_zeros_threshold: 2,
         },
-        [61u32; 1],
+        [61u64; 1],
     )
     .unwrap();
 
@@ -182,7 +192,7 @@ fn test_create3_random() {
             leading_zeros_threshold: 5,
             total_zeros_threshold: 2,
         },
-        [357u32; 1],
+        [357u64; 1],
     )
     .unwrap();
 
@@ -196,7 +206,7 @@ fn test_create3_random() {
                 .to_owned()
                 .into_boxed_str(),
         },
-        [87u32; 1],
+        [87u64; 1],
     )
     .unwrap();
 
@@ -211,7 +221,7 @@ fn test_create3_caller() {
         SaltVariant::Sender { calling_address },
         CreateXVariant::Create3,
         RewardVariant::LeadingZeros { zeros_threshold: 1 },
-        [66u32; 1],
+        [66u64; 1],
     )
     .unwrap();
 
@@ -221,7 +231,7 @@ fn test_create3_caller() {
         SaltVariant::Sender { calling_address },
         CreateXVariant::Create3,
         RewardVariant::TotalZeros { zeros_threshold: 2 },
-        [1579u32; 1],
+        [1579u64; 1],
     )
     .unwrap();
 
@@ -234,7 +244,7 @@ fn test_create3_caller() {
             leading_zeros_threshold: 1,
             total_zeros_threshold: 2,
         },
-        [66u32; 1],
+        [66u64; 1],
     )
     .unwrap();
 
@@ -247,7 +257,7 @@ fn test_create3_caller() {
             leading_zeros_threshold: 5,
             total_zeros_threshold: 2,
         },
-        [1579u32; 1],
+        [1579u64; 1],
     )
     .unwrap();
 
@@ -260,7 +270,7 @@ fn test_create3_caller() {
             leading_zeros_threshold: 1,
             total_zeros_threshold: 2,
         },
-        [1579u32; 1],
+        [1579u64; 1],
     )
     .unwrap();
 
@@ -274,7 +284,7 @@ fn test_create3_caller() {
                 .to_owned()
                 .into_boxed_str(),
         },
-        [152u32; 1],
+        [152u64; 1],
     )
     .unwrap();
 
@@ -292,7 +302,7 @@ fn test_create2_crosschain() {
         SaltVariant::Crosschain { chain_id },
         CreateXVariant::Create2 { init_code_hash },
         RewardVariant::LeadingZeros { zeros_threshold: 1 },
-        [126u32; 1],
+        [126u64; 1],
     )
     .unwrap();
 
@@ -302,7 +312,7 @@ fn test_create2_crosschain() {
         SaltVariant::Crosschain { chain_id },
         CreateXVariant::Create2 { init_code_hash },
         RewardVariant::TotalZeros { zeros_threshold: 2 },
-        [746u32; 1],
+        [746u64; 1],
     )
     .unwrap();
 
@@ -315,7 +325,7 @@ fn test_create2_crosschain() {
             leading_zeros_threshold: 1,
             total_zeros_threshold: 2,
         },
-        [126u32; 1],
+        [126u64; 1],
     )
     .unwrap();
 
@@ -328,7 +338,7 @@ fn test_create2_crosschain() {
             leading_zeros_threshold: 5,
             total_zeros_threshold: 2,
         },
-        [746u32; 1],
+        [746u64; 1],
     )
     .unwrap();
 
@@ -341,7 +351,7 @@ fn test_create2_crosschain() {
             leading_zeros_threshold: 1,
             total_zeros_threshold: 2,
         },
-        [2091u32; 1],
+        [2091u64; 1],
     )
     .unwrap();
 
@@ -355,7 +365,7 @@ fn test_create2_crosschain() {
                 .to_owned()
                 .into_boxed_str(),
         },
-        [45u32; 1],
+        [45u64; 1],
     )
     .unwrap();
 
@@ -378,7 +388,7 @@ fn test_create2_crosschain_caller() {
         },
         CreateXVariant::Create2 { init_code_hash },
         RewardVariant::LeadingZeros { zeros_threshold: 1 },
-        [343u32; 1],
+        [343u64; 1],
     )
     .unwrap();
 
@@ -391,7 +401,7 @@ fn test_create2_crosschain_caller() {
         },
         CreateXVariant::Create2 { init_code_hash },
         RewardVariant::TotalZeros { zeros_threshold: 2 },
-        [487u32; 1],
+        [487u64; 1],
     )
     .unwrap();
 
@@ -407,7 +417,7 @@ fn test_create2_crosschain_caller() {
             leading_zeros_threshold: 1,
             total_zeros_threshold: 2,
         },
-        [343u32; 1],
+        [343u64; 1],
     )
     .unwrap();
 
@@ -423,7 +433,7 @@ fn test_create2_crosschain_caller() {
             leading_zeros_threshold: 5,
             total_zeros_threshold: 2,
         },
-        [487u32; 1],
+        [487u64; 1],
     )
     .unwrap();
 
@@ -439,7 +449,7 @@ fn test_create2_crosschain_caller() {
             leading_zeros_threshold: 1,
             total_zeros_threshold: 2,
         },
-        [759u32; 1],
+        [759u64; 1],
     )
     .unwrap();
 
@@ -456,13 +466,162 @@ fn test_create2_crosschain_caller() {
                 .to_owned()
                 .into_boxed_str(),
         },
-        [50u32; 1],
+        [50u64; 1],
     )
     .unwrap();
 
     assert_eq!("0xbbfaecabdd12e01f3a4ce699095ab6dbd1a62b1c", address);
 }
 
+#[rstest]
+fn test_factory_kinds_match_cpu() {
+    let caller = "0x34A50a7A272E86EE30b7A74E36f3f02AF18B1eB5";
+    let init_code_hash = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    let any = RewardVariant::Matching {
+        pattern: "X".repeat(40).into_boxed_str(),
+    };
+
+    let builders = [
+        ConfigBuilder::new().factory_kind(FactoryKind::Plain),
+        ConfigBuilder::new().factory_kind(FactoryKind::CreateX),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::CreateX)
+            .calling_address(caller),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::CreateX)
+            .chain_id(1),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::CreateX)
+            .calling_address(caller)
+            .chain_id(1),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Arachnid)
+            .init_code_hash(init_code_hash),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Safe)
+            .init_code_hash(init_code_hash),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Immutable)
+            .init_code_hash(init_code_hash),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Immutable)
+            .calling_address(caller)
+            .init_code_hash(init_code_hash),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::Solady)
+            .factory_address(caller),
+        ConfigBuilder::new()
+            .factory_kind(FactoryKind::ZeframLou)
+            .calling_address(caller),
+    ];
+
+    for builder in builders {
+        for init_code_hash in [None, Some(init_code_hash)] {
+            let mut builder = builder.clone().reward(any.clone());
+            if let Some(init_code_hash) = init_code_hash {
+                builder = builder.init_code_hash(init_code_hash);
+            }
+            // skip deployments the factory does not support
+            let Ok(config) = builder.build() else {
+                continue;
+            };
+
+            let (salt, address) = run_kernel(&config, [7u64; 1]).unwrap();
+            let expected = format!("0x{}", encode(config.derive_address(&salt)));
+            assert_eq!(expected, address, "{}", config.factory_kind);
+        }
+    }
+}
+
+/// Evaluates `isFancy` on the device for every address.
+fn run_fancy_kernel(config: &Config, addresses: &[u8]) -> ocl::Result<Vec<u8>> {
+    let src = mk_kernel_src(config)
+        + r#"
+__kernel void checkFancy(__global uchar const *addresses, __global uchar *accepted)
+{
+  uchar d[20];
+  for (int i = 0; i < 20; ++i)
+    d[i] = addresses[get_global_id(0) * 20 + i];
+  accepted[get_global_id(0)] = isFancy(d);
+}
+"#;
+    let count = addresses.len() / 20;
+    let ocl_pq = ProQue::builder().src(src).dims(count).build()?;
+    let addresses_buffer = Buffer::builder()
+        .queue(ocl_pq.queue().clone())
+        .flags(MemFlags::new().read_only())
+        .len(addresses.len())
+        .copy_host_slice(addresses)
+        .build()?;
+    let accepted_buffer = ocl_pq.create_buffer::<u8>()?;
+    let kern = ocl_pq
+        .kernel_builder("checkFancy")
+        .arg(&addresses_buffer)
+        .arg(&accepted_buffer)
+        .build()?;
+    unsafe {
+        kern.enq()?;
+    }
+    let mut accepted = vec![0u8; count];
+    accepted_buffer.read(&mut accepted).enq()?;
+    Ok(accepted)
+}
+
+#[rstest]
+fn test_fancy_predicate_matches_cpu() {
+    let mut rng = StdRng::seed_from_u64(36);
+    for (min_difficulty, disabled) in [
+        (16.0f64.powi(8), vec![]),
+        (16.0f64.powi(4), vec!["random".to_string()]),
+        (
+            16.0f64.powi(3),
+            vec!["random".to_string(), "leading_any".to_string()],
+        ),
+    ] {
+        let registry = ScoringSpec {
+            disabled,
+            ..Default::default()
+        }
+        .registry()
+        .unwrap();
+        let thresholds = Thresholds {
+            min_difficulty,
+            ..Default::default()
+        };
+        let config = ConfigBuilder::new()
+            .reward(RewardVariant::Fancy)
+            .scoring(registry.clone())
+            .thresholds(thresholds.clone())
+            .build()
+            .unwrap();
+
+        // random addresses, a quarter of them starting with a run of zeros or letters
+        let addresses: Vec<[u8; 20]> = (0..1 << 20)
+            .map(|i| {
+                let mut address: [u8; 20] = rng.gen();
+                let run = rng.gen_range(0..6);
+                match i % 8 {
+                    0 => address[..run].fill(0),
+                    1 => address[..run].fill(rng.gen_range(10..16u8) * 17),
+                    _ => {}
+                }
+                address
+            })
+            .collect();
+        let accepted = run_fancy_kernel(&config, &addresses.concat()).unwrap();
+
+        let predicate = FancyPredicate::new(&registry, &thresholds);
+        for (address, accepted) in addresses.iter().zip(accepted) {
+            assert_eq!(
+                predicate.accepts(address),
+                accepted == 1,
+                "0x{}",
+                encode(address)
+            );
+        }
+    }
+}
+
 fn string_to_addr_bytes(s: &str) -> [u8; 20] {
     let mut addr = [0u8; 20];
     let s = s.trim_start_matches("0x");
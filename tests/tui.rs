@@ -0,0 +1,141 @@
+use console::Key;
+use createxcrunch::{
+    score::{ScorerRegistry, Thresholds},
+    tui::{format_eta, sparkline, Dashboard},
+};
+use rstest::*;
+use std::time::Duration;
+use web3::types::Address;
+
+/// 10 leading zeros, a difficulty of 16^10.
+const ZEROS: [u8; 20] = [
+    0, 0, 0, 0, 0, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d,
+    0x0c, 0x81,
+];
+
+/// 8 leading zeros.
+const FEWER_ZEROS: [u8; 20] = [
+    0, 0, 0, 0, 0xbf, 0xc6, 0xc4, 0x6e, 0xbf, 0x35, 0x3a, 0x52, 0xbd, 0xba, 0xb7, 0x08, 0xc2, 0x3d,
+    0x0c, 0x81,
+];
+
+fn score(address: &[u8; 20]) -> createxcrunch::score::FancyScore {
+    ScorerRegistry::default().score(Address::from_slice(address), &Thresholds::default())
+}
+
+#[rstest]
+#[case(&[], "")]
+#[case(&[0.0, 0.0], "▁▁")]
+#[case(&[1.0, 2.0, 4.0, 8.0], "▂▃▅█")]
+fn test_sparkline(#[case] samples: &[f64], #[case] expected: &str) {
+    assert_eq!(sparkline(samples), expected);
+}
+
+#[rstest]
+#[case(3.54, "3.5s")]
+#[case(125.0, "2m 05s")]
+#[case(7500.0, "2h 05m")]
+#[case(180000.0, "2d 02h")]
+#[case(f64::INFINITY, "never")]
+fn test_format_eta(#[case] seconds: f64, #[case] expected: &str) {
+    assert_eq!(format_eta(seconds), expected);
+}
+
+#[rstest]
+fn test_batches() {
+    let dashboard = Dashboard::new("abcd", Some(4e9), 0.0);
+    dashboard.add_device(0, "GPU 0");
+    dashboard.batch(
+        0,
+        1_000_000_000,
+        Duration::from_secs(1),
+        "message 0a1b nonce 1",
+    );
+    dashboard.batch(
+        1,
+        500_000_000,
+        Duration::from_secs(1),
+        "message 2c3d nonce 2",
+    );
+    // the hashrate is that of the last batch of each device
+    dashboard.batch(
+        0,
+        2_000_000_000,
+        Duration::from_secs(1),
+        "message 0a1b nonce 2",
+    );
+    assert_eq!(dashboard.hashrate(), 2.5e9);
+    assert_eq!(dashboard.processed(), 3_500_000_000);
+
+    let lines = dashboard.render(200);
+    assert!(lines[0].starts_with("createXcrunch job abcd | running"));
+    assert!(lines.iter().any(|line| line.starts_with("0 GPU 0")
+        && line.contains("2000.0MH/s")
+        && line.contains("message 0a1b nonce 2")
+        && line.ends_with("▅█")));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("Total") && line.contains("2500.0MH/s")));
+    assert!(lines
+        .iter()
+        .any(|line| line == "Next hit expected in 1.6s (1 in 4.0000e9 addresses)"));
+    assert!(dashboard
+        .render(20)
+        .iter()
+        .all(|line| line.chars().count() <= 20));
+}
+
+#[rstest]
+fn test_best_results() {
+    let dashboard = Dashboard::new("abcd", None, 0.0);
+    dashboard.accepted(&score(&FEWER_ZEROS));
+    dashboard.accepted(&score(&ZEROS));
+    dashboard.accepted(&score(&FEWER_ZEROS));
+
+    let best = dashboard.best();
+    assert_eq!(best.len(), 1);
+    assert_eq!(
+        best["leading_zeroes"].address,
+        "0x0000000000c6c46EBF353a52bdBAb708C23D0c81"
+    );
+    assert_eq!(best["leading_zeroes"].difficulty, 16f64.powi(10));
+
+    let lines = dashboard.render(200);
+    assert!(lines.iter().any(|line| line.contains("3 accepted")));
+    assert!(lines.iter().any(|line| line.starts_with("leading_zeroes")
+        && line.ends_with("0x0000000000c6c46EBF353a52bdBAb708C23D0c81")));
+    assert!(lines
+        .iter()
+        .any(|line| line == "Next hit: no difficulty estimate"));
+}
+
+#[rstest]
+fn test_keys() {
+    let dashboard = Dashboard::new("abcd", None, 0.02);
+    assert!(dashboard.handle_key(&Key::Char('p')));
+    assert!(dashboard.is_paused());
+    assert!(dashboard.render(200)[0].contains("PAUSED"));
+    assert!(dashboard.handle_key(&Key::Char('p')));
+    assert!(!dashboard.is_paused());
+
+    dashboard.handle_key(&Key::Char('+'));
+    assert!((dashboard.throttle() - 0.03).abs() < 1e-9);
+    for _ in 0..5 {
+        dashboard.handle_key(&Key::Char('-'));
+    }
+    assert_eq!(dashboard.throttle(), 0.0);
+
+    assert!(!dashboard.handle_key(&Key::Char('q')));
+}
+
+#[rstest]
+fn test_log_pane() {
+    let dashboard = Dashboard::new("abcd", None, 0.0);
+    for i in 0..20 {
+        dashboard.log(format!("line {i}"));
+    }
+    let lines = dashboard.render(200);
+    assert!(!lines.iter().any(|line| line == "line 11"));
+    assert!(lines.iter().any(|line| line == "line 12"));
+    assert!(lines.iter().any(|line| line == "line 19"));
+}